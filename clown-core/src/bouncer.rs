@@ -0,0 +1,56 @@
+use clown_parser::tags::{iter_tags, unescape_tag_value};
+
+/// Network announced by a soju bouncer (soju.im/bouncer-networks)
+/// BOUNCER NETWORK <netid> <attributes>
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BouncerNetwork {
+    pub id: String,
+    pub name: Option<String>,
+    /// The attributes are * when the network has been removed
+    pub is_deleted: bool,
+}
+
+impl BouncerNetwork {
+    /// Parameters following the NETWORK subcommand
+    pub fn parse(parameters: &[String]) -> Option<Self> {
+        let [id, attributes, ..] = parameters else {
+            return None;
+        };
+        let is_deleted = attributes == "*";
+        let name = iter_tags(attributes)
+            .find(|(key, _)| *key == "name")
+            .map(|(_, value)| unescape_tag_value(value).to_string())
+            .filter(|v| !v.is_empty());
+        Some(Self {
+            id: id.to_string(),
+            name,
+            is_deleted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_network() {
+        let parameters = vec![
+            "42".to_string(),
+            "name=Libera\\sChat;host=irc.libera.chat;state=connected".to_string(),
+        ];
+        assert_eq!(
+            BouncerNetwork::parse(&parameters),
+            Some(BouncerNetwork {
+                id: "42".to_string(),
+                name: Some("Libera Chat".to_string()),
+                is_deleted: false
+            })
+        );
+
+        let deleted = BouncerNetwork::parse(&["42".to_string(), "*".to_string()]).unwrap();
+        assert!(deleted.is_deleted);
+        assert_eq!(deleted.name, None);
+        assert!(BouncerNetwork::parse(&["42".to_string()]).is_none());
+    }
+}
@@ -0,0 +1,349 @@
+use crate::command::Command;
+use crate::sts::StsPolicy;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Capabilities requested to the server when advertised
+pub const SUPPORTED_CAPABILITIES: &[&str] = &[
+    "batch",
+    "server-time",
+    "message-tags",
+    "draft/chathistory",
+    "echo-message",
+    "labeled-response",
+    "away-notify",
+    "account-notify",
+    "extended-join",
+    "invite-notify",
+    "chghost",
+    "setname",
+    "userhost-in-names",
+    "multi-prefix",
+    "soju.im/bouncer-networks",
+    "soju.im/bouncer-networks-notify",
+    "znc.in/playback",
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CapSubCommand {
+    Ls,
+    List,
+    Ack,
+    Nak,
+    New,
+    Del,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CapReply<'a> {
+    pub sub_command: CapSubCommand,
+    /// false when the server announces more lines (CAP * LS * :...)
+    pub is_last: bool,
+    capabilities: &'a str,
+}
+
+impl<'a> CapReply<'a> {
+    /// Parse the arguments of a CAP command received from the server
+    /// <client> <subcommand> [*] :<capabilities>
+    pub fn parse(arguments: &'a str) -> Option<Self> {
+        let (parameters, capabilities) = arguments.split_once(" :").unwrap_or((arguments, ""));
+        let mut parameters = parameters.split_ascii_whitespace();
+        let _client = parameters.next()?;
+        let sub_command = match parameters.next()?.to_ascii_uppercase().as_str() {
+            "LS" => CapSubCommand::Ls,
+            "LIST" => CapSubCommand::List,
+            "ACK" => CapSubCommand::Ack,
+            "NAK" => CapSubCommand::Nak,
+            "NEW" => CapSubCommand::New,
+            "DEL" => CapSubCommand::Del,
+            _ => return None,
+        };
+        let is_last = parameters.next() != Some("*");
+
+        Some(Self {
+            sub_command,
+            is_last,
+            capabilities,
+        })
+    }
+
+    /// Capability names with their optional value (sasl=PLAIN,EXTERNAL)
+    pub fn capabilities(&self) -> impl Iterator<Item = (&'a str, Option<&'a str>)> {
+        self.capabilities
+            .split_ascii_whitespace()
+            .map(|cap| match cap.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (cap, None),
+            })
+    }
+}
+
+/// Negotiates the capabilities during the registration and keeps track of the enabled ones
+#[derive(Debug)]
+pub struct CapabilityNegotiation {
+    available: Vec<String>,
+    enabled: Vec<String>,
+    is_registering: bool,
+    sts: Option<StsPolicy>,
+    /// Network of the bouncer to bind to before the end of the registration
+    bouncer_network: Option<String>,
+}
+
+impl Default for CapabilityNegotiation {
+    fn default() -> Self {
+        Self {
+            available: Vec::new(),
+            enabled: Vec::new(),
+            is_registering: true,
+            sts: None,
+            bouncer_network: None,
+        }
+    }
+}
+
+impl CapabilityNegotiation {
+    pub fn is_enabled(&self, capability: &str) -> bool {
+        self.enabled.iter().any(|v| v == capability)
+    }
+
+    /// Policy advertised by the server, it is never requested
+    pub fn get_sts(&self) -> Option<&StsPolicy> {
+        self.sts.as_ref()
+    }
+
+    /// Bind the connection to a network of a soju bouncer
+    pub fn set_bouncer_network(&mut self, network: Option<String>) {
+        self.bouncer_network = network;
+    }
+
+    fn request(&self, capabilities: &[String]) -> Option<Command> {
+        let to_request = capabilities
+            .iter()
+            .filter(|cap| SUPPORTED_CAPABILITIES.contains(&cap.as_str()) && !self.is_enabled(cap))
+            .map(|cap| cap.as_str())
+            .collect::<Vec<&str>>();
+
+        if to_request.is_empty() {
+            None
+        } else {
+            Some(Command::Cap(format!("REQ :{}", to_request.join(" "))))
+        }
+    }
+
+    fn end_registration(&mut self) -> Vec<Command> {
+        if !self.is_registering {
+            return Vec::new();
+        }
+        self.is_registering = false;
+        let mut commands = Vec::new();
+        if let Some(network) = &self.bouncer_network
+            && self
+                .available
+                .iter()
+                .any(|v| v == "soju.im/bouncer-networks")
+        {
+            commands.push(Command::Bouncer(
+                "BIND".to_string(),
+                vec![network.to_string()],
+            ));
+        }
+        commands.push(Command::Cap("END".to_string()));
+        commands
+    }
+
+    /// Returns the commands to send back to the server
+    pub fn handle(&mut self, arguments: &str) -> Vec<Command> {
+        let Some(reply) = CapReply::parse(arguments) else {
+            return Vec::new();
+        };
+
+        let capabilities = reply
+            .capabilities()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<String>>();
+
+        if matches!(reply.sub_command, CapSubCommand::Ls | CapSubCommand::New)
+            && let Some((_, Some(value))) = reply.capabilities().find(|(name, _)| *name == "sts")
+        {
+            self.sts = StsPolicy::parse(value);
+        }
+
+        match reply.sub_command {
+            CapSubCommand::Ls => {
+                self.available.extend(capabilities);
+                if !reply.is_last {
+                    return Vec::new();
+                }
+                if let Some(request) = self.request(&self.available) {
+                    vec![request]
+                } else {
+                    self.end_registration()
+                }
+            }
+            CapSubCommand::New => {
+                self.available.extend(capabilities.iter().cloned());
+                self.request(&capabilities).into_iter().collect()
+            }
+            CapSubCommand::Ack => {
+                for cap in capabilities {
+                    if let Some(removed) = cap.strip_prefix('-') {
+                        self.enabled.retain(|v| v != removed);
+                    } else if !self.is_enabled(&cap) {
+                        self.enabled.push(cap);
+                    }
+                }
+                self.end_registration()
+            }
+            CapSubCommand::Nak => self.end_registration(),
+            CapSubCommand::Del => {
+                self.available.retain(|v| !capabilities.contains(v));
+                self.enabled.retain(|v| !capabilities.contains(v));
+                Vec::new()
+            }
+            CapSubCommand::List => Vec::new(),
+        }
+    }
+}
+
+/// Negotiation of the client, shared with the users of the connection
+#[derive(Debug, Clone, Default)]
+pub struct SharedCapabilities(Arc<RwLock<CapabilityNegotiation>>);
+
+impl SharedCapabilities {
+    pub fn is_enabled(&self, capability: &str) -> bool {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_enabled(capability)
+    }
+
+    /// Policy advertised by the server, it is never requested
+    pub fn get_sts(&self) -> Option<StsPolicy> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_sts()
+            .cloned()
+    }
+
+    pub(crate) fn set_bouncer_network(&self, network: Option<String>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_bouncer_network(network);
+    }
+
+    /// Returns the commands to send back to the server
+    pub(crate) fn handle(&self, arguments: &str) -> Vec<Command> {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .handle(arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(commands: Vec<Command>) -> Vec<String> {
+        commands
+            .into_iter()
+            .map(|c| match c {
+                Command::Cap(v) => v,
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let reply = CapReply::parse("* LS * :batch sasl=PLAIN,EXTERNAL").unwrap();
+        assert_eq!(reply.sub_command, CapSubCommand::Ls);
+        assert!(!reply.is_last);
+        assert_eq!(
+            reply.capabilities().collect::<Vec<_>>(),
+            vec![("batch", None), ("sasl", Some("PLAIN,EXTERNAL"))]
+        );
+
+        let reply = CapReply::parse("nick ACK :server-time").unwrap();
+        assert_eq!(reply.sub_command, CapSubCommand::Ack);
+        assert!(reply.is_last);
+        assert!(CapReply::parse("nick UNKNOWN :a").is_none());
+    }
+
+    #[test]
+    fn test_negotiation_multiline() {
+        let mut negotiation = CapabilityNegotiation::default();
+        assert!(negotiation.handle("* LS * :batch account-tag").is_empty());
+        assert_eq!(
+            to_strings(negotiation.handle("* LS :server-time draft/chathistory")),
+            vec!["REQ :batch server-time draft/chathistory"]
+        );
+        assert_eq!(
+            to_strings(negotiation.handle("* ACK :batch server-time draft/chathistory")),
+            vec!["END"]
+        );
+        assert!(negotiation.is_enabled("batch"));
+        assert!(negotiation.is_enabled("draft/chathistory"));
+        assert!(!negotiation.is_enabled("account-tag"));
+    }
+
+    #[test]
+    fn test_negotiation_nothing_to_request() {
+        let mut negotiation = CapabilityNegotiation::default();
+        assert_eq!(to_strings(negotiation.handle("* LS :sasl")), vec!["END"]);
+        //Only one END
+        assert!(negotiation.handle("* NAK :batch").is_empty());
+    }
+
+    #[test]
+    fn test_sts_policy() {
+        let mut negotiation = CapabilityNegotiation::default();
+        assert!(negotiation.get_sts().is_none());
+        assert_eq!(
+            to_strings(negotiation.handle("* LS :sts=port=6697 batch")),
+            vec!["REQ :batch"]
+        );
+        assert_eq!(negotiation.get_sts().and_then(|v| v.port), Some(6697));
+    }
+
+    #[test]
+    fn test_bouncer_bind() {
+        let mut negotiation = CapabilityNegotiation::default();
+        negotiation.set_bouncer_network(Some("42".to_string()));
+        negotiation.handle("* LS :soju.im/bouncer-networks");
+        assert_eq!(
+            to_strings(negotiation.handle("* ACK :soju.im/bouncer-networks")),
+            vec!["Bouncer(\"BIND\", [\"42\"])", "END"]
+        );
+
+        //Not a bouncer, nothing to bind to
+        let mut negotiation = CapabilityNegotiation::default();
+        negotiation.set_bouncer_network(Some("42".to_string()));
+        assert_eq!(to_strings(negotiation.handle("* LS :sasl")), vec!["END"]);
+    }
+
+    #[test]
+    fn test_negotiation_new_del() {
+        let mut negotiation = CapabilityNegotiation::default();
+        negotiation.handle("* LS :");
+        assert_eq!(
+            to_strings(negotiation.handle("* NEW :batch")),
+            vec!["REQ :batch"]
+        );
+        assert!(negotiation.handle("* ACK :batch").is_empty());
+        assert!(negotiation.is_enabled("batch"));
+        negotiation.handle("* DEL :batch");
+        assert!(!negotiation.is_enabled("batch"));
+    }
+
+    #[test]
+    fn test_shared_capabilities() {
+        let shared = SharedCapabilities::default();
+        let view = shared.clone();
+        shared.handle("* LS :sts=duration=300 echo-message");
+        shared.handle("* ACK :echo-message");
+        assert!(view.is_enabled("echo-message"));
+        assert_eq!(view.get_sts().and_then(|v| v.duration), Some(300));
+    }
+}
@@ -1,6 +1,7 @@
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::io::{BufReader, BufWriter};
 
+use crate::capability::SharedCapabilities;
 use crate::conn::{self, Connection};
 use crate::error::{ClownError, ConnectionError};
 use crate::message::MessageReceiver;
@@ -14,6 +15,8 @@ pub struct LoginConfig {
     pub real_name: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Network of a soju bouncer (BOUNCER BIND)
+    pub bouncer_network: Option<String>,
 }
 
 pub struct Client {
@@ -27,6 +30,7 @@ pub struct Client {
 impl Client {
     pub fn new(login_config: LoginConfig) -> Self {
         let mut outgoing = Outgoing::default();
+        outgoing.set_bouncer_network(login_config.bouncer_network.clone());
         let (sender, message_receiver) = outgoing.create_outgoing();
         Self {
             sender,
@@ -46,7 +50,7 @@ impl Client {
     fn try_connect(&mut self) -> Result<(), ClownError> {
         let mut command_sender = self.command_sender();
 
-        //command_sender.send(crate::command::Command::CAP("LS 302".to_string()))?;
+        command_sender.send(crate::command::Command::Cap("LS 302".to_string()))?;
         if let Some(password) = &self.login_config.password
             && !password.is_empty()
         {
@@ -86,13 +90,21 @@ impl Client {
     }
 
     pub async fn launch(
-        self,
+        mut self,
         connection_config: &conn::ConnectionConfig,
     ) -> Result<(), ClownError> {
+        //STS does not apply to WebSocket gateways
+        self.outgoing
+            .set_secure(connection_config.use_tls || connection_config.websocket.is_some());
         let conn = Connection::new(connection_config).connect().await?;
         self.start(conn).await
     }
 
+    /// The capabilities enabled on the connection, kept up to date by the client
+    pub fn capabilities(&self) -> SharedCapabilities {
+        self.outgoing.capabilities()
+    }
+
     pub fn command_sender(&self) -> CommandSender {
         self.sender.clone()
     }
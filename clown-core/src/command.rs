@@ -1,3 +1,4 @@
+use clown_parser::tags::escape_tag_value;
 use phf::phf_map;
 use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
 use tokio::sync::mpsc;
@@ -23,6 +24,15 @@ enum CommandName {
     Invite,
     Kick,
     Cap,
+    Batch,
+    ChatHistory,
+    Ack,
+    Away,
+    Account,
+    ChgHost,
+    SetName,
+    TagMsg,
+    Bouncer,
     Error,
 }
 
@@ -44,6 +54,15 @@ static COMMAND_NAME: phf::Map<&'static str, CommandName> = phf_map! {
         "INVITE" => CommandName::Invite,
         "KICK" => CommandName::Kick,
         "CAP" => CommandName::Cap,
+        "BATCH" => CommandName::Batch,
+        "CHATHISTORY" => CommandName::ChatHistory,
+        "ACK" => CommandName::Ack,
+        "AWAY" => CommandName::Away,
+        "ACCOUNT" => CommandName::Account,
+        "CHGHOST" => CommandName::ChgHost,
+        "SETNAME" => CommandName::SetName,
+        "TAGMSG" => CommandName::TagMsg,
+        "BOUNCER" => CommandName::Bouncer,
         "ERROR" => CommandName::Error
 };
 
@@ -81,6 +100,14 @@ pub enum Command {
     /// JOIN <channel>
     Join(String),
 
+    /// Join several channels, the keys are matched by position so the keyed channels are sent first.
+    /// JOIN <channel>{,<channel>} [<key>{,<key>}]
+    JoinKeys(Vec<(String, Option<String>)>),
+
+    /// Join sent by the server with the extended-join capability, the account is * if not logged in.
+    /// JOIN <channel> <account> :<realname>
+    ExtendedJoin(String, String, String),
+
     /// Leave a channel, optionally with a reason.
     /// PART <channel> [<reason>]
     Part(String, Option<String>),
@@ -101,6 +128,14 @@ pub enum Command {
     /// WHO <mask>
     Who(String),
 
+    /// Information about a user, the nick is repeated to get the idle time from its server.
+    /// WHOIS <nick> <nick>
+    Whois(String),
+
+    /// Information about a user who has left.
+    /// WHOWAS <nick>
+    Whowas(String),
+
     /// List channels, optionally filtered by channel name.
     /// LIST [<channel>]
     List(Option<String>),
@@ -117,6 +152,58 @@ pub enum Command {
     /// CAP <subcommand>
     Cap(String),
 
+    /// Start or end a batch of messages, the reference starts with '+' or '-'.
+    /// BATCH <+/-reference> [<type> [<parameters>...]]
+    Batch(String, Vec<String>),
+
+    /// Request the history of a target, the reference is a timestamp=, msgid= or *.
+    /// CHATHISTORY <subcommand> <target> <reference> <limit>
+    ChatHistory(String, String, String, u16),
+
+    /// Set or remove the away status, also sent by the server with away-notify.
+    /// AWAY [:<message>]
+    Away(Option<String>),
+
+    /// The account of a user has changed (account-notify), * if logged out.
+    /// ACCOUNT <account>
+    Account(String),
+
+    /// The user or host of a user has changed (chghost).
+    /// CHGHOST <new_user> <new_host>
+    ChgHost(String, String),
+
+    /// Change the realname (setname).
+    /// SETNAME :<realname>
+    SetName(String),
+
+    /// Follow the presence of nicknames, the subcommand is +, -, C, L or S.
+    /// MONITOR <subcommand> [<target>{,<target>}]
+    Monitor(String, Vec<String>),
+
+    /// Ask which nicknames are online.
+    /// ISON <nickname>{ <nickname>}
+    Ison(Vec<String>),
+
+    /// Message without content, only its tags matter (message-tags).
+    /// TAGMSG <target>
+    TagMsg(String),
+
+    /// Networks of a soju bouncer (soju.im/bouncer-networks).
+    /// BOUNCER <subcommand> [<parameters>...]
+    Bouncer(String, Vec<String>),
+
+    /// Send a command with a label, the replies of the server will have the same label.
+    /// @label=<label> <command>
+    Labeled(String, Box<Command>),
+
+    /// Send a command with client tags, an empty value only writes the key.
+    /// @<key>=<value>;<key> <command>
+    Tagged(Vec<(String, String)>, Box<Command>),
+
+    /// Reply of a labeled command without any content.
+    /// ACK
+    Ack,
+
     /// Error command
     /// ERROR :Connection timeout  ; Server closing a client connection because it is unresponsive.
     Error(String),
@@ -125,6 +212,46 @@ pub enum Command {
 }
 impl Command {
     pub async fn write<W>(&self, writer: &mut BufWriter<W>) -> Result<(), std::io::Error>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut tags = Vec::new();
+        let mut command = self;
+        loop {
+            match command {
+                Command::Labeled(label, inner) => {
+                    tags.push(("label", label.as_str()));
+                    command = inner;
+                }
+                Command::Tagged(list, inner) => {
+                    tags.extend(
+                        list.iter()
+                            .map(|(key, value)| (key.as_str(), value.as_str())),
+                    );
+                    command = inner;
+                }
+                _ => break,
+            }
+        }
+
+        if !tags.is_empty() {
+            writer.write_all(b"@").await?;
+            for (i, (key, value)) in tags.into_iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b";").await?;
+                }
+                writer.write_all(key.as_bytes()).await?;
+                if !value.is_empty() {
+                    writer.write_all(b"=").await?;
+                    writer.write_all(escape_tag_value(value).as_bytes()).await?;
+                }
+            }
+            writer.write_all(b" ").await?;
+        }
+        command.write_command(writer).await
+    }
+
+    async fn write_command<W>(&self, writer: &mut BufWriter<W>) -> Result<(), std::io::Error>
     where
         W: AsyncWrite + Unpin,
     {
@@ -139,6 +266,40 @@ impl Command {
                 writer.write_all(b"JOIN ").await?;
                 writer.write_all(channel.as_bytes()).await?;
             }
+            Command::ExtendedJoin(channel, _, _) => {
+                writer.write_all(b"JOIN ").await?;
+                writer.write_all(channel.as_bytes()).await?;
+            }
+            Command::JoinKeys(channels) => {
+                let (keyed, others): (Vec<_>, Vec<_>) =
+                    channels.iter().partition(|(_, key)| key.is_some());
+                let names = keyed
+                    .iter()
+                    .chain(others.iter())
+                    .map(|(channel, _)| channel.as_str())
+                    .collect::<Vec<&str>>();
+                writer.write_all(b"JOIN ").await?;
+                writer.write_all(names.join(",").as_bytes()).await?;
+                if !keyed.is_empty() {
+                    let keys = keyed
+                        .iter()
+                        .filter_map(|(_, key)| key.as_deref())
+                        .collect::<Vec<&str>>();
+                    writer.write_all(b" ").await?;
+                    writer.write_all(keys.join(",").as_bytes()).await?;
+                }
+            }
+            Command::Away(message) => {
+                writer.write_all(b"AWAY").await?;
+                if let Some(m) = message {
+                    writer.write_all(b" :").await?;
+                    writer.write_all(m.as_bytes()).await?;
+                }
+            }
+            Command::SetName(realname) => {
+                writer.write_all(b"SETNAME :").await?;
+                writer.write_all(realname.as_bytes()).await?;
+            }
             Command::Part(channel, reason) => {
                 writer.write_all(b"PART ").await?;
                 writer.write_all(channel.as_bytes()).await?;
@@ -162,13 +323,26 @@ impl Command {
             Command::Mode(target, mode) => {
                 writer.write_all(b"MODE ").await?;
                 writer.write_all(target.as_bytes()).await?;
-                writer.write_all(b" ").await?;
-                writer.write_all(mode.as_bytes()).await?;
+                //Without mode, the server replies with the current modes
+                if !mode.is_empty() {
+                    writer.write_all(b" ").await?;
+                    writer.write_all(mode.as_bytes()).await?;
+                }
             }
             Command::Who(mask) => {
                 writer.write_all(b"WHO ").await?;
                 writer.write_all(mask.as_bytes()).await?;
             }
+            Command::Whois(nick) => {
+                writer.write_all(b"WHOIS ").await?;
+                writer.write_all(nick.as_bytes()).await?;
+                writer.write_all(b" ").await?;
+                writer.write_all(nick.as_bytes()).await?;
+            }
+            Command::Whowas(nick) => {
+                writer.write_all(b"WHOWAS ").await?;
+                writer.write_all(nick.as_bytes()).await?;
+            }
             Command::List(channel) => {
                 writer.write_all(b"LIST").await?;
                 if let Some(c) = channel {
@@ -218,6 +392,48 @@ impl Command {
                 writer.write_all(b"CAP ").await?;
                 writer.write_all(cap.as_bytes()).await?;
             }
+            Command::Batch(reference, parameters) => {
+                writer.write_all(b"BATCH ").await?;
+                writer.write_all(reference.as_bytes()).await?;
+                for parameter in parameters {
+                    writer.write_all(b" ").await?;
+                    writer.write_all(parameter.as_bytes()).await?;
+                }
+            }
+            Command::ChatHistory(sub_command, target, reference, limit) => {
+                writer.write_all(b"CHATHISTORY ").await?;
+                writer.write_all(sub_command.as_bytes()).await?;
+                writer.write_all(b" ").await?;
+                writer.write_all(target.as_bytes()).await?;
+                writer.write_all(b" ").await?;
+                writer.write_all(reference.as_bytes()).await?;
+                writer.write_all(b" ").await?;
+                writer.write_all(limit.to_string().as_bytes()).await?;
+            }
+            Command::Monitor(sub_command, targets) => {
+                writer.write_all(b"MONITOR ").await?;
+                writer.write_all(sub_command.as_bytes()).await?;
+                if !targets.is_empty() {
+                    writer.write_all(b" ").await?;
+                    writer.write_all(targets.join(",").as_bytes()).await?;
+                }
+            }
+            Command::TagMsg(target) => {
+                writer.write_all(b"TAGMSG ").await?;
+                writer.write_all(target.as_bytes()).await?;
+            }
+            Command::Bouncer(sub_command, parameters) => {
+                writer.write_all(b"BOUNCER ").await?;
+                writer.write_all(sub_command.as_bytes()).await?;
+                for parameter in parameters {
+                    writer.write_all(b" ").await?;
+                    writer.write_all(parameter.as_bytes()).await?;
+                }
+            }
+            Command::Ison(nicknames) => {
+                writer.write_all(b"ISON ").await?;
+                writer.write_all(nicknames.join(" ").as_bytes()).await?;
+            }
             Command::Quit(reason) => {
                 writer.write_all(b"QUIT").await?;
                 if let Some(r) = reason {
@@ -228,7 +444,12 @@ impl Command {
             Command::Unknown(un) => {
                 writer.write_all(un.as_bytes()).await?;
             }
-            Command::Error(_) => {}
+            Command::Error(_)
+            | Command::Ack
+            | Command::Account(_)
+            | Command::ChgHost(_, _)
+            | Command::Labeled(_, _)
+            | Command::Tagged(_, _) => {}
         }
         // Every IRC message ends with CRLF
         writer.write_all(b"\r\n").await
@@ -267,11 +488,32 @@ impl CommandBuilder {
         }
     }
 
-    fn join(parameters: &str, _: Option<&str>) -> Option<Command> {
-        if parameters.is_empty() {
-            return None;
+    // JOIN <channel> [<account> :<realname>]
+    fn join(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        let mut parameters = Self::tokenize(parameters);
+        match (parameters.next(), parameters.next()) {
+            (Some(channel), Some(account)) => Some(Command::ExtendedJoin(
+                channel.to_string(),
+                account.to_string(),
+                trailing.unwrap_or_default().to_string(),
+            )),
+            (Some(channel), None) => Some(Command::Join(channel.to_string())),
+            _ => trailing
+                .filter(|v| !v.is_empty())
+                .map(|channel| Command::Join(channel.to_string())),
+        }
+    }
+
+    // CHGHOST <new_user> <new_host>
+    fn chghost(parameters: &str) -> Option<Command> {
+        let mut parameters = Self::tokenize(parameters);
+        if let Some(user) = parameters.next()
+            && let Some(host) = parameters.next()
+        {
+            Some(Command::ChgHost(user.to_string(), host.to_string()))
+        } else {
+            None
         }
-        Some(Command::Join(parameters.to_string()))
     }
 
     fn quit(trailing: Option<&str>) -> Option<Command> {
@@ -332,8 +574,10 @@ impl CommandBuilder {
     }
 
     // MODE <target> <mode>
-    fn mode(parameters: &str) -> Option<Command> {
-        let parameters = Self::tokenize(parameters).collect::<Vec<&str>>();
+    fn mode(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        let parameters = Self::tokenize(parameters)
+            .chain(trailing.into_iter().flat_map(Self::tokenize))
+            .collect::<Vec<&str>>();
         if let [first, others @ ..] = parameters.as_slice() {
             Some(Command::Mode(first.to_string(), others.join(" ")))
         } else {
@@ -358,9 +602,11 @@ impl CommandBuilder {
         }
     }
 
-    // INVITE <nick> <channel>
-    fn invite(parameters: &str) -> Option<Command> {
-        let parameters = Self::tokenize(parameters).collect::<Vec<&str>>();
+    // INVITE <nick> [:]<channel>
+    fn invite(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        let parameters = Self::tokenize(parameters)
+            .chain(trailing)
+            .collect::<Vec<&str>>();
         if let [first, others] = parameters.as_slice() {
             Some(Command::Invite(first.to_string(), others.to_string()))
         } else {
@@ -384,6 +630,43 @@ impl CommandBuilder {
         }
     }
 
+    // CAP <client> <subcommand> [*] :<capabilities>
+    // The arguments are kept raw, they are parsed by the capability negotiation
+    fn cap(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        match trailing {
+            Some(trailing) => Some(Command::Cap(format!("{parameters} :{trailing}"))),
+            None => Self::make_command_1(parameters, trailing, Command::Cap),
+        }
+    }
+
+    // BATCH <+/-reference> [<type> [<parameters>...]]
+    fn batch(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        let mut parameters = Self::tokenize(parameters);
+        parameters.next().map(|reference| {
+            Command::Batch(
+                reference.to_string(),
+                parameters
+                    .map(|v| v.to_string())
+                    .chain(trailing.map(|v| v.to_string()))
+                    .collect(),
+            )
+        })
+    }
+
+    // BOUNCER <subcommand> [<parameters>...]
+    fn bouncer(parameters: &str, trailing: Option<&str>) -> Option<Command> {
+        let mut parameters = Self::tokenize(parameters);
+        parameters.next().map(|sub_command| {
+            Command::Bouncer(
+                sub_command.to_string(),
+                parameters
+                    .map(|v| v.to_string())
+                    .chain(trailing.map(|v| v.to_string()))
+                    .collect(),
+            )
+        })
+    }
+
     pub fn get_command(
         command_name: &str,
         parameters: Option<&str>,
@@ -411,17 +694,33 @@ impl CommandBuilder {
                 CommandName::Part => CommandBuilder::part(parameters, trailing),
                 CommandName::Notice => CommandBuilder::notice(parameters, trailing),
                 CommandName::Topic => CommandBuilder::topic(parameters, trailing),
-                CommandName::Mode => CommandBuilder::mode(parameters),
+                CommandName::Mode => CommandBuilder::mode(parameters, trailing),
                 CommandName::Who => CommandBuilder::who(parameters),
                 CommandName::List => CommandBuilder::list(parameters),
-                CommandName::Invite => CommandBuilder::invite(parameters),
+                CommandName::Invite => CommandBuilder::invite(parameters, trailing),
                 CommandName::Kick => CommandBuilder::kick(parameters, trailing),
                 CommandName::Error => {
                     CommandBuilder::make_command_1(parameters, trailing, Command::Error)
                 }
-                CommandName::Cap => {
-                    CommandBuilder::make_command_1(parameters, trailing, Command::Cap)
+                CommandName::Cap => CommandBuilder::cap(parameters, trailing),
+                CommandName::Batch => CommandBuilder::batch(parameters, trailing),
+                CommandName::Ack => Some(Command::Ack),
+                CommandName::Away => Some(Command::Away(
+                    trailing.filter(|v| !v.is_empty()).map(|v| v.to_string()),
+                )),
+                CommandName::Account => {
+                    CommandBuilder::make_command_1(parameters, trailing, Command::Account)
+                }
+                CommandName::ChgHost => CommandBuilder::chghost(parameters),
+                CommandName::TagMsg => {
+                    CommandBuilder::make_command_1(parameters, trailing, Command::TagMsg)
+                }
+                CommandName::Bouncer => CommandBuilder::bouncer(parameters, trailing),
+                CommandName::SetName => {
+                    Some(Command::SetName(trailing.unwrap_or(parameters).to_string()))
                 }
+                //Only sent by the client
+                CommandName::ChatHistory => None,
             }
         } else {
             None
@@ -1,4 +1,5 @@
 use crate::error::ConnectionError;
+use crate::websocket::{OFFERED_PROTOCOLS, WebSocketStream};
 use rustls::RootCertStore;
 use rustls::pki_types::ServerName;
 use std::pin::Pin;
@@ -6,9 +7,12 @@ use std::sync::Arc;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio_rustls::TlsConnector;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
 pub enum IRCStream {
     TLS(Box<tokio_rustls::client::TlsStream<tokio::net::TcpStream>>),
     PLAIN(tokio::net::TcpStream),
+    WS(Box<WebSocketStream<IRCStream>>),
     #[cfg(test)]
     MOCK(test::StreamMock),
 }
@@ -22,6 +26,7 @@ impl AsyncRead for IRCStream {
         match self.get_mut() {
             IRCStream::TLS(stream) => Pin::new(stream).poll_read(cx, buf),
             IRCStream::PLAIN(stream) => Pin::new(stream).poll_read(cx, buf),
+            IRCStream::WS(stream) => Pin::new(stream).poll_read(cx, buf),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => Pin::new(mock).poll_read(cx, buf),
@@ -34,6 +39,7 @@ impl AsyncWrite for IRCStream {
         match self {
             IRCStream::TLS(stream) => stream.get_ref().0.is_write_vectored(),
             IRCStream::PLAIN(stream) => stream.is_write_vectored(),
+            IRCStream::WS(stream) => stream.is_write_vectored(),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => mock.is_write_vectored(),
@@ -47,6 +53,7 @@ impl AsyncWrite for IRCStream {
         match self.get_mut() {
             IRCStream::TLS(stream) => Pin::new(stream).poll_flush(cx),
             IRCStream::PLAIN(stream) => Pin::new(stream).poll_flush(cx),
+            IRCStream::WS(stream) => Pin::new(stream).poll_flush(cx),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => Pin::new(mock).poll_flush(cx),
@@ -60,6 +67,7 @@ impl AsyncWrite for IRCStream {
         match self.get_mut() {
             IRCStream::TLS(stream) => Pin::new(stream).poll_shutdown(cx),
             IRCStream::PLAIN(stream) => Pin::new(stream).poll_shutdown(cx),
+            IRCStream::WS(stream) => Pin::new(stream).poll_shutdown(cx),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => Pin::new(mock).poll_shutdown(cx),
@@ -74,6 +82,7 @@ impl AsyncWrite for IRCStream {
         match self.get_mut() {
             IRCStream::TLS(stream) => Pin::new(stream).poll_write(cx, buf),
             IRCStream::PLAIN(stream) => Pin::new(stream).poll_write(cx, buf),
+            IRCStream::WS(stream) => Pin::new(stream).poll_write(cx, buf),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => Pin::new(mock).poll_write(cx, buf),
@@ -88,6 +97,7 @@ impl AsyncWrite for IRCStream {
         match self.get_mut() {
             IRCStream::TLS(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
             IRCStream::PLAIN(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            IRCStream::WS(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
 
             #[cfg(test)]
             IRCStream::MOCK(mock) => Pin::new(mock).poll_write_vectored(cx, bufs),
@@ -101,6 +111,9 @@ pub struct ConnectionConfig {
     pub address: String,
     pub port: u16,
     pub use_tls: bool,
+    /// Path of a WebSocket gateway (ws:// or wss:// with TLS) instead of a raw IRC connection
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub websocket: Option<String>,
 }
 
 #[derive(Debug)]
@@ -148,13 +161,45 @@ impl Connection {
         Ok(IRCStream::TLS(Box::new(stream)))
     }
 
+    async fn establish_websocket(
+        &self,
+        stream: IRCStream,
+        path: &str,
+    ) -> Result<IRCStream, ConnectionError> {
+        let config = &self.connection_config;
+        let scheme = if config.use_tls { "wss" } else { "ws" };
+        let path = path.trim_start_matches('/');
+        let mut request = format!("{scheme}://{}:{}/{path}", config.address, config.port)
+            .into_client_request()
+            .map_err(|err| ConnectionError::WebSocket(Box::new(err)))?;
+        request.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(OFFERED_PROTOCOLS),
+        );
+
+        let (stream, response) = tokio_tungstenite::client_async(request, stream)
+            .await
+            .map_err(|err| ConnectionError::WebSocket(Box::new(err)))?;
+        let protocol = response
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|v| v.to_str().ok());
+        Ok(IRCStream::WS(Box::new(WebSocketStream::new(
+            stream, protocol,
+        ))))
+    }
+
     pub async fn connect(&self) -> Result<IRCStream, ConnectionError> {
-        if self.connection_config.use_tls {
+        let stream = if self.connection_config.use_tls {
             self.establish_stream_tls(&self.connection_config.address, self.connection_config.port)
-                .await
+                .await?
         } else {
             self.establish_stream(&self.connection_config.address, self.connection_config.port)
-                .await
+                .await?
+        };
+        match &self.connection_config.websocket {
+            Some(path) => self.establish_websocket(stream, path).await,
+            None => Ok(stream),
         }
     }
 }
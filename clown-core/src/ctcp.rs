@@ -0,0 +1,81 @@
+const DELIMITER: char = '\x01';
+
+/// Client-To-Client Protocol message, queries are sent by PRIVMSG and replies by NOTICE
+/// \x01<command> [params]\x01
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ctcp {
+    /// Always uppercase
+    pub command: String,
+    pub params: Option<String>,
+}
+
+impl Ctcp {
+    pub fn new(command: &str, params: Option<String>) -> Self {
+        Self {
+            command: command.to_ascii_uppercase(),
+            params: params.filter(|v| !v.is_empty()),
+        }
+    }
+
+    /// The final delimiter is optional, some clients do not send it
+    pub fn parse(content: &str) -> Option<Self> {
+        let content = content.strip_prefix(DELIMITER)?;
+        let content = content.strip_suffix(DELIMITER).unwrap_or(content);
+        let (command, params) = match content.split_once(' ') {
+            Some((command, params)) => (command, Some(params.to_string())),
+            None => (content, None),
+        };
+        if command.is_empty() {
+            return None;
+        }
+        Some(Self::new(command, params))
+    }
+
+    pub fn is_action(&self) -> bool {
+        self.command == "ACTION"
+    }
+}
+
+impl std::fmt::Display for Ctcp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.params {
+            Some(params) => write!(f, "{DELIMITER}{} {params}{DELIMITER}", self.command),
+            None => write!(f, "{DELIMITER}{}{DELIMITER}", self.command),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ctcp() {
+        assert_eq!(
+            Ctcp::parse("\x01ACTION waves\x01"),
+            Some(Ctcp::new("ACTION", Some("waves".into())))
+        );
+        assert!(Ctcp::parse("\x01action waves").unwrap().is_action());
+        assert_eq!(
+            Ctcp::parse("\x01VERSION\x01"),
+            Some(Ctcp::new("VERSION", None))
+        );
+        assert_eq!(
+            Ctcp::parse("\x01PING 1234 5678\x01").unwrap().params,
+            Some("1234 5678".to_string())
+        );
+        assert_eq!(Ctcp::parse("hello"), None);
+        assert_eq!(Ctcp::parse("\x01\x01"), None);
+    }
+
+    #[test]
+    fn test_encode_ctcp() {
+        assert_eq!(Ctcp::new("version", None).to_string(), "\x01VERSION\x01");
+        assert_eq!(
+            Ctcp::new("PING", Some("42".into())).to_string(),
+            "\x01PING 42\x01"
+        );
+        let ctcp = Ctcp::new("TIME", Some("Sun, 18 Oct 2026 10:00:00 +0000".into()));
+        assert_eq!(Ctcp::parse(&ctcp.to_string()), Some(ctcp));
+    }
+}
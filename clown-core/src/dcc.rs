@@ -0,0 +1,544 @@
+use crate::ctcp::Ctcp;
+use crate::error::DccError;
+use std::net::{IpAddr, Ipv4Addr};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+const CHUNK_SIZE: usize = 16 * 1024;
+/// A longer chat line is split, so that a peer without line ending cannot fill the memory
+const MAX_CHAT_LINE: usize = 8 * 1024;
+
+/// Offer of a file, the port is 0 for a passive (reverse) offer:
+/// the receiver listens and answers with its address and the same token
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DccSend {
+    pub filename: String,
+    pub address: IpAddr,
+    pub port: u16,
+    pub size: Option<u64>,
+    pub token: Option<String>,
+}
+
+/// Position to restart a transfer from, sent by the receiver and accepted by the sender
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DccResume {
+    pub filename: String,
+    pub port: u16,
+    pub position: u64,
+    pub token: Option<String>,
+}
+
+/// Offer of a direct chat, passive like a send when the port is 0
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DccChat {
+    pub address: IpAddr,
+    pub port: u16,
+    pub token: Option<String>,
+}
+
+/// Requests carried by a CTCP DCC query
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DccRequest {
+    Send(DccSend),
+    Chat(DccChat),
+    Resume(DccResume),
+    Accept(DccResume),
+}
+
+/// The filename is quoted when it contains spaces
+fn split_filename(params: &str) -> Option<(&str, &str)> {
+    if let Some(quoted) = params.strip_prefix('"') {
+        let (filename, rest) = quoted.split_once('"')?;
+        Some((filename, rest.trim_start()))
+    } else {
+        Some(params.split_once(' ').unwrap_or((params, "")))
+    }
+}
+
+fn quote_filename(filename: &str) -> String {
+    if filename.contains(' ') {
+        format!("\"{filename}\"")
+    } else {
+        filename.to_string()
+    }
+}
+
+/// IPv4 addresses are sent as an integer, IPv6 ones as text
+fn parse_address(address: &str) -> Option<IpAddr> {
+    match address.parse::<u32>() {
+        Ok(value) => Some(IpAddr::V4(Ipv4Addr::from(value))),
+        Err(_) => address.parse::<IpAddr>().ok(),
+    }
+}
+
+fn format_address(address: &IpAddr) -> String {
+    match address {
+        IpAddr::V4(v4) => u32::from(*v4).to_string(),
+        IpAddr::V6(v6) => v6.to_string(),
+    }
+}
+
+impl DccResume {
+    fn parse(params: &str) -> Option<Self> {
+        let (filename, rest) = split_filename(params)?;
+        let mut rest = rest.split_ascii_whitespace();
+        Some(Self {
+            filename: filename.to_string(),
+            port: rest.next()?.parse().ok()?,
+            position: rest.next()?.parse().ok()?,
+            token: rest.next().map(|v| v.to_string()),
+        })
+    }
+
+    fn format(&self, command: &str) -> String {
+        let mut params = format!(
+            "{command} {} {} {}",
+            quote_filename(&self.filename),
+            self.port,
+            self.position
+        );
+        if let Some(token) = &self.token {
+            params.push(' ');
+            params.push_str(token);
+        }
+        params
+    }
+}
+
+impl DccRequest {
+    pub fn parse(ctcp: &Ctcp) -> Option<Self> {
+        if ctcp.command != "DCC" {
+            return None;
+        }
+        let (kind, params) = ctcp.params.as_deref()?.split_once(' ')?;
+        match kind.to_ascii_uppercase().as_str() {
+            "SEND" => {
+                let (filename, rest) = split_filename(params)?;
+                let mut rest = rest.split_ascii_whitespace();
+                Some(Self::Send(DccSend {
+                    filename: filename.to_string(),
+                    address: parse_address(rest.next()?)?,
+                    port: rest.next()?.parse().ok()?,
+                    size: rest.next().and_then(|v| v.parse().ok()),
+                    token: rest.next().map(|v| v.to_string()),
+                }))
+            }
+            //The protocol is always "chat"
+            "CHAT" => {
+                let mut rest = params.split_ascii_whitespace().skip(1);
+                Some(Self::Chat(DccChat {
+                    address: parse_address(rest.next()?)?,
+                    port: rest.next()?.parse().ok()?,
+                    token: rest.next().map(|v| v.to_string()),
+                }))
+            }
+            "RESUME" => DccResume::parse(params).map(Self::Resume),
+            "ACCEPT" => DccResume::parse(params).map(Self::Accept),
+            _ => None,
+        }
+    }
+
+    pub fn to_ctcp(&self) -> Ctcp {
+        let params = match self {
+            Self::Send(send) => {
+                let mut params = format!(
+                    "SEND {} {} {} {}",
+                    quote_filename(&send.filename),
+                    format_address(&send.address),
+                    send.port,
+                    send.size.unwrap_or_default()
+                );
+                if let Some(token) = &send.token {
+                    params.push(' ');
+                    params.push_str(token);
+                }
+                params
+            }
+            Self::Chat(chat) => {
+                let mut params =
+                    format!("CHAT chat {} {}", format_address(&chat.address), chat.port);
+                if let Some(token) = &chat.token {
+                    params.push(' ');
+                    params.push_str(token);
+                }
+                params
+            }
+            Self::Resume(resume) => resume.format("RESUME"),
+            Self::Accept(resume) => resume.format("ACCEPT"),
+        };
+        Ctcp::new("DCC", Some(params))
+    }
+}
+
+/// Keeps only the name of the file, the offers must not write outside of the download directory
+pub fn safe_filename(filename: &str) -> String {
+    let name = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim_matches(|v: char| v == '.' || v.is_whitespace());
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Sends the file from its current position, the total is the size of the whole file.
+/// The receiver acknowledges the position it has reached, the transfer ends with the last one.
+pub async fn send_file<S, F>(
+    stream: S,
+    file: &mut F,
+    position: u64,
+    total: u64,
+    progress: &mut impl FnMut(u64),
+) -> Result<u64, DccError>
+where
+    S: AsyncRead + AsyncWrite,
+    F: AsyncRead + Unpin,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    //The acknowledgements are read during the transfer, not to block the receiver
+    let acknowledged = async {
+        loop {
+            match reader.read_u32().await {
+                Ok(ack) if ack == total as u32 => return Ok(()),
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+    };
+    let sent = async {
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut sent = position;
+        loop {
+            let read = file.read(&mut buffer).await?;
+            let Some(chunk) = buffer.get(..read).filter(|v| !v.is_empty()) else {
+                break;
+            };
+            writer.write_all(chunk).await?;
+            sent = sent.saturating_add(read as u64);
+            progress(sent);
+        }
+        writer.flush().await?;
+        Ok::<u64, std::io::Error>(sent)
+    };
+    let (sent, acknowledged) = tokio::join!(sent, acknowledged);
+    let sent = sent?;
+    acknowledged?;
+    if sent < total {
+        return Err(DccError::Incomplete(sent, total));
+    }
+    Ok(sent)
+}
+
+/// Writes the received data after the position, until the size of the offer is reached.
+/// Without a size, the transfer ends when the sender closes the connection.
+/// A byte more than the remaining size is read to tell a peer sending past the offer.
+pub async fn receive_file<S, F>(
+    stream: S,
+    file: &mut F,
+    position: u64,
+    size: Option<u64>,
+    progress: &mut impl FnMut(u64),
+) -> Result<u64, DccError>
+where
+    S: AsyncRead + AsyncWrite,
+    F: AsyncWrite + Unpin,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut received = position;
+    while size.is_none_or(|size| received < size) {
+        let limit = size.map_or(CHUNK_SIZE, |size| {
+            usize::try_from(size - received)
+                .unwrap_or(usize::MAX)
+                .saturating_add(1)
+                .min(CHUNK_SIZE)
+        });
+        let read = match buffer.get_mut(..limit) {
+            Some(buffer) => reader.read(buffer).await?,
+            None => 0,
+        };
+        let Some(chunk) = buffer.get(..read).filter(|v| !v.is_empty()) else {
+            break;
+        };
+        if let Some(size) = size
+            && received.saturating_add(read as u64) > size
+        {
+            return Err(DccError::TooLarge(size));
+        }
+        file.write_all(chunk).await?;
+        received = received.saturating_add(read as u64);
+        writer.write_u32(received as u32).await?;
+        progress(received);
+    }
+    file.flush().await?;
+    if let Some(size) = size
+        && received < size
+    {
+        return Err(DccError::Incomplete(received, size));
+    }
+    Ok(received)
+}
+
+/// Exchanges lines with the peer until one of the sides closes the session.
+/// The received lines are given without their line ending.
+pub async fn chat<S>(
+    stream: S,
+    outgoing: &mut mpsc::UnboundedReceiver<String>,
+    incoming: &mut impl FnMut(String),
+) -> Result<(), DccError>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+    loop {
+        let mut line_reader = (&mut reader).take((MAX_CHAT_LINE - buffer.len()) as u64);
+        tokio::select! {
+            read = line_reader.read_until(b'\n', &mut buffer) => {
+                if read? == 0 {
+                    return Ok(());
+                }
+                while buffer.last().is_some_and(|v| *v == b'\n' || *v == b'\r') {
+                    buffer.pop();
+                }
+                incoming(String::from_utf8_lossy(&buffer).into_owned());
+                buffer.clear();
+            }
+            line = outgoing.recv() => {
+                let Some(line) = line else {
+                    writer.shutdown().await?;
+                    return Ok(());
+                };
+                writer.write_all(format!("{line}\n").as_bytes()).await?;
+                writer.flush().await?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::net::Ipv6Addr;
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn test_parse_send() {
+        let ctcp = Ctcp::parse("\x01DCC SEND \"my log.txt\" 2130706433 5000 1024\x01").unwrap();
+        let request = DccRequest::parse(&ctcp).unwrap();
+        assert_eq!(
+            request,
+            DccRequest::Send(DccSend {
+                filename: "my log.txt".into(),
+                address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                port: 5000,
+                size: Some(1024),
+                token: None,
+            })
+        );
+        assert_eq!(request.to_ctcp(), ctcp);
+
+        let ctcp = Ctcp::parse("\x01DCC SEND file ::1 0 10 42\x01").unwrap();
+        let Some(DccRequest::Send(send)) = DccRequest::parse(&ctcp) else {
+            panic!("Not a send");
+        };
+        assert_eq!(send.address, IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(send.token.as_deref(), Some("42"));
+        assert_eq!(DccRequest::Send(send).to_ctcp(), ctcp);
+
+        assert!(DccRequest::parse(&Ctcp::new("DCC", Some("SEND file".into()))).is_none());
+        assert!(DccRequest::parse(&Ctcp::new("VERSION", None)).is_none());
+    }
+
+    #[test]
+    fn test_parse_resume() {
+        let ctcp = Ctcp::parse("\x01DCC RESUME file.txt 5000 512\x01").unwrap();
+        let resume = DccResume {
+            filename: "file.txt".into(),
+            port: 5000,
+            position: 512,
+            token: None,
+        };
+        assert_eq!(
+            DccRequest::parse(&ctcp),
+            Some(DccRequest::Resume(resume.clone()))
+        );
+        let accept = DccRequest::Accept(resume).to_ctcp();
+        assert_eq!(accept.params.as_deref(), Some("ACCEPT file.txt 5000 512"));
+    }
+
+    #[test]
+    fn test_parse_chat() {
+        let ctcp = Ctcp::parse("\x01DCC CHAT chat 2130706433 5000\x01").unwrap();
+        let request = DccRequest::parse(&ctcp).unwrap();
+        assert_eq!(
+            request,
+            DccRequest::Chat(DccChat {
+                address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                port: 5000,
+                token: None,
+            })
+        );
+        assert_eq!(request.to_ctcp(), ctcp);
+
+        let ctcp = Ctcp::parse("\x01DCC CHAT chat 2130706433 0 7\x01").unwrap();
+        let Some(DccRequest::Chat(chat)) = DccRequest::parse(&ctcp) else {
+            panic!("Not a chat");
+        };
+        assert_eq!(chat.token.as_deref(), Some("7"));
+        assert_eq!(DccRequest::Chat(chat).to_ctcp(), ctcp);
+    }
+
+    #[test]
+    fn test_safe_filename() {
+        assert_eq!(safe_filename("../../etc/passwd"), "passwd");
+        assert_eq!(safe_filename("C:\\logs\\today.log"), "today.log");
+        assert_eq!(safe_filename(".."), "file");
+        assert_eq!(safe_filename("notes.txt"), "notes.txt");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_loopback() {
+        let content: Vec<u8> = (0..100_000u32).map(|v| (v % 251) as u8).collect();
+        let total = content.len() as u64;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let to_send = content.clone();
+        let sender = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut file = Cursor::new(to_send);
+            send_file(stream, &mut file, 0, total, &mut |_| {}).await
+        });
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let mut received = Cursor::new(Vec::new());
+        let mut last_progress = 0;
+        let size = receive_file(stream, &mut received, 0, Some(total), &mut |v| {
+            last_progress = v
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(size, total);
+        assert_eq!(last_progress, total);
+        assert_eq!(sender.await.unwrap().unwrap(), total);
+        assert_eq!(received.into_inner(), content);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_resume() {
+        let content = b"0123456789".repeat(1000);
+        let total = content.len() as u64;
+        let position = 4000;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let mut file = Cursor::new(content.clone());
+        file.set_position(position);
+        let sender = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            send_file(stream, &mut file, position, total, &mut |_| {}).await
+        });
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let mut received = Cursor::new(content.get(..4000).unwrap().to_vec());
+        received.set_position(position);
+        receive_file(stream, &mut received, position, Some(total), &mut |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(sender.await.unwrap().unwrap(), total);
+        assert_eq!(received.into_inner(), content);
+    }
+
+    #[tokio::test]
+    async fn test_chat_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let peer = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"hello\r\nsecond line\n").await.unwrap();
+            let mut received = String::new();
+            BufReader::new(&mut stream)
+                .read_line(&mut received)
+                .await
+                .unwrap();
+            //Closing the connection ends the session
+            received
+        });
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let (sender, mut outgoing) = mpsc::unbounded_channel();
+        sender.send("hi there".to_string()).unwrap();
+        let mut lines = Vec::new();
+        chat(stream, &mut outgoing, &mut |v| lines.push(v))
+            .await
+            .unwrap();
+        assert_eq!(lines, vec!["hello", "second line"]);
+        assert_eq!(peer.await.unwrap(), "hi there\n");
+    }
+
+    #[tokio::test]
+    async fn test_chat_long_line() {
+        let (mut peer, stream) = tokio::io::duplex(1024);
+        let long_line = "a".repeat(MAX_CHAT_LINE + 10);
+        tokio::spawn(async move {
+            peer.write_all(format!("{long_line}\nend\n").as_bytes())
+                .await
+                .unwrap();
+        });
+        let (_sender, mut outgoing) = mpsc::unbounded_channel();
+        let mut lines = Vec::new();
+        chat(stream, &mut outgoing, &mut |v| lines.push(v))
+            .await
+            .unwrap();
+        assert_eq!(
+            lines.iter().map(String::len).collect::<Vec<_>>(),
+            vec![MAX_CHAT_LINE, 10, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_interrupted() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let sender = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"abc").await.unwrap();
+        });
+
+        let stream = TcpStream::connect(address).await.unwrap();
+        let mut received = Cursor::new(Vec::new());
+        let result = receive_file(stream, &mut received, 0, Some(10), &mut |_| {}).await;
+        sender.await.unwrap();
+        assert!(matches!(result, Err(DccError::Incomplete(3, 10))));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_larger_than_offer() {
+        let (mut peer, stream) = tokio::io::duplex(1024);
+        peer.write_all(b"0123456789").await.unwrap();
+        let mut received = Cursor::new(Vec::new());
+        let result = receive_file(stream, &mut received, 0, Some(5), &mut |_| {}).await;
+        assert!(matches!(result, Err(DccError::TooLarge(5))));
+        assert!(received.into_inner().is_empty());
+
+        //The exact size ends the transfer without waiting for the peer
+        let (mut peer, stream) = tokio::io::duplex(1024);
+        peer.write_all(b"01234").await.unwrap();
+        let mut received = Cursor::new(Vec::new());
+        let size = receive_file(stream, &mut received, 0, Some(5), &mut |_| {})
+            .await
+            .unwrap();
+        assert_eq!(size, 5);
+        assert_eq!(received.into_inner(), b"01234");
+        drop(peer);
+    }
+}
@@ -8,6 +8,8 @@ pub enum ConnectionError {
     ConnectTCP(#[from] io::Error),
     #[error("DNS cannot be created")]
     InvalidDNS,
+    #[error("WebSocket handshake failed: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
     #[error("unknown data store error")]
     Unknown,
 }
@@ -28,6 +30,18 @@ pub enum IRCIOError {
     Unknown,
     #[error("Uninitialized")]
     Uninitialized,
+    #[error("Upgrade to a secure connection on the port {0} (STS)")]
+    StsUpgrade(u16),
+}
+
+#[derive(Error, Debug)]
+pub enum DccError {
+    #[error("Transfer failed: {0}")]
+    IO(#[from] io::Error),
+    #[error("Transfer incomplete: {0} of {1} bytes")]
+    Incomplete(u64, u64),
+    #[error("Transfer larger than the {0} bytes offered")]
+    TooLarge(u64),
 }
 
 #[derive(Error, Debug)]
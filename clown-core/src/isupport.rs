@@ -0,0 +1,332 @@
+/// Features advertised by the server with RPL_ISUPPORT (005)
+#[derive(Debug, PartialEq, Clone)]
+pub struct ISupport {
+    /// (mode, symbol) ordered from the highest rank to the lowest, PREFIX=(ov)@+
+    prefix: Vec<(char, char)>,
+    /// CHANMODES=A,B,C,D, the modes of D never have a parameter
+    list_modes: String,
+    always_parameter_modes: String,
+    set_parameter_modes: String,
+    /// MONITOR=<limit>, None if the server does not support it
+    monitor: Option<usize>,
+    /// MODES=<count>, changes with a parameter in a single MODE command
+    max_modes: usize,
+    /// EXTBAN=<prefix>,<types>, e.g. ~,qjncrRa
+    extban: Option<(Option<char>, String)>,
+    /// CHANTYPES=#&, the first character of the channel names
+    chantypes: String,
+    /// NICKLEN=<length>, the longer nicknames are truncated by the server
+    nicklen: Option<usize>,
+    /// STATUSMSG=@+, a message to @#chan is only sent to the operators of #chan
+    statusmsg: String,
+}
+
+impl Default for ISupport {
+    fn default() -> Self {
+        Self {
+            prefix: vec![('o', '@'), ('v', '+')],
+            list_modes: "beI".to_string(),
+            always_parameter_modes: "k".to_string(),
+            set_parameter_modes: "l".to_string(),
+            monitor: None,
+            max_modes: DEFAULT_MAX_MODES,
+            extban: None,
+            chantypes: DEFAULT_CHANTYPES.to_string(),
+            nicklen: None,
+            statusmsg: String::new(),
+        }
+    }
+}
+
+/// Channel prefixes of RFC 1459 when the server does not advertise CHANTYPES
+const DEFAULT_CHANTYPES: &str = "#&";
+
+/// Value of RFC 1459 when the server does not advertise MODES
+const DEFAULT_MAX_MODES: usize = 3;
+
+impl ISupport {
+    /// No mode takes a parameter
+    pub fn empty() -> Self {
+        Self {
+            prefix: Vec::new(),
+            list_modes: String::new(),
+            always_parameter_modes: String::new(),
+            set_parameter_modes: String::new(),
+            monitor: None,
+            max_modes: DEFAULT_MAX_MODES,
+            extban: None,
+            chantypes: DEFAULT_CHANTYPES.to_string(),
+            nicklen: None,
+            statusmsg: String::new(),
+        }
+    }
+
+    /// Update the features from the parameters of a 005 reply
+    pub fn update<S: AsRef<str>>(&mut self, tokens: &[S]) {
+        for token in tokens.iter().map(|v| v.as_ref()) {
+            let (key, value) = token.split_once('=').unwrap_or((token, ""));
+            match key {
+                "PREFIX" => self.set_prefix(value),
+                "-PREFIX" => self.prefix = Self::default().prefix,
+                "CHANMODES" => self.set_chanmodes(value),
+                "-CHANMODES" => self.set_chanmodes("beI,k,l,imnpst"),
+                //Without a value, there is no limit
+                "MONITOR" => self.monitor = Some(value.parse().unwrap_or(usize::MAX)),
+                "-MONITOR" => self.monitor = None,
+                //Without a value, there is no limit
+                "MODES" => self.max_modes = value.parse().unwrap_or(usize::MAX),
+                "-MODES" => self.max_modes = DEFAULT_MAX_MODES,
+                "EXTBAN" => self.set_extban(value),
+                "-EXTBAN" => self.extban = None,
+                //Without a value, the server has no channels
+                "CHANTYPES" => self.chantypes = value.to_string(),
+                "-CHANTYPES" => self.chantypes = DEFAULT_CHANTYPES.to_string(),
+                "NICKLEN" => self.nicklen = value.parse().ok(),
+                "-NICKLEN" => self.nicklen = None,
+                "STATUSMSG" => self.statusmsg = value.to_string(),
+                "-STATUSMSG" => self.statusmsg.clear(),
+                _ => {}
+            }
+        }
+    }
+
+    // (qaohv)~&@%+
+    fn set_prefix(&mut self, value: &str) {
+        if let Some(value) = value.strip_prefix('(')
+            && let Some((modes, symbols)) = value.split_once(')')
+        {
+            self.prefix = modes.chars().zip(symbols.chars()).collect();
+        } else if value.is_empty() {
+            self.prefix.clear();
+        }
+    }
+
+    // A,B,C,D
+    fn set_chanmodes(&mut self, value: &str) {
+        let mut groups = value.split(',');
+        self.list_modes = groups.next().unwrap_or_default().to_string();
+        self.always_parameter_modes = groups.next().unwrap_or_default().to_string();
+        self.set_parameter_modes = groups.next().unwrap_or_default().to_string();
+    }
+
+    // ~,qjncrRa or ,ABCNOQRSTUacjmnprswz without prefix
+    fn set_extban(&mut self, value: &str) {
+        self.extban = value
+            .split_once(',')
+            .map(|(prefix, types)| (prefix.chars().next(), types.to_string()));
+    }
+
+    pub fn get_symbol(&self, mode: char) -> Option<char> {
+        self.prefix
+            .iter()
+            .find(|(m, _)| *m == mode)
+            .map(|(_, symbol)| *symbol)
+    }
+
+    pub fn get_prefix_mode(&self, symbol: char) -> Option<char> {
+        self.prefix
+            .iter()
+            .find(|(_, s)| *s == symbol)
+            .map(|(mode, _)| *mode)
+    }
+
+    pub fn is_prefix_mode(&self, mode: char) -> bool {
+        self.get_symbol(mode).is_some()
+    }
+
+    /// 0 is the highest rank, the number of prefixes if the mode is not a prefix
+    pub fn get_rank(&self, mode: char) -> usize {
+        self.prefix
+            .iter()
+            .position(|(m, _)| *m == mode)
+            .unwrap_or(self.prefix.len())
+    }
+
+    pub fn nb_ranks(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// Split a NAMES entry (@+nick) in its prefix modes and the remaining name
+    pub fn split_prefix<'a>(&self, entry: &'a str) -> (Vec<char>, &'a str) {
+        let mut modes = Vec::new();
+        let mut rest = entry;
+        while let Some(symbol) = rest.chars().next()
+            && let Some(mode) = self.get_prefix_mode(symbol)
+        {
+            modes.push(mode);
+            rest = rest.get(symbol.len_utf8()..).unwrap_or_default();
+        }
+        (modes, rest)
+    }
+
+    /// The name without its prefixes (@+nick)
+    pub fn strip_prefix<'a>(&self, entry: &'a str) -> &'a str {
+        entry.trim_start_matches(|v| self.get_prefix_mode(v).is_some())
+    }
+
+    /// The name starts with one of CHANTYPES
+    pub fn is_channel(&self, name: &str) -> bool {
+        name.starts_with(|v| self.chantypes.contains(v))
+    }
+
+    /// The channel of a STATUSMSG target (@#chan), the symbols stop at the first of CHANTYPES
+    pub fn strip_statusmsg<'a>(&self, target: &'a str) -> &'a str {
+        let channel = target
+            .trim_start_matches(|v| self.statusmsg.contains(v) && !self.chantypes.contains(v));
+        if self.is_channel(channel) {
+            channel
+        } else {
+            target
+        }
+    }
+
+    /// Modes with a list of values (ban, exception...), they are not a state of the channel
+    pub fn is_list_mode(&self, mode: char) -> bool {
+        self.list_modes.contains(mode)
+    }
+
+    /// Maximum number of targets of MONITOR, None if not supported
+    pub fn get_monitor_limit(&self) -> Option<usize> {
+        self.monitor
+    }
+
+    /// Maximum length of a nickname, None if not advertised
+    pub fn get_nick_length(&self) -> Option<usize> {
+        self.nicklen
+    }
+
+    /// Maximum number of changes with a parameter in a MODE command
+    pub fn get_max_modes(&self) -> usize {
+        self.max_modes.max(1)
+    }
+
+    /// Prefix of an extended ban, e.g. `~q:` for the quiets, None if not supported
+    pub fn get_extban(&self, kind: char) -> Option<String> {
+        let (prefix, types) = self.extban.as_ref()?;
+        types
+            .contains(kind)
+            .then(|| format!("{}{kind}:", prefix.map(String::from).unwrap_or_default()))
+    }
+
+    /// Does the channel mode need a parameter
+    pub fn takes_parameter(&self, mode: char, is_set: bool) -> bool {
+        if self.is_prefix_mode(mode)
+            || self.list_modes.contains(mode)
+            || self.always_parameter_modes.contains(mode)
+        {
+            true
+        } else {
+            is_set && self.set_parameter_modes.contains(mode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_prefix() {
+        let isupport = ISupport::default();
+        assert_eq!(isupport.get_symbol('o'), Some('@'));
+        assert_eq!(isupport.get_prefix_mode('+'), Some('v'));
+        assert_eq!(isupport.get_rank('o'), 0);
+        assert_eq!(isupport.get_rank('x'), 2);
+    }
+
+    #[test]
+    fn test_update_prefix() {
+        let mut isupport = ISupport::default();
+        isupport.update(&["NETWORK=Libera", "PREFIX=(qaohv)~&@%+", "CHANTYPES=#"]);
+        assert_eq!(isupport.nb_ranks(), 5);
+        assert_eq!(isupport.get_symbol('h'), Some('%'));
+        assert_eq!(isupport.get_rank('q'), 0);
+        assert_eq!(isupport.get_rank('v'), 4);
+        assert_eq!(isupport.split_prefix("~@bob"), (vec!['q', 'o'], "bob"));
+        assert_eq!(isupport.split_prefix("bob"), (vec![], "bob"));
+        assert_eq!(isupport.strip_prefix("~@bob"), "bob");
+        assert_eq!(isupport.strip_prefix("!bob"), "!bob");
+
+        isupport.update(&["-PREFIX"]);
+        assert_eq!(isupport.nb_ranks(), 2);
+    }
+
+    #[test]
+    fn test_chantypes() {
+        let mut isupport = ISupport::default();
+        assert!(isupport.is_channel("#rust"));
+        assert!(isupport.is_channel("&local"));
+        assert!(!isupport.is_channel("bob"));
+        isupport.update(&["CHANTYPES=#!"]);
+        assert!(isupport.is_channel("!12345chan"));
+        assert!(!isupport.is_channel("&local"));
+        isupport.update(&["CHANTYPES"]);
+        assert!(!isupport.is_channel("#rust"));
+        isupport.update(&["-CHANTYPES"]);
+        assert!(isupport.is_channel("#rust"));
+    }
+
+    #[test]
+    fn test_statusmsg() {
+        let mut isupport = ISupport::default();
+        assert_eq!(isupport.strip_statusmsg("@#rust"), "@#rust");
+        isupport.update(&["STATUSMSG=~&@%+"]);
+        assert_eq!(isupport.strip_statusmsg("@#rust"), "#rust");
+        assert_eq!(isupport.strip_statusmsg("%+#rust"), "#rust");
+        assert_eq!(isupport.strip_statusmsg("@&local"), "&local");
+        assert_eq!(isupport.strip_statusmsg("&&local"), "&&local");
+        assert_eq!(isupport.strip_statusmsg("@bob"), "@bob");
+        isupport.update(&["-STATUSMSG"]);
+        assert_eq!(isupport.strip_statusmsg("@#rust"), "@#rust");
+    }
+
+    #[test]
+    fn test_takes_parameter() {
+        let mut isupport = ISupport::default();
+        isupport.update(&["CHANMODES=eIbq,k,flj,CFLMPQScgimnprstuz"]);
+        assert!(isupport.takes_parameter('o', false));
+        assert!(isupport.takes_parameter('q', true));
+        assert!(isupport.takes_parameter('k', false));
+        assert!(isupport.takes_parameter('l', true));
+        assert!(!isupport.takes_parameter('l', false));
+        assert!(!isupport.takes_parameter('m', true));
+        assert!(isupport.is_list_mode('b'));
+        assert!(!isupport.is_list_mode('k'));
+    }
+
+    #[test]
+    fn test_monitor() {
+        let mut isupport = ISupport::default();
+        assert_eq!(isupport.get_monitor_limit(), None);
+        isupport.update(&["MONITOR=100"]);
+        assert_eq!(isupport.get_monitor_limit(), Some(100));
+        isupport.update(&["MONITOR"]);
+        assert_eq!(isupport.get_monitor_limit(), Some(usize::MAX));
+        isupport.update(&["-MONITOR"]);
+        assert_eq!(isupport.get_monitor_limit(), None);
+    }
+
+    #[test]
+    fn test_modes_and_extban() {
+        let mut isupport = ISupport::default();
+        assert_eq!(isupport.get_max_modes(), 3);
+        assert_eq!(isupport.get_extban('q'), None);
+        isupport.update(&["MODES=4", "EXTBAN=~,qjncrRa"]);
+        assert_eq!(isupport.get_max_modes(), 4);
+        assert_eq!(isupport.get_extban('q').as_deref(), Some("~q:"));
+        assert_eq!(isupport.get_extban('m'), None);
+        isupport.update(&["MODES", "EXTBAN=,ABCNOQRSTUacjmnprswz"]);
+        assert_eq!(isupport.get_max_modes(), usize::MAX);
+        assert_eq!(isupport.get_extban('m').as_deref(), Some("m:"));
+    }
+
+    #[test]
+    fn test_nicklen() {
+        let mut isupport = ISupport::default();
+        assert_eq!(isupport.get_nick_length(), None);
+        isupport.update(&["NICKLEN=16"]);
+        assert_eq!(isupport.get_nick_length(), Some(16));
+        isupport.update(&["-NICKLEN"]);
+        assert_eq!(isupport.get_nick_length(), None);
+    }
+}
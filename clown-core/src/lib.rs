@@ -1,7 +1,15 @@
+pub mod bouncer;
+pub mod capability;
 pub mod client;
 pub mod command;
 pub mod conn;
+pub mod ctcp;
+pub mod dcc;
 pub mod error;
+pub mod isupport;
 pub mod message;
+pub mod mode;
 pub mod outgoing;
 pub mod response;
+pub mod sts;
+pub mod websocket;
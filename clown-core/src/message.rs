@@ -27,6 +27,17 @@ impl ServerMessage {
         self.message.source()
     }
 
+    /// user@host of the source
+    pub fn source_user_host(&self) -> Option<String> {
+        self.message
+            .source_user_host()
+            .map(|(user, host)| format!("{user}@{host}"))
+    }
+
+    pub fn tag(&self, key: &str) -> Option<std::borrow::Cow<'_, str>> {
+        self.message.tag(key)
+    }
+
     pub fn reply(&self) -> Response {
         if let Some(command) = self.message.command_name() {
             let params = self.message.parameters();
@@ -246,6 +257,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_batch_command() {
+        let message = create_message(
+            b"@time=2024-05-20T14:30:05.000Z :irc.example.com BATCH +ref chathistory #chan",
+        )
+        .unwrap();
+        let server_message = ServerMessage::new(message);
+        assert_eq!(
+            server_message.tag("time").as_deref(),
+            Some("2024-05-20T14:30:05.000Z")
+        );
+        assert!(
+            matches!(server_message.reply(), Response::Cmd(Command::Batch(reference, params))
+            if reference == "+ref" && params == vec!["chathistory", "#chan"])
+        );
+
+        let message = create_message(b":irc.example.com BATCH -ref").unwrap();
+        let reply = ServerMessage::new(message).reply();
+        assert!(
+            matches!(reply, Response::Cmd(Command::Batch(reference, params))
+            if reference == "-ref" && params.is_empty())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bouncer_command() {
+        let message =
+            create_message(b":bouncer BOUNCER NETWORK 42 name=Libera\\sChat;host=irc.libera.chat")
+                .unwrap();
+        assert!(
+            matches!(ServerMessage::new(message).reply(), Response::Cmd(Command::Bouncer(sub_command, params))
+            if sub_command == "NETWORK" && params == vec!["42", "name=Libera\\sChat;host=irc.libera.chat"])
+        );
+
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::Bouncer("BIND".to_string(), vec!["42".to_string()])
+            .write(&mut writer)
+            .await
+            .unwrap();
+        assert_eq!(writer.buffer(), b"BOUNCER BIND 42\r\n");
+    }
+
+    #[test]
+    fn test_cap_command_keeps_capabilities() {
+        let message = create_message(b":irc.example.com CAP * LS :batch server-time").unwrap();
+        let reply = ServerMessage::new(message).reply();
+        assert!(matches!(reply, Response::Cmd(Command::Cap(arguments))
+            if arguments == "* LS :batch server-time"));
+    }
+
     #[test]
     fn test_topic_command() {
         let message = create_message(b":farineA!farine4@hidden TOPIC #rust-spam :yo").unwrap();
@@ -254,4 +315,335 @@ mod tests {
         assert!(matches!(reply, Response::Cmd(Command::Topic(channel, msg))
             if msg == "yo" && channel == "#rust-spam"));
     }
+
+    #[test]
+    fn test_ack_command() {
+        let message = create_message(b"@label=abc :irc.example.com ACK").unwrap();
+        let server_message = ServerMessage::new(message);
+        assert_eq!(server_message.tag("label").as_deref(), Some("abc"));
+        assert!(matches!(
+            server_message.reply(),
+            Response::Cmd(Command::Ack)
+        ));
+    }
+
+    #[test]
+    fn test_join_commands() {
+        let message = create_message(b":alice!al@host.com JOIN #rust").unwrap();
+        let server_message = ServerMessage::new(message);
+        assert_eq!(
+            server_message.source_user_host().as_deref(),
+            Some("al@host.com")
+        );
+        assert!(matches!(server_message.reply(),
+            Response::Cmd(Command::Join(channel)) if channel == "#rust"));
+
+        let message = create_message(b":alice JOIN :#rust").unwrap();
+        let server_message = ServerMessage::new(message);
+        assert_eq!(server_message.source_user_host(), None);
+        assert!(matches!(server_message.reply(),
+            Response::Cmd(Command::Join(channel)) if channel == "#rust"));
+
+        let message =
+            create_message(b":alice!al@host.com JOIN #rust alice_acc :Alice Liddell").unwrap();
+        let server_message = ServerMessage::new(message);
+        assert!(matches!(server_message.reply(),
+            Response::Cmd(Command::ExtendedJoin(channel, account, realname))
+                if channel == "#rust" && account == "alice_acc" && realname == "Alice Liddell"));
+    }
+
+    #[test]
+    fn test_user_notify_commands() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+
+        assert!(matches!(reply(b":alice AWAY :Gone to lunch"),
+            Response::Cmd(Command::Away(Some(message))) if message == "Gone to lunch"));
+        assert!(matches!(
+            reply(b":alice AWAY"),
+            Response::Cmd(Command::Away(None))
+        ));
+        assert!(matches!(reply(b":alice ACCOUNT alice_acc"),
+            Response::Cmd(Command::Account(account)) if account == "alice_acc"));
+        assert!(matches!(reply(b":alice CHGHOST al new.host"),
+            Response::Cmd(Command::ChgHost(user, host)) if user == "al" && host == "new.host"));
+        assert!(matches!(reply(b":alice SETNAME :Alice Liddell"),
+            Response::Cmd(Command::SetName(realname)) if realname == "Alice Liddell"));
+    }
+
+    #[test]
+    fn test_isupport_reply() {
+        let message = create_message(
+            b":irc.example.com 005 me PREFIX=(ov)@+ CHANTYPES=# :are supported by this server",
+        )
+        .unwrap();
+        let server_message = ServerMessage::new(message);
+        assert!(matches!(server_message.reply(),
+            Response::Rpl(ResponseNumber::ISupport(tokens, text))
+                if tokens == vec!["PREFIX=(ov)@+".to_string(), "CHANTYPES=#".to_string()]
+                    && text == "are supported by this server"));
+    }
+
+    #[test]
+    fn test_mode_command() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(matches!(reply(b":alice MODE #rust +o-v bob carl"),
+            Response::Cmd(Command::Mode(target, modes))
+                if target == "#rust" && modes == "+o-v bob carl"));
+        assert!(matches!(reply(b":me MODE me :+iw"),
+            Response::Cmd(Command::Mode(target, modes)) if target == "me" && modes == "+iw"));
+        assert!(
+            matches!(reply(b":irc.example.com 324 me #rust +ntk secret"),
+            Response::Rpl(ResponseNumber::ChannelModeIs(channel, modes))
+                if channel == "#rust" && modes == "+ntk secret")
+        );
+    }
+
+    #[test]
+    fn test_notify_replies() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(matches!(reply(b":irc.example.com 303 me :alice bob"),
+            Response::Rpl(ResponseNumber::Ison(nicks)) if nicks == vec!["alice", "bob"]));
+        assert!(matches!(reply(b":irc.example.com 303 me :"),
+            Response::Rpl(ResponseNumber::Ison(nicks)) if nicks.is_empty()));
+        assert!(
+            matches!(reply(b":irc.example.com 730 me :alice!al@host.com,bob!b@host"),
+            Response::Rpl(ResponseNumber::MonOnline(targets))
+                if targets == vec!["alice!al@host.com", "bob!b@host"])
+        );
+        assert!(matches!(reply(b":irc.example.com 731 me :alice"),
+            Response::Rpl(ResponseNumber::MonOffline(targets)) if targets == vec!["alice"]));
+    }
+
+    #[test]
+    fn test_logged_in_reply() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(
+            matches!(reply(b":irc.example.com 900 me me!u@host me :You are now logged in as me"),
+            Response::Rpl(ResponseNumber::LoggedIn(content)) if content == "You are now logged in as me")
+        );
+    }
+
+    #[test]
+    fn test_invite_commands() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(matches!(reply(b":alice!al@host INVITE me :#secret"),
+            Response::Cmd(Command::Invite(nick, channel)) if nick == "me" && channel == "#secret"));
+        assert!(matches!(reply(b":alice!al@host INVITE bob #secret"),
+            Response::Cmd(Command::Invite(nick, channel)) if nick == "bob" && channel == "#secret"));
+        assert!(matches!(reply(b":irc.example.com 341 me bob #secret"),
+            Response::Rpl(ResponseNumber::Invite(nick, channel))
+                if nick == "bob" && channel == "#secret"));
+    }
+
+    #[test]
+    fn test_whois_replies() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(
+            matches!(reply(b":irc.example.com 311 me alice ~al host.example.com * :Alice Liddell"),
+            Response::Rpl(ResponseNumber::WhoisUser(nick, user, host, realname))
+                if nick == "alice" && user == "~al" && host == "host.example.com" && realname == "Alice Liddell")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 314 me bob bo old.example.com * :Bob"),
+            Response::Rpl(ResponseNumber::WhowasUser(nick, ..)) if nick == "bob")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 312 me alice irc.example.com :Stockholm, SE"),
+            Response::Rpl(ResponseNumber::WhoisServer(nick, server, info))
+                if nick == "alice" && server == "irc.example.com" && info == "Stockholm, SE")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 313 me alice :is an IRC operator"),
+            Response::Rpl(ResponseNumber::WhoisOperator(nick, _)) if nick == "alice")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 317 me alice 125 1700000000 :seconds idle, signon time"),
+            Response::Rpl(ResponseNumber::WhoisIdleTime(nick, 125, Some(1700000000))) if nick == "alice")
+        );
+        assert!(matches!(
+            reply(b":irc.example.com 317 me alice 5 :seconds idle"),
+            Response::Rpl(ResponseNumber::WhoisIdleTime(_, 5, None))
+        ));
+        assert!(
+            matches!(reply(b":irc.example.com 319 me alice :@#rust +#clown"),
+            Response::Rpl(ResponseNumber::WhoisChannels(nick, channels))
+                if nick == "alice" && channels == vec!["@#rust", "+#clown"])
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 330 me alice alice_account :is logged in as"),
+            Response::Rpl(ResponseNumber::WhoisAccount(nick, account)) if nick == "alice" && account == "alice_account")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 338 me alice ~al@10.0.0.1 10.0.0.1 :Actual user@host, Actual IP"),
+            Response::Rpl(ResponseNumber::WhoisActually(nick, actually))
+                if nick == "alice" && actually == "~al@10.0.0.1 10.0.0.1")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 671 me alice :is using a secure connection"),
+            Response::Rpl(ResponseNumber::WhoisSecure(nick)) if nick == "alice")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 318 me alice :End of /WHOIS list."),
+            Response::Rpl(ResponseNumber::EndOfWhois(nick)) if nick == "alice")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 369 me bob :End of WHOWAS"),
+            Response::Rpl(ResponseNumber::EndOfWhowas(nick)) if nick == "bob")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_whois() {
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::Whois("alice".to_string())
+            .write(&mut writer)
+            .await
+            .unwrap();
+        Command::Whowas("bob".to_string())
+            .write(&mut writer)
+            .await
+            .unwrap();
+        assert_eq!(writer.buffer(), b"WHOIS alice alice\r\nWHOWAS bob\r\n");
+    }
+
+    #[test]
+    fn test_channel_list() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(
+            matches!(reply(b":irc.example.com 322 me #rust 1234 :[+nt] The Rust language"),
+            Response::Rpl(ResponseNumber::List(channel, users, topic))
+                if channel == "#rust" && users == 1234 && topic == "[+nt] The Rust language")
+        );
+        assert!(matches!(reply(b":irc.example.com 322 me #empty 3"),
+            Response::Rpl(ResponseNumber::List(channel, 3, topic)) if channel == "#empty" && topic.is_empty()));
+        assert!(matches!(
+            reply(b":irc.example.com 323 me :End of /LIST"),
+            Response::Rpl(ResponseNumber::ListEnd(_))
+        ));
+    }
+
+    #[test]
+    fn test_mode_lists() {
+        let reply = |line: &[u8]| ServerMessage::new(create_message(line).unwrap()).reply();
+        assert!(
+            matches!(reply(b":irc.example.com 367 me #rust *!*@spam.example.com alice!al@host 1700000000"),
+            Response::Rpl(ResponseNumber::BanList(channel, entry))
+                if channel == "#rust" && entry.mask == "*!*@spam.example.com"
+                    && entry.setter.as_deref() == Some("alice!al@host") && entry.set_at == Some(1700000000))
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 348 me #rust *!*@friend.example.com"),
+            Response::Rpl(ResponseNumber::ExceptionList(channel, entry))
+                if channel == "#rust" && entry.setter.is_none() && entry.set_at.is_none())
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 346 me #rust $a:alice bob :1700000000"),
+            Response::Rpl(ResponseNumber::InviteList(_, entry))
+                if entry.mask == "$a:alice" && entry.set_at == Some(1700000000))
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 368 me #rust :End of channel ban list"),
+            Response::Rpl(ResponseNumber::EndOfBanList(channel)) if channel == "#rust")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 349 me #rust :End of channel exception list"),
+            Response::Rpl(ResponseNumber::EndOfExceptionList(channel)) if channel == "#rust")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 347 me #rust :End of channel invite list"),
+            Response::Rpl(ResponseNumber::EndOfInviteList(channel)) if channel == "#rust")
+        );
+        assert!(
+            matches!(reply(b":irc.example.com 477 me #rust :Cannot join channel (+r)"),
+            Response::Rpl(ResponseNumber::NeedReggedNick(channel, _)) if channel == "#rust")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_notify_commands() {
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::Monitor(
+            "+".to_string(),
+            vec!["alice".to_string(), "bob".to_string()],
+        )
+        .write(&mut writer)
+        .await
+        .unwrap();
+        Command::Ison(vec!["alice".to_string(), "bob".to_string()])
+            .write(&mut writer)
+            .await
+            .unwrap();
+        assert_eq!(
+            writer.buffer(),
+            b"MONITOR + alice,bob\r\nISON alice bob\r\n"
+        );
+    }
+
+    #[test]
+    fn test_tagmsg_command() {
+        let message =
+            create_message(b"@+typing=active;+draft/reply=abc :alice!a@host TAGMSG #rust").unwrap();
+        let server_message = ServerMessage::new(message);
+        assert_eq!(server_message.tag("+typing").as_deref(), Some("active"));
+        assert_eq!(server_message.tag("+draft/reply").as_deref(), Some("abc"));
+        assert!(matches!(server_message.reply(),
+            Response::Cmd(Command::TagMsg(target)) if target == "#rust"));
+    }
+
+    #[tokio::test]
+    async fn test_write_join_keys() {
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::JoinKeys(vec![
+            ("#rust".to_string(), None),
+            ("#secret".to_string(), Some("key1".to_string())),
+            ("#linux".to_string(), None),
+            ("#private".to_string(), Some("key2".to_string())),
+        ])
+        .write(&mut writer)
+        .await
+        .unwrap();
+        Command::JoinKeys(vec![("#rust".to_string(), None)])
+            .write(&mut writer)
+            .await
+            .unwrap();
+        assert_eq!(
+            writer.buffer(),
+            b"JOIN #secret,#private,#rust,#linux key1,key2\r\nJOIN #rust\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_tagged_command() {
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::Labeled(
+            "l1".to_string(),
+            Box::new(Command::Tagged(
+                vec![
+                    ("+draft/react".to_string(), "a b".to_string()),
+                    ("+draft/reply".to_string(), "abc".to_string()),
+                ],
+                Box::new(Command::TagMsg("#rust".to_string())),
+            )),
+        )
+        .write(&mut writer)
+        .await
+        .unwrap();
+        assert_eq!(
+            writer.buffer(),
+            b"@label=l1;+draft/react=a\\sb;+draft/reply=abc TAGMSG #rust\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_labeled_command() {
+        let mut writer = tokio::io::BufWriter::new(Vec::new());
+        Command::Labeled(
+            "a b".to_string(),
+            Box::new(Command::PrivMsg("#rust".to_string(), "hello".to_string())),
+        )
+        .write(&mut writer)
+        .await
+        .unwrap();
+        assert_eq!(writer.buffer(), b"@label=a\\sb PRIVMSG #rust :hello\r\n");
+    }
 }
@@ -0,0 +1,159 @@
+use crate::isupport::ISupport;
+
+/// A single mode change of a MODE command
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModeChange {
+    pub is_set: bool,
+    pub mode: char,
+    pub parameter: Option<String>,
+}
+
+/// Parse the modes of a channel MODE command: +o-v alice bob
+pub fn parse_channel_modes(modes: &str, isupport: &ISupport) -> Vec<ModeChange> {
+    let mut tokens = modes.split_ascii_whitespace();
+    let Some(letters) = tokens.next() else {
+        return Vec::new();
+    };
+
+    let mut is_set = true;
+    let mut changes = Vec::new();
+    for mode in letters.chars() {
+        match mode {
+            '+' => is_set = true,
+            '-' => is_set = false,
+            _ => {
+                let parameter = if isupport.takes_parameter(mode, is_set) {
+                    tokens.next().map(|v| v.to_string())
+                } else {
+                    None
+                };
+                changes.push(ModeChange {
+                    is_set,
+                    mode,
+                    parameter,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// User modes never have parameters: +iw
+pub fn parse_user_modes(modes: &str) -> Vec<ModeChange> {
+    parse_channel_modes(
+        modes.split_ascii_whitespace().next().unwrap_or_default(),
+        &ISupport::empty(),
+    )
+}
+
+/// Opposite of the parsing: +o-v alice bob
+pub fn format_modes(changes: &[ModeChange]) -> String {
+    let mut letters = String::new();
+    let mut is_set = None;
+    for change in changes {
+        if is_set != Some(change.is_set) {
+            letters.push(if change.is_set { '+' } else { '-' });
+            is_set = Some(change.is_set);
+        }
+        letters.push(change.mode);
+    }
+    std::iter::once(letters.as_str())
+        .chain(changes.iter().filter_map(|v| v.parameter.as_deref()))
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// The changes split in several MODE commands, at most `max_modes` parameters in each
+pub fn batch_modes(changes: &[ModeChange], max_modes: usize) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<ModeChange> = Vec::new();
+    for change in changes {
+        let count = batch.iter().filter(|v| v.parameter.is_some()).count();
+        if change.parameter.is_some() && count >= max_modes.max(1) {
+            batches.push(format_modes(&batch));
+            batch.clear();
+        }
+        batch.push(change.clone());
+    }
+    if !batch.is_empty() {
+        batches.push(format_modes(&batch));
+    }
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(is_set: bool, mode: char, parameter: Option<&str>) -> ModeChange {
+        ModeChange {
+            is_set,
+            mode,
+            parameter: parameter.map(|v| v.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_prefix_modes() {
+        let isupport = ISupport::default();
+        assert_eq!(
+            parse_channel_modes("+o-v alice bob", &isupport),
+            vec![
+                change(true, 'o', Some("alice")),
+                change(false, 'v', Some("bob"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mixed_modes() {
+        let isupport = ISupport::default();
+        assert_eq!(
+            parse_channel_modes("+mlk-l+b 10 secret *!*@spam", &isupport),
+            vec![
+                change(true, 'm', None),
+                change(true, 'l', Some("10")),
+                change(true, 'k', Some("secret")),
+                change(false, 'l', None),
+                change(true, 'b', Some("*!*@spam")),
+            ]
+        );
+        assert!(parse_channel_modes("", &isupport).is_empty());
+    }
+
+    #[test]
+    fn test_parse_user_modes() {
+        assert_eq!(
+            parse_user_modes("+iw-o"),
+            vec![
+                change(true, 'i', None),
+                change(true, 'w', None),
+                change(false, 'o', None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_modes() {
+        let isupport = ISupport::default();
+        for modes in ["+o-v alice bob", "+mlk-l+b 10 secret *!*@spam", "-i"] {
+            assert_eq!(format_modes(&parse_channel_modes(modes, &isupport)), modes);
+        }
+        assert_eq!(format_modes(&parse_channel_modes("o+", &isupport)), "+o");
+    }
+
+    #[test]
+    fn test_batch_modes() {
+        let isupport = ISupport::default();
+        let changes = parse_channel_modes("+ooo-v+m alice bob carol dave", &isupport);
+        assert_eq!(
+            batch_modes(&changes, 3),
+            vec!["+ooo alice bob carol", "-v+m dave"]
+        );
+        assert_eq!(
+            batch_modes(&changes, 10),
+            vec!["+ooo-v+m alice bob carol dave"]
+        );
+        assert!(batch_modes(&[], 3).is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::capability::SharedCapabilities;
 use crate::command::Command;
 use crate::command::CommandReceiver;
 use crate::error::IRCIOError;
@@ -16,6 +17,8 @@ use tokio::sync::mpsc;
 pub struct Outgoing {
     receiver: Option<CommandReceiver>,
     message_sender: Option<MessageSender>,
+    capabilities: SharedCapabilities,
+    is_secure: bool,
 }
 
 impl Outgoing {
@@ -32,13 +35,27 @@ impl Outgoing {
                 Command::Pong(token).write(writer).await?;
                 writer.flush().await?;
             }
-            Response::Cmd(Command::Cap(_)) => {
-                Command::Cap("END".into()).write(writer).await?;
+            Response::Cmd(Command::Cap(arguments)) => {
+                let commands = self.capabilities.handle(&arguments);
+                //STS: the registration must not be completed over plaintext
+                if !self.is_secure
+                    && let Some(port) = self.capabilities.get_sts().and_then(|v| v.port)
+                {
+                    self.forward(server_message).await?;
+                    return Err(IRCIOError::StsUpgrade(port));
+                }
+                for command in commands {
+                    command.write(writer).await?;
+                }
                 writer.flush().await?;
             }
             _ => {}
         }
 
+        self.forward(server_message).await
+    }
+
+    async fn forward(&self, server_message: ServerMessage) -> Result<(), IRCIOError> {
         if let Some(sender) = &self.message_sender {
             sender
                 .inner
@@ -49,6 +66,21 @@ impl Outgoing {
         Ok(())
     }
 
+    /// Network of a soju bouncer to bind to during the registration
+    pub fn set_bouncer_network(&mut self, network: Option<String>) {
+        self.capabilities.set_bouncer_network(network);
+    }
+
+    /// The capabilities negotiated on this connection
+    pub fn capabilities(&self) -> SharedCapabilities {
+        self.capabilities.clone()
+    }
+
+    /// The connection is made with TLS
+    pub fn set_secure(&mut self, is_secure: bool) {
+        self.is_secure = is_secure;
+    }
+
     pub async fn process<R, W>(
         &mut self,
         reader: BufReader<R>,
@@ -86,6 +118,11 @@ impl Outgoing {
                 cmd = receiver.inner.recv() => {
                     match cmd {
                         Some(cmd)=> {
+                            //The label cannot be sent if the server does not support it
+                            let cmd = match cmd {
+                                Command::Labeled(_, command) if !self.capabilities.is_enabled("labeled-response") => *command,
+                                cmd => cmd
+                            };
                             cmd.write(&mut writer).await?;
                             writer.flush().await?;
                         }
@@ -129,6 +166,10 @@ impl CommandSender {
             .map_err(|_| IRCIOError::SendCommand)
     }
 
+    pub fn send_labeled(&mut self, label: String, in_command: Command) -> Result<(), IRCIOError> {
+        self.send(Command::Labeled(label, Box::new(in_command)))
+    }
+
     pub fn is_closed(&self) -> bool {
         self.inner.is_closed()
     }
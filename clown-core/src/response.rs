@@ -25,6 +25,14 @@ impl fmt::Display for Response {
     }
 }
 
+/// Entry of a +b, +e or +I list, the setter and the time are not sent by every server
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListEntry {
+    pub mask: String,
+    pub setter: Option<String>,
+    pub set_at: Option<u64>,
+}
+
 /// All standard IRC RPL (Reply) numerics.
 /// See: RFC 1459, RFC 2812
 #[derive(Debug)]
@@ -38,7 +46,7 @@ pub enum ResponseNumber {
     /// 004: Server info and supported modes
     MyInfo(String),
     /// 005: ISUPPORT/feature list (may be multi-line)
-    Bounce(String),
+    ISupport(Vec<String>, String),
 
     /// 200: Link info
     TraceLink(String),
@@ -152,8 +160,8 @@ pub enum ResponseNumber {
     Away(String),
     /// 302: User host
     UserHost(String),
-    /// 303: ISON reply
-    Ison(String),
+    /// 303: ISON reply, the nicknames online
+    Ison(Vec<String>),
     /// 304: Text (unused)
     Text(String),
     /// 305: You are no longer marked as away
@@ -161,55 +169,60 @@ pub enum ResponseNumber {
     /// 306: You are now marked as away
     NowAway(String),
 
-    /// 311: WHOIS user
-    WhoisUser(String),
-    /// 312: WHOIS server
-    WhoisServer(String),
-    /// 313: WHOIS operator
-    WhoisOperator(String),
-    /// 314: WHOWAS user
-    WhowasUser(String),
+    /// 311: WHOIS user <nick> <user> <host> <realname>
+    WhoisUser(String, String, String, String),
+    /// 312: WHOIS server <nick> <server> <server info>
+    WhoisServer(String, String, String),
+    /// 313: WHOIS operator <nick>, Text
+    WhoisOperator(String, String),
+    /// 314: WHOWAS user <nick> <user> <host> <realname>
+    WhowasUser(String, String, String, String),
     /// 315: End of WHO
     EndOfWho(String),
     /// 316: WHOIS idle
     WhoisIdle(String),
-    /// 317: WHOIS idle time
-    WhoisIdleTime(String),
-    /// 318: End of WHOIS
+    /// 317: WHOIS idle time <nick> <idle seconds> [<signon>]
+    WhoisIdleTime(String, u64, Option<u64>),
+    /// 318: End of WHOIS <nick>
     EndOfWhois(String),
-    /// 319: WHOIS channels
-    WhoisChannels(String),
+    /// 319: WHOIS channels <nick>, channels with their prefix
+    WhoisChannels(String, Vec<String>),
 
     /// 321: List start
     ListStart(String),
-    /// 322: List
-    List(String),
+    /// 322: List <channel> <users> <topic>
+    List(String, usize, String),
     /// 323: End of list
     ListEnd(String),
 
     /// 324: Channel mode
-    ChannelModeIs(String),
+    ChannelModeIs(String /*channel*/, String /*modes*/),
     /// 325: Unique channel ID
     UniqueOpIs(String),
 
     /// 331: No topic set
     NoTopic(String),
+    /// 330: WHOIS account <nick> <account>
+    WhoisAccount(String, String),
     /// 332: <channel>, Topic
     Topic(String, String),
     /// 333: Topic <channel> <nick> <setat>
     TopicWhoTime(String, String, u64),
 
-    /// 341: INVITE confirmation
-    Invite(String),
+    /// 338: WHOIS actual host <nick>, the host and/or the IP address
+    WhoisActually(String, String),
+
+    /// 341: INVITE confirmation <nick> <channel>
+    Invite(String, String),
     /// 342: Summon answer
     SummonAnswer(String),
-    /// 346: Invite list
-    InviteList(String),
-    /// 347: End of invite list
+    /// 346: Invite list <channel> <mask> [<setter> <setat>]
+    InviteList(String, ListEntry),
+    /// 347: End of invite list <channel>
     EndOfInviteList(String),
-    /// 348: Exception list
-    ExceptionList(String),
-    /// 349: End of exception list
+    /// 348: Exception list <channel> <mask> [<setter> <setat>]
+    ExceptionList(String, ListEntry),
+    /// 349: End of exception list <channel>
     EndOfExceptionList(String),
 
     /// 351: Version
@@ -236,11 +249,11 @@ pub enum ResponseNumber {
     EndOfLinks(String),
     /// 366: End of NAMES
     EndOfNames(String),
-    /// 367: Ban list
-    BanList(String),
-    /// 368: End of ban list
+    /// 367: Ban list <channel> <mask> [<setter> <setat>]
+    BanList(String, ListEntry),
+    /// 368: End of ban list <channel>
     EndOfBanList(String),
-    /// 369: End of WHOWAS
+    /// 369: End of WHOWAS <nick>
     EndOfWhowas(String),
 
     /// 371: Info
@@ -272,6 +285,23 @@ pub enum ResponseNumber {
     /// 395: No users
     NoUsers(String),
 
+    /// 671: WHOIS secure connection <nick>
+    WhoisSecure(String),
+    /// 730: MONITOR targets online, nick!user@host
+    MonOnline(Vec<String>),
+    /// 731: MONITOR targets offline
+    MonOffline(Vec<String>),
+    /// 732: MONITOR list
+    MonList(String),
+    /// 733: End of MONITOR list
+    EndOfMonList(String),
+
+    /// 477: The channel needs an account <channel>
+    NeedReggedNick(String, String),
+
+    /// 900: Logged in to an account
+    LoggedIn(String),
+
     Err(u16, String),
 
     /// Any other reply not explicitly listed
@@ -295,7 +325,15 @@ impl ResponseBuilder {
             2 => YourHost(string_to_send),
             3 => Created(string_to_send),
             4 => MyInfo(string_to_send),
-            5 => Bounce(string_to_send),
+            5 => ISupport(
+                parameters
+                    .unwrap_or_default()
+                    .split_ascii_whitespace()
+                    .skip(1)
+                    .map(|v| v.to_string())
+                    .collect(),
+                trailing.unwrap_or_default().to_string(),
+            ),
             200 => TraceLink(string_to_send),
             201 => TraceConnecting(string_to_send),
             202 => TraceHandshake(string_to_send),
@@ -346,24 +384,82 @@ impl ResponseBuilder {
             300 => None(string_to_send),
             301 => Away(string_to_send),
             302 => UserHost(string_to_send),
-            303 => Ison(string_to_send),
+            303 => Ison(
+                string_to_send
+                    .split_ascii_whitespace()
+                    .map(|v| v.to_string())
+                    .collect(),
+            ),
             304 => Text(string_to_send),
             305 => UnAway(string_to_send),
             306 => NowAway(string_to_send),
-            311 => WhoisUser(string_to_send),
-            312 => WhoisServer(string_to_send),
-            313 => WhoisOperator(string_to_send),
-            314 => WhowasUser(string_to_send),
+            311 | 314 if let Some([nick, user, host, ..]) = Self::whois_parameters(parameters) => {
+                let realname = trailing.unwrap_or_default().to_string();
+                if reply_number == 311 {
+                    WhoisUser(nick, user, host, realname)
+                } else {
+                    WhowasUser(nick, user, host, realname)
+                }
+            }
+            312 if let Some([nick, server, ..]) = Self::whois_parameters(parameters) => {
+                WhoisServer(nick, server, trailing.unwrap_or_default().to_string())
+            }
+            313 if let Some([nick, ..]) = Self::whois_parameters(parameters) => {
+                WhoisOperator(nick, string_to_send)
+            }
             315 => EndOfWho(string_to_send),
             316 => WhoisIdle(string_to_send),
-            317 => WhoisIdleTime(string_to_send),
-            318 => EndOfWhois(string_to_send),
-            319 => WhoisChannels(string_to_send),
+            317 if let Some([nick, idle, signon, ..]) = Self::whois_parameters(parameters)
+                && let Ok(idle) = idle.parse::<u64>() =>
+            {
+                WhoisIdleTime(nick, idle, signon.parse::<u64>().ok())
+            }
+            318 if let Some([nick, ..]) = Self::whois_parameters(parameters) => EndOfWhois(nick),
+            319 if let Some([nick, ..]) = Self::whois_parameters(parameters) => WhoisChannels(
+                nick,
+                trailing
+                    .unwrap_or_default()
+                    .split_ascii_whitespace()
+                    .map(|v| v.to_string())
+                    .collect(),
+            ),
             321 => ListStart(string_to_send),
-            322 => List(string_to_send),
+            322 if let Some(parameters) = parameters => {
+                let mut parameters = parameters.split_ascii_whitespace().skip(1);
+                if let Some(channel) = parameters.next()
+                    && let Some(users) = parameters.next().and_then(|v| v.parse::<usize>().ok())
+                {
+                    List(
+                        channel.to_string(),
+                        users,
+                        trailing.unwrap_or_default().to_string(),
+                    )
+                } else {
+                    Unknown(reply_number, string_to_send)
+                }
+            }
             323 => ListEnd(string_to_send),
-            324 => ChannelModeIs(string_to_send),
+            324 if let Some(parameters) = parameters => {
+                let mut parameters = parameters.split_ascii_whitespace().skip(1);
+                match parameters.next() {
+                    Some(channel) => ChannelModeIs(
+                        channel.to_string(),
+                        parameters
+                            .chain(
+                                trailing
+                                    .into_iter()
+                                    .flat_map(|v| v.split_ascii_whitespace()),
+                            )
+                            .collect::<Vec<&str>>()
+                            .join(" "),
+                    ),
+                    _ => Unknown(reply_number, string_to_send),
+                }
+            }
             325 => UniqueOpIs(string_to_send),
+            330 if let Some([nick, account, ..]) = Self::whois_parameters(parameters) => {
+                WhoisAccount(nick, account)
+            }
             331 => NoTopic(string_to_send),
             332 if let Some(parameters) = parameters => {
                 let parameter = parameters.split_ascii_whitespace().rfind(|v| !v.is_empty());
@@ -386,12 +482,43 @@ impl ResponseBuilder {
                     Unknown(reply_number, string_to_send)
                 }
             }
-            341 => Invite(string_to_send),
+            341 => {
+                //The channel may be sent as the trailing parameter
+                let mut parameters = parameters
+                    .unwrap_or_default()
+                    .split_ascii_whitespace()
+                    .skip(1)
+                    .chain(trailing);
+                if let Some(nick) = parameters.next()
+                    && let Some(channel) = parameters.next()
+                {
+                    Invite(nick.to_string(), channel.to_string())
+                } else {
+                    Unknown(reply_number, string_to_send)
+                }
+            }
+            //<nick> <host> or <nick> <user@host> <ip>, the text explains them
+            338 if let Some(parameters) = parameters
+                && let Some((nick, actually)) = parameters
+                    .split_ascii_whitespace()
+                    .skip(1)
+                    .collect::<Vec<_>>()
+                    .split_first()
+                && !actually.is_empty() =>
+            {
+                WhoisActually(nick.to_string(), actually.join(" "))
+            }
             342 => SummonAnswer(string_to_send),
-            346 => InviteList(string_to_send),
-            347 => EndOfInviteList(string_to_send),
-            348 => ExceptionList(string_to_send),
-            349 => EndOfExceptionList(string_to_send),
+            346 if let Some((channel, entry)) = Self::list_entry(parameters, trailing) => {
+                InviteList(channel, entry)
+            }
+            347 if let Some(channel) = Self::list_channel(parameters) => EndOfInviteList(channel),
+            348 if let Some((channel, entry)) = Self::list_entry(parameters, trailing) => {
+                ExceptionList(channel, entry)
+            }
+            349 if let Some(channel) = Self::list_channel(parameters) => {
+                EndOfExceptionList(channel)
+            }
             351 => Version(string_to_send),
             352 => WhoReply(string_to_send),
             353 if let Some(parameters) = parameters => {
@@ -415,9 +542,11 @@ impl ResponseBuilder {
             364 => Links2(string_to_send),
             365 => EndOfLinks(string_to_send),
             366 => EndOfNames(string_to_send),
-            367 => BanList(string_to_send),
-            368 => EndOfBanList(string_to_send),
-            369 => EndOfWhowas(string_to_send),
+            367 if let Some((channel, entry)) = Self::list_entry(parameters, trailing) => {
+                BanList(channel, entry)
+            }
+            368 if let Some(channel) = Self::list_channel(parameters) => EndOfBanList(channel),
+            369 if let Some([nick, ..]) = Self::whois_parameters(parameters) => EndOfWhowas(nick),
             371 => Info(string_to_send),
             372 => MOTD(string_to_send),
             373 => MOTDStart(string_to_send),
@@ -432,10 +561,68 @@ impl ResponseBuilder {
             393 => Users(string_to_send),
             394 => EndOfUsers(string_to_send),
             395 => NoUsers(string_to_send),
-            400..=502 | 524..=525 | 691 | 696 | 723 | 902 | 904..=907 => {
+            671 if let Some([nick, ..]) = Self::whois_parameters(parameters) => WhoisSecure(nick),
+            730 => MonOnline(
+                string_to_send
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(|v| v.to_string())
+                    .collect(),
+            ),
+            731 => MonOffline(
+                string_to_send
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(|v| v.to_string())
+                    .collect(),
+            ),
+            732 => MonList(string_to_send),
+            733 => EndOfMonList(string_to_send),
+            477 if let Some(channel) = Self::list_channel(parameters) => {
+                NeedReggedNick(channel, string_to_send)
+            }
+            900 => LoggedIn(string_to_send),
+            400..=502 | 524..=525 | 691 | 696 | 723 | 734 | 902 | 904..=907 => {
                 Err(reply_number, string_to_send)
             }
             _ => Unknown(reply_number, string_to_send),
         }
     }
+
+    /// The first three parameters after <client>, the missing ones are empty, None without a nick
+    fn whois_parameters(parameters: Option<&str>) -> Option<[String; 3]> {
+        let mut parameters = parameters?.split_ascii_whitespace().skip(1);
+        let nick = parameters.next()?.to_string();
+        let mut next = || parameters.next().unwrap_or_default().to_string();
+        Some([nick, next(), next()])
+    }
+
+    /// <client> <channel>
+    fn list_channel(parameters: Option<&str>) -> Option<String> {
+        parameters?
+            .split_ascii_whitespace()
+            .nth(1)
+            .map(|v| v.to_string())
+    }
+
+    /// <client> <channel> <mask> [<setter> <setat>]
+    fn list_entry(parameters: Option<&str>, trailing: Option<&str>) -> Option<(String, ListEntry)> {
+        let mut parameters = parameters?.split_ascii_whitespace().skip(1).chain(
+            trailing
+                .into_iter()
+                .flat_map(|v| v.split_ascii_whitespace()),
+        );
+        let channel = parameters.next()?.to_string();
+        let mask = parameters.next()?.to_string();
+        let setter = parameters.next().map(|v| v.to_string());
+        let set_at = parameters.next().and_then(|v| v.parse::<u64>().ok());
+        Some((
+            channel,
+            ListEntry {
+                mask,
+                setter,
+                set_at,
+            },
+        ))
+    }
 }
@@ -0,0 +1,52 @@
+/// Strict Transport Security policy advertised with the sts capability
+/// sts=port=6697,duration=300,preload
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct StsPolicy {
+    /// Port to use for the secure connection, only sent over plaintext
+    pub port: Option<u16>,
+    /// Seconds the policy must be remembered, only sent over TLS
+    pub duration: Option<u64>,
+    pub preload: bool,
+}
+
+impl StsPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut policy = Self::default();
+        for key_value in value.split(',') {
+            match key_value.split_once('=') {
+                Some(("port", port)) => policy.port = Some(port.parse().ok()?),
+                Some(("duration", duration)) => policy.duration = Some(duration.parse().ok()?),
+                None if key_value == "preload" => policy.preload = true,
+                //Unknown keys must be ignored
+                _ => {}
+            }
+        }
+        Some(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            StsPolicy::parse("port=6697"),
+            Some(StsPolicy {
+                port: Some(6697),
+                duration: None,
+                preload: false
+            })
+        );
+        assert_eq!(
+            StsPolicy::parse("duration=300,preload,unknown=1"),
+            Some(StsPolicy {
+                port: None,
+                duration: Some(300),
+                preload: true
+            })
+        );
+        assert_eq!(StsPolicy::parse("port=abc"), None);
+    }
+}
@@ -0,0 +1,206 @@
+use futures_util::{Sink, Stream};
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Subprotocols of the IRCv3 WebSocket extension, text is preferred
+pub const TEXT_PROTOCOL: &str = "text.ircv3.net";
+pub const BINARY_PROTOCOL: &str = "binary.ircv3.net";
+pub const OFFERED_PROTOCOLS: &str = "text.ircv3.net, binary.ircv3.net";
+
+/// IRC lines carried by a WebSocket, one message per line without the CRLF.
+/// The lines are given back with a CRLF so the stream can be read like a TCP one.
+pub struct WebSocketStream<S> {
+    inner: tokio_tungstenite::WebSocketStream<S>,
+    read_buffer: Vec<u8>,
+    read_pos: usize,
+    write_buffer: Vec<u8>,
+    binary: bool,
+}
+
+fn to_io_error(error: tokio_tungstenite::tungstenite::Error) -> std::io::Error {
+    std::io::Error::other(error)
+}
+
+impl<S> WebSocketStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(inner: tokio_tungstenite::WebSocketStream<S>, protocol: Option<&str>) -> Self {
+        Self {
+            inner,
+            read_buffer: Vec::new(),
+            read_pos: 0,
+            write_buffer: Vec::new(),
+            binary: protocol == Some(BINARY_PROTOCOL),
+        }
+    }
+
+    /// Sends every complete line waiting in the buffer
+    fn poll_send_lines(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while let Some(end) = self.write_buffer.iter().position(|v| *v == b'\n') {
+            ready!(Pin::new(&mut self.inner).poll_ready(cx)).map_err(to_io_error)?;
+            let mut line: Vec<u8> = self.write_buffer.drain(..=end).collect();
+            while line.last().is_some_and(|v| *v == b'\n' || *v == b'\r') {
+                line.pop();
+            }
+            let message = if self.binary {
+                Message::binary(line)
+            } else {
+                Message::text(String::from_utf8_lossy(&line).into_owned())
+            };
+            Pin::new(&mut self.inner)
+                .start_send(message)
+                .map_err(to_io_error)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncRead for WebSocketStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(remaining) = this.read_buffer.get(this.read_pos..)
+                && !remaining.is_empty()
+            {
+                let to_read = std::cmp::min(remaining.len(), buf.remaining());
+                if let Some(slice) = remaining.get(..to_read) {
+                    buf.put_slice(slice);
+                }
+                this.read_pos += to_read;
+                return Poll::Ready(Ok(()));
+            }
+
+            this.read_buffer.clear();
+            this.read_pos = 0;
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(message @ (Message::Text(_) | Message::Binary(_)))) => {
+                    this.read_buffer.extend_from_slice(&message.into_data());
+                    this.read_buffer.extend_from_slice(b"\r\n");
+                }
+                //Ping and pong are answered by tungstenite
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                Some(Err(error)) => return Poll::Ready(Err(to_io_error(error))),
+            }
+        }
+    }
+}
+
+impl<S> AsyncWrite for WebSocketStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_lines(cx))?;
+        this.write_buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_lines(cx))?;
+        Pin::new(&mut this.inner)
+            .poll_flush(cx)
+            .map_err(to_io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_lines(cx))?;
+        Pin::new(&mut this.inner)
+            .poll_close(cx)
+            .map_err(to_io_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::conn::{Connection, ConnectionConfig};
+    use crate::outgoing::Outgoing;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{BufReader, BufWriter};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+    use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+    use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+
+    //The error type is imposed by tungstenite
+    #[allow(clippy::result_large_err)]
+    fn select_protocol(
+        request: &Request,
+        mut response: Response,
+    ) -> Result<Response, ErrorResponse> {
+        if request.uri().path() != "/webirc" {
+            let mut error = ErrorResponse::new(None);
+            *error.status_mut() = StatusCode::NOT_FOUND;
+            return Err(error);
+        }
+        response.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(super::TEXT_PROTOCOL),
+        );
+        Ok(response)
+    }
+
+    /// Stand-in for an ircd behind a WebSocket gateway
+    async fn gateway(listener: TcpListener) -> Vec<Message> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_hdr_async(stream, select_protocol)
+            .await
+            .unwrap();
+
+        ws.send(Message::text("PING :abc")).await.unwrap();
+        let mut received = vec![ws.next().await.unwrap().unwrap()];
+        received.push(ws.next().await.unwrap().unwrap());
+        received
+    }
+
+    #[tokio::test]
+    async fn test_websocket_process() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(gateway(listener));
+
+        let stream = Connection::new(&ConnectionConfig {
+            address: "127.0.0.1".to_string(),
+            port,
+            use_tls: false,
+            websocket: Some("/webirc".to_string()),
+        })
+        .connect()
+        .await
+        .unwrap();
+
+        let mut outgoing = Outgoing::default();
+        let (mut sender, _receiver) = outgoing.create_outgoing();
+        let (reader, writer) = tokio::io::split(stream);
+        let client = tokio::spawn(async move {
+            outgoing
+                .process(BufReader::new(reader), BufWriter::new(writer))
+                .await
+        });
+        sender
+            .send(crate::command::Command::Nick("tester".to_string()))
+            .unwrap();
+
+        let received = server.await.unwrap();
+        assert!(received.contains(&Message::text("PONG abc")));
+        assert!(received.contains(&Message::text("NICK tester")));
+        client.abort();
+    }
+}
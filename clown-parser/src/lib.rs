@@ -1,3 +1,4 @@
 pub mod message;
 pub mod parser;
 mod source;
+pub mod tags;
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 
 use crate::parser::{parse_command, parse_parameters, parse_trailing};
 use crate::source::{Source, parse_source};
+use crate::tags::{iter_tags, parse_tags, unescape_tag_value};
 use ouroboros::self_referencing;
 
 use thiserror::Error;
@@ -15,6 +16,7 @@ pub enum ParsingError {
 /// Note: Server sources (used for server-to-server communications) are not handled.
 #[derive(PartialEq, Eq)]
 pub struct IRCMessage<'s> {
+    tags: Option<&'s [u8]>,
     source: Option<Source<'s>>,
     command: Option<&'s [u8]>,
     parameters: Option<&'s [u8]>,
@@ -24,6 +26,10 @@ pub struct IRCMessage<'s> {
 impl Debug for IRCMessage<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("IRCMessage")
+            .field(
+                "tags",
+                &self.tags.as_ref().map(|t| String::from_utf8_lossy(t)),
+            )
             .field("source", &self.source)
             .field(
                 "command",
@@ -82,13 +88,37 @@ impl Message {
         }
     }
 
+    /// user@host of the source, if the server has sent them
+    pub fn source_user_host(&self) -> Option<(&str, &str)> {
+        let irc = self.borrow_internal();
+        let source = irc.source.as_ref()?;
+        Some((source.get_user()?, source.get_host()?))
+    }
+
     pub fn trailing(&self) -> Option<&str> {
         let irc = self.borrow_internal();
         irc.trailing.and_then(|value| str::from_utf8(value).ok())
     }
+
+    /// Raw IRCv3 tags, values are still escaped
+    pub fn tags(&self) -> Option<&str> {
+        let irc = self.borrow_internal();
+        irc.tags.and_then(|value| str::from_utf8(value).ok())
+    }
+
+    /// Unescaped value of a tag, an empty string if the tag has no value
+    pub fn tag(&self, key: &str) -> Option<std::borrow::Cow<'_, str>> {
+        self.tags()
+            .and_then(|tags| iter_tags(tags).find(|(k, _)| *k == key))
+            .map(|(_, value)| unescape_tag_value(value))
+    }
 }
 
 fn parse_message(buf: &[u8]) -> Result<IRCMessage<'_>, ParsingError> {
+    let (buf, tags) = match parse_tags(buf) {
+        Ok((buf, tags)) => (buf, tags),
+        Err(_) => (buf, None),
+    };
     let (buf, source) = parse_source(buf);
 
     let (buf, command) = match parse_command(buf) {
@@ -107,6 +137,7 @@ fn parse_message(buf: &[u8]) -> Result<IRCMessage<'_>, ParsingError> {
     };
 
     Ok(IRCMessage {
+        tags,
         source,
         command,
         parameters,
@@ -164,4 +195,27 @@ mod tests {
         assert_eq!(internal.parameters, Some(&b"#chan"[..]));
         assert_eq!(internal.trailing, Some(&b"hello world"[..]));
     }
+
+    #[test]
+    fn test_parse_message_tags() {
+        let input =
+            b"@batch=ref;time=2024-05-20T14:30:05.000Z;msgid=a\\sb :nick!user@host PRIVMSG #chan :hello\r\n";
+        let msg = create_message(input).unwrap();
+        assert_eq!(msg.command_name(), Some("PRIVMSG"));
+        assert_eq!(msg.source(), Some("nick"));
+        assert_eq!(msg.parameters(), Some("#chan"));
+        assert_eq!(msg.trailing(), Some("hello"));
+        assert_eq!(msg.tag("batch").as_deref(), Some("ref"));
+        assert_eq!(msg.tag("time").as_deref(), Some("2024-05-20T14:30:05.000Z"));
+        assert_eq!(msg.tag("msgid").as_deref(), Some("a b"));
+        assert_eq!(msg.tag("label"), None);
+    }
+
+    #[test]
+    fn test_parse_message_tags_no_source() {
+        let msg = create_message(b"@label=1 PONG :token").unwrap();
+        assert_eq!(msg.command_name(), Some("PONG"));
+        assert_eq!(msg.source(), None);
+        assert_eq!(msg.tag("label").as_deref(), Some("1"));
+    }
 }
@@ -69,6 +69,14 @@ impl Source<'_> {
             None
         }
     }
+
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.and_then(|v| std::str::from_utf8(v).ok())
+    }
+
+    pub fn get_host(&self) -> Option<&str> {
+        self.host.and_then(|v| std::str::from_utf8(v).ok())
+    }
 }
 
 fn nickname(input: &[u8]) -> IResult<&[u8], &[u8]> {
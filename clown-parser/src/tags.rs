@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+
+use nom::{
+    IResult, Parser,
+    bytes::complete::take_till1,
+    character::complete::{char, space1},
+    combinator::opt,
+    sequence::{preceded, terminated},
+};
+
+/// Parse the IRCv3 tags section, without the leading '@'
+/// @aaa=bbb;ccc;example.com/ddd=eee :nick!ident@host.com PRIVMSG me :Hello
+pub fn parse_tags(input: &[u8]) -> IResult<&[u8], Option<&[u8]>> {
+    opt(terminated(
+        preceded(char('@'), take_till1(|c| c == b' ')),
+        space1,
+    ))
+    .parse(input)
+}
+
+/// Iterate over the raw key/value of a tags section
+pub fn iter_tags(tags: &str) -> impl Iterator<Item = (&str, &str)> {
+    tags.split(';')
+        .filter(|v| !v.is_empty())
+        .map(|tag| tag.split_once('=').unwrap_or((tag, "")))
+}
+
+/// Tag values escape ';', ' ', '\', CR and LF
+pub fn unescape_tag_value(value: &str) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(':') => result.push(';'),
+                Some('s') => result.push(' '),
+                Some('\\') => result.push('\\'),
+                Some('r') => result.push('\r'),
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                //A trailing backslash is dropped
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Cow::Owned(result)
+}
+
+pub fn escape_tag_value(value: &str) -> Cow<'_, str> {
+    if !value.contains([';', ' ', '\\', '\r', '\n']) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut result = String::with_capacity(value.len() + 4);
+    for c in value.chars() {
+        match c {
+            ';' => result.push_str("\\:"),
+            ' ' => result.push_str("\\s"),
+            '\\' => result.push_str("\\\\"),
+            '\r' => result.push_str("\\r"),
+            '\n' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags() {
+        let input = b"@time=2024-01-01T00:00:00.000Z;msgid=abc :nick PRIVMSG #chan :hi";
+        let (rest, tags) = parse_tags(input).unwrap();
+        assert_eq!(tags, Some(&b"time=2024-01-01T00:00:00.000Z;msgid=abc"[..]));
+        assert_eq!(rest, b":nick PRIVMSG #chan :hi");
+    }
+
+    #[test]
+    fn test_parse_no_tags() {
+        let input = b":nick PRIVMSG #chan :hi";
+        let (rest, tags) = parse_tags(input).unwrap();
+        assert_eq!(tags, None);
+        assert_eq!(rest, input);
+    }
+
+    #[test]
+    fn test_iter_tags() {
+        let tags = iter_tags("aaa=bbb;ccc;example.com/ddd=eee").collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![("aaa", "bbb"), ("ccc", ""), ("example.com/ddd", "eee")]
+        );
+    }
+
+    #[test]
+    fn test_unescape_tag_value() {
+        assert_eq!(unescape_tag_value("plain"), "plain");
+        assert_eq!(unescape_tag_value("a\\sb\\:c\\\\d"), "a b;c\\d");
+        assert_eq!(unescape_tag_value("line\\r\\n"), "line\r\n");
+        assert_eq!(unescape_tag_value("\\btrailing\\"), "btrailing");
+    }
+
+    #[test]
+    fn test_escape_tag_value() {
+        assert_eq!(escape_tag_value("plain"), "plain");
+        assert_eq!(escape_tag_value("a b;c\\d"), "a\\sb\\:c\\\\d");
+        assert_eq!(
+            unescape_tag_value(&escape_tag_value("x; y\r\n")),
+            "x; y\r\n"
+        );
+    }
+}
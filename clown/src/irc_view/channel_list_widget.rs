@@ -0,0 +1,337 @@
+use crate::component::{Draw, EventHandler};
+use crate::irc_view::input::text_input;
+use crate::message_event::MessageEvent;
+use crate::state::channel_list::{self, ChannelListEntry, SortBy};
+use crate::state::server_id::ServerID;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Cell, Paragraph, Row, Table, TableState};
+
+const CHANNEL_LENGTH: u16 = 24;
+const USERS_LENGTH: u16 = 6;
+const HELP: &str = "Type to filter  Enter: join  Tab: sort  PgUp/PgDn: scroll  Esc: close";
+
+/// Channels of the server returned by LIST, they are shown as they are received
+#[derive(Default)]
+pub struct ChannelListWidget {
+    area: Rect,
+    server_id: Option<ServerID>,
+    entries: Vec<ChannelListEntry>,
+    /// Positions in `entries` of the channels to display
+    visible: Vec<usize>,
+    is_loading: bool,
+    sort_by: SortBy,
+    selected: usize,
+    scroll_offset: usize,
+    page_height: usize,
+    input: text_input::InputWidget,
+    need_redraw: bool,
+}
+
+impl ChannelListWidget {
+    fn refresh(&mut self) {
+        let selected = self.selected_entry().map(|v| v.name.clone());
+        self.visible = channel_list::visible(&self.entries, self.input.get_value(), self.sort_by);
+        //The selection stays on the same channel when the list grows
+        self.selected = selected
+            .and_then(|name| {
+                self.visible
+                    .iter()
+                    .position(|v| self.entries.get(*v).is_some_and(|v| v.name == name))
+            })
+            .unwrap_or_default();
+        self.need_redraw = true;
+    }
+
+    fn selected_entry(&self) -> Option<&ChannelListEntry> {
+        self.visible
+            .get(self.selected)
+            .and_then(|v| self.entries.get(*v))
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(offset).min(last);
+        self.need_redraw = true;
+    }
+
+    /// The first row displayed, so that the selection is visible
+    fn update_scroll(&mut self, height: usize) {
+        self.page_height = height;
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if height > 0 && self.selected >= self.scroll_offset + height {
+            self.scroll_offset = self.selected + 1 - height;
+        }
+    }
+
+    fn handle_key(&mut self, key: &KeyEvent) -> Option<MessageEvent> {
+        let page = isize::try_from(self.page_height.max(1)).unwrap_or(1);
+        match key.code {
+            KeyCode::Esc => return Some(MessageEvent::ChannelListEnd()),
+            KeyCode::Enter => {
+                let server_id = self.server_id?;
+                return self
+                    .selected_entry()
+                    .map(|v| MessageEvent::ChannelListJoin(server_id, v.name.clone()));
+            }
+            KeyCode::Tab => {
+                self.sort_by = self.sort_by.next();
+                self.refresh();
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-page),
+            KeyCode::PageDown => self.move_selection(page),
+            _ => {
+                let previous = self.input.get_value().to_string();
+                self.input.handle_key_events(key);
+                if previous != self.input.get_value() {
+                    self.selected = 0;
+                    self.scroll_offset = 0;
+                    self.refresh();
+                }
+                self.need_redraw = true;
+            }
+        }
+        None
+    }
+
+    fn title(&self) -> Line<'_> {
+        let sort_by = match self.sort_by {
+            SortBy::Users => "users",
+            SortBy::Name => "name",
+        };
+        let mut title = format!(
+            "{}/{} channels, sorted by {sort_by}",
+            self.visible.len(),
+            self.entries.len()
+        );
+        if self.is_loading {
+            title.push_str(", loading…");
+        }
+        Line::from(Span::from(title).style(Style::default().fg(Color::Cyan)))
+    }
+}
+
+impl Draw for ChannelListWidget {
+    fn render(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        frame: &mut ratatui::prelude::Frame<'_>,
+        area: Rect,
+    ) {
+        self.need_redraw = false;
+        self.area = area;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),       // Title
+                Constraint::Percentage(100), // Channels
+                Constraint::Length(2),       // Input area
+            ])
+            .split(area);
+
+        if let Some(title_area) = layout.first() {
+            frame.render_widget(Paragraph::new(self.title()), *title_area);
+        }
+
+        if let Some(table_area) = layout.get(1) {
+            //Only the visible rows are built, the list can hold thousands of channels
+            let height = usize::from(table_area.height.saturating_sub(1));
+            self.update_scroll(height);
+            let rows = self
+                .visible
+                .iter()
+                .skip(self.scroll_offset)
+                .take(height)
+                .filter_map(|v| self.entries.get(*v))
+                .map(|entry| {
+                    Row::new([
+                        Cell::from(entry.name.as_str()),
+                        Cell::from(
+                            Line::from(entry.users.to_string())
+                                .alignment(ratatui::layout::Alignment::Right),
+                        ),
+                        Cell::from(entry.topic.as_str()),
+                    ])
+                });
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(CHANNEL_LENGTH),
+                    Constraint::Length(USERS_LENGTH),
+                    Constraint::Min(10),
+                ],
+            )
+            .header(
+                Row::new(["Channel", " Users", "Topic"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .column_spacing(1);
+            let mut table_state = TableState::default().with_selected(
+                (!self.visible.is_empty()).then(|| self.selected - self.scroll_offset),
+            );
+            frame.render_stateful_widget(table, *table_area, &mut table_state);
+        }
+
+        if let Some(input_area) = layout.get(2) {
+            let width = input_area.width.max(3) - 3;
+            let scroll = self.input.compute_visual_scroll(width as usize);
+            let input = Paragraph::new(vec![
+                Line::from(vec![
+                    Span::from("> ").style(Style::default().fg(Color::Cyan)),
+                    Span::from(self.input.get_value()),
+                ]),
+                Line::from(Span::from(HELP).style(Style::default().fg(Color::DarkGray))),
+            ]);
+            frame.render_widget(input.scroll((0, scroll as u16)), *input_area);
+
+            let x = self.input.visual_cursor().max(scroll) - scroll + 2;
+            frame.set_cursor_position((input_area.x + x as u16, input_area.y))
+        }
+    }
+}
+
+impl EventHandler for ChannelListWidget {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    fn handle_actions(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        event: &MessageEvent,
+    ) -> Option<MessageEvent> {
+        match event {
+            MessageEvent::ChannelListOpen(server_id, filter) => {
+                let mut input = text_input::InputWidget::default();
+                input.reset_with(filter.clone());
+                *self = Self {
+                    server_id: Some(*server_id),
+                    is_loading: true,
+                    input,
+                    need_redraw: true,
+                    ..Default::default()
+                };
+            }
+            MessageEvent::ChannelList(server_id, entries, is_complete)
+                if self.server_id == Some(*server_id) =>
+            {
+                self.entries.extend(entries.iter().cloned());
+                self.is_loading = !is_complete;
+                self.refresh();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_events(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        event: &crate::event_handler::Event,
+    ) -> Option<MessageEvent> {
+        match event {
+            crate::event_handler::Event::Crossterm(crossterm_event) => match crossterm_event {
+                crossterm::event::Event::Key(key) => self.handle_key(key),
+                crossterm::event::Event::Resize(_x, _y) => {
+                    self.need_redraw = true;
+                    None
+                }
+                crossterm::event::Event::Mouse(mouse_event) => match mouse_event.kind {
+                    crossterm::event::MouseEventKind::ScrollDown => {
+                        self.move_selection(1);
+                        None
+                    }
+                    crossterm::event::MouseEventKind::ScrollUp => {
+                        self.move_selection(-1);
+                        None
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn need_redraw(&self) -> bool {
+        self.need_redraw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn widget_with(names: &[(&str, usize)]) -> ChannelListWidget {
+        let mut widget = ChannelListWidget {
+            server_id: Some(ServerID::new(0)),
+            entries: names
+                .iter()
+                .map(|(name, users)| ChannelListEntry::new(name.to_string(), *users, ""))
+                .collect(),
+            ..Default::default()
+        };
+        widget.refresh();
+        widget
+    }
+
+    #[test]
+    fn test_filter_and_join() {
+        let mut widget = widget_with(&[("#linux", 50), ("#rust", 100), ("#rust-beginners", 20)]);
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Enter)),
+            Some(MessageEvent::ChannelListJoin(server_id, ref channel))
+            if server_id == ServerID::new(0) && channel == "#rust"
+        ));
+        for c in "begin".chars() {
+            widget.handle_key(&key(KeyCode::Char(c)));
+        }
+        assert_eq!(widget.visible.len(), 1);
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Enter)),
+            Some(MessageEvent::ChannelListJoin(_, ref channel)) if channel == "#rust-beginners"
+        ));
+    }
+
+    #[test]
+    fn test_selection_kept_while_loading() {
+        let mut widget = widget_with(&[("#a", 10), ("#b", 5)]);
+        widget.handle_key(&key(KeyCode::Down));
+        widget.handle_key(&key(KeyCode::Down));
+        assert_eq!(widget.selected_entry().map(|v| v.name.as_str()), Some("#b"));
+        widget
+            .entries
+            .push(ChannelListEntry::new("#c".to_string(), 50, ""));
+        widget.refresh();
+        assert_eq!(widget.selected, 2);
+        assert_eq!(widget.selected_entry().map(|v| v.name.as_str()), Some("#b"));
+
+        widget.handle_key(&key(KeyCode::Tab));
+        assert_eq!(widget.sort_by, SortBy::Name);
+        assert_eq!(widget.selected, 1);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut widget = widget_with(&[("#a", 1), ("#b", 2), ("#c", 3), ("#d", 4)]);
+        widget.update_scroll(2);
+        widget.handle_key(&key(KeyCode::PageDown));
+        widget.handle_key(&key(KeyCode::Down));
+        widget.update_scroll(2);
+        assert_eq!((widget.selected, widget.scroll_offset), (3, 2));
+        widget.handle_key(&key(KeyCode::PageUp));
+        widget.update_scroll(2);
+        assert_eq!((widget.selected, widget.scroll_offset), (1, 1));
+    }
+}
@@ -42,6 +42,7 @@ pub struct DiscussWidget {
 
     last_hovered: Option<Hovered>,
     redraw: bool,
+    selected_msgid: Option<String>, /*Message replied to*/
 
     display_time: bool,
 }
@@ -59,6 +60,7 @@ impl DiscussWidget {
             content_width: 0,
             last_hovered: None,
             redraw: true,
+            selected_msgid: None,
             display_time: true,
         }
     }
@@ -66,6 +68,7 @@ impl DiscussWidget {
     pub fn set_current_channel(&mut self, server_id: Option<ServerID>, channel: &str) {
         self.current_channel = channel.to_lowercase();
         self.current_server_id = server_id;
+        self.selected_msgid = None;
         self.scroll_offset = 0;
         self.follow_last = true;
     }
@@ -158,17 +161,26 @@ impl DiscussWidget {
                     break;
                 }
 
+                //The quote of a reply is displayed above the content
+                let quote_rows = usize::from(line.get_reply_to().is_some());
+                let visible_quote_rows = quote_rows.saturating_sub(rows_to_skip_in_message);
+                let content_rows_to_skip = rows_to_skip_in_message.saturating_sub(quote_rows);
+                if index_y < visible_rows_total + visible_quote_rows {
+                    return None;
+                }
+                let content_top = visible_rows_total + visible_quote_rows;
+
                 let stripped = line.stripped_formatting();
                 let mut rows = wrap_content(&stripped, self.content_width);
                 let mut char_skipped: usize = 0;
-                if let Some(rows) = rows.get(..rows_to_skip_in_message) {
+                if let Some(rows) = rows.get(..content_rows_to_skip) {
                     char_skipped = rows.iter().map(|v| v.chars().count()).sum();
                 }
-                rows = rows.into_iter().skip(rows_to_skip_in_message).collect();
+                rows = rows.into_iter().skip(content_rows_to_skip).collect();
                 let rows_len = rows.len();
 
-                if index_y < visible_rows_total + rows_len {
-                    let pointed_row = (index_y - visible_rows_total).min(rows_len - 1);
+                if index_y < content_top + rows_len {
+                    let pointed_row = (index_y - content_top).min(rows_len - 1);
                     //it will be an approximation, because wrapping can remove spaces,
                     //  but sometimes does not remove characters
                     let char_position: usize = rows
@@ -180,15 +192,65 @@ impl DiscussWidget {
                     }
                     return Some((line_index, char_skipped + char_position + pos_x));
                 }
+                //The reactions are displayed under the content
+                let visible_in_message = rows_available.max(visible_quote_rows + rows_len);
+                if index_y < visible_rows_total + visible_in_message {
+                    return None;
+                }
                 rows_to_skip_in_message = 0; // Reset for subsequent messages
 
-                visible_rows_total += rows_len;
+                visible_rows_total += visible_in_message;
             }
         }
 
         None
     }
 
+    /// Index of the message displayed at this row, whatever the column
+    pub fn get_message_index(&self, messages: &ServersMessages, mouse_pos_y: u16) -> Option<usize> {
+        let index_y = mouse_pos_y.saturating_sub(self.area.y) as usize;
+        let messages = messages.get_messages(self.current_server_id, &self.current_channel)?;
+        let (start_message_index, mut rows_to_skip_in_message) = self.find_viewport_start(messages);
+
+        let mut visible_rows_total = 0;
+        for (line_index, line) in messages.iter().enumerate().skip(start_message_index) {
+            visible_rows_total += line
+                .wrapped_line_count(self.content_width)
+                .saturating_sub(rows_to_skip_in_message);
+            if index_y < visible_rows_total {
+                return Some(line_index);
+            }
+            rows_to_skip_in_message = 0;
+            if visible_rows_total >= self.max_visible_height {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Select the message to reply to, selecting it again cancels the reply
+    fn select_reply(&self, messages: &ServersMessages, mouse_pos_y: u16) -> Option<MessageEvent> {
+        let index = self.get_message_index(messages, mouse_pos_y)?;
+        let message = messages
+            .get_messages(self.current_server_id, &self.current_channel)
+            .and_then(|messages| messages.get(index))?;
+        match message.get_msgid() {
+            Some(msgid) if self.selected_msgid.as_deref() == Some(msgid) => {
+                Some(MessageEvent::SelectReply(None))
+            }
+            Some(msgid) => Some(MessageEvent::SelectReply(Some((
+                msgid.to_string(),
+                message.to_quote(),
+            )))),
+            None => Some(MessageEvent::AddMessageViewInfo(
+                None,
+                None,
+                crate::message_irc::message_content::MessageKind::Error,
+                "This message cannot be replied to (no msgid)".to_string(),
+            )),
+        }
+    }
+
     fn collect_visible_rows<'a>(
         &'a mut self,
         messages: &'a ServersMessages,
@@ -226,6 +288,10 @@ impl DiscussWidget {
                     crate::message_irc::message_content::TimeFormat::Hour
                 };
 
+                let is_selected = self
+                    .selected_msgid
+                    .as_deref()
+                    .is_some_and(|msgid| line.get_msgid() == Some(msgid));
                 let rows = line
                     .create_rows(
                         self.content_width as u16,
@@ -234,7 +300,14 @@ impl DiscussWidget {
                         NICKNAME_LENGTH,
                     )
                     .skip(rows_to_skip_in_message)
-                    .take(rows_to_take);
+                    .take(rows_to_take)
+                    .map(move |row| {
+                        if is_selected {
+                            row.style(Style::default().bg(Color::DarkGray))
+                        } else {
+                            row
+                        }
+                    });
 
                 visible_rows_total += rows_to_take;
                 visible_rows.extend(rows);
@@ -559,6 +632,54 @@ impl crate::component::EventHandler for DiscussWidget {
 
                 None
             }
+            MessageEvent::Mode(server_id, source, target, changes) => {
+                if !changes.is_empty() {
+                    let modes = clown_core::mode::format_modes(changes);
+                    let data = match source {
+                        Some(source) => format!("{source} has set mode for {target} to {modes}"),
+                        None => format!("Modes of {target}: {modes}"),
+                    };
+                    //The user modes are displayed in the server buffer
+                    let buffer = if ctx.session.model.is_channel(*server_id, target) {
+                        target.as_str()
+                    } else {
+                        ctx.session.model.get_server_name(*server_id)
+                    }
+                    .to_string();
+                    self.add_line(
+                        &mut ctx.messages,
+                        Some(*server_id),
+                        &buffer,
+                        MessageContent::info(data),
+                    );
+                }
+
+                None
+            }
+            MessageEvent::Notify(server_id, user, is_online) => {
+                //Only the changes are displayed, the model is not updated yet
+                let previous = ctx
+                    .session
+                    .model
+                    .get_server(*server_id)
+                    .and_then(|v| v.get_notify_status(user));
+                if previous != Some(*is_online) && (previous.is_some() || *is_online) {
+                    let buffer = ctx.session.model.get_server_name(*server_id).to_string();
+                    let data = if *is_online {
+                        format!("{user} is online")
+                    } else {
+                        format!("{user} is offline")
+                    };
+                    self.add_line(
+                        &mut ctx.messages,
+                        Some(*server_id),
+                        &buffer,
+                        MessageContent::info(data),
+                    );
+                }
+
+                None
+            }
             MessageEvent::Quit(server_id, user, reason) => {
                 if let Some(irc_server) = ctx.session.model.get_server(*server_id) {
                     for channel in irc_server.get_all_joined_channel(user) {
@@ -612,7 +733,37 @@ impl crate::component::EventHandler for DiscussWidget {
 
                 None
             }
-            MessageEvent::PrivMsg(server_id, source, target, content) => {
+            MessageEvent::Kick(server_id, channel, user, source, reason) => {
+                let msg = match reason {
+                    Some(reason) => format!("{user} was kicked by {source} ({reason})"),
+                    None => format!("{user} was kicked by {source}"),
+                };
+                self.add_line(
+                    &mut ctx.messages,
+                    Some(*server_id),
+                    channel,
+                    MessageContent::info(msg),
+                );
+
+                None
+            }
+            MessageEvent::Reaction(server_id, source, target, msgid, reaction) => {
+                if let Some(irc_server) = ctx.session.model.get_server(*server_id) {
+                    let target = irc_server.get_target(source, target);
+                    if ctx.messages.add_reaction(
+                        *server_id,
+                        &target.to_lowercase(),
+                        msgid,
+                        source,
+                        reaction,
+                    ) {
+                        self.redraw = true;
+                    }
+                }
+
+                None
+            }
+            MessageEvent::PrivMsg(server_id, source, target, content, msgid, reply_to) => {
                 if let Some(irc_server) = ctx.session.model.get_server(*server_id)
                     && let Some(current_channel) = irc_server.get_current_channel()
                 {
@@ -629,6 +780,20 @@ impl crate::component::EventHandler for DiscussWidget {
                         );
                     }
 
+                    //The server has echoed our message (echo-message)
+                    if source.eq_ignore_ascii_case(irc_server.get_current_nick())
+                        && ctx.messages.confirm_pending(
+                            *server_id,
+                            &target.to_lowercase(),
+                            &MessageContent::message(Some(source.clone()), content.clone())
+                                .with_msgid(msgid.clone())
+                                .with_reply_to(reply_to.clone()),
+                        )
+                    {
+                        self.redraw = true;
+                        return None;
+                    }
+
                     let is_highlight =
                         self.should_highlight(irc_server.get_current_nick(), content);
 
@@ -640,7 +805,9 @@ impl crate::component::EventHandler for DiscussWidget {
                             MessageContent::highlight(Some(source.clone()), content.clone())
                         } else {
                             MessageContent::message(Some(source.clone()), content.clone())
-                        },
+                        }
+                        .with_msgid(msgid.clone())
+                        .with_reply_to(reply_to.clone()),
                     );
                     if is_highlight {
                         Some(MessageEvent::Bel)
@@ -665,28 +832,66 @@ impl crate::component::EventHandler for DiscussWidget {
 
                 None
             }
-            MessageEvent::ActionMsg(server_id, source, target, content) => {
+            MessageEvent::ActionMsg(server_id, source, target, content, msgid) => {
                 if let Some(irc_server) = ctx.session.model.get_server(*server_id) {
                     let target = irc_server.get_target(source, target);
+                    let message = MessageContent::action(source.clone(), content.clone())
+                        .with_msgid(msgid.clone());
 
-                    self.add_line(
-                        &mut ctx.messages,
-                        Some(*server_id),
-                        target,
-                        MessageContent::action(source.clone(), content.clone()),
-                    );
+                    if source.eq_ignore_ascii_case(irc_server.get_current_nick())
+                        && ctx.messages.confirm_pending(
+                            *server_id,
+                            &target.to_lowercase(),
+                            &message,
+                        )
+                    {
+                        self.redraw = true;
+                    } else {
+                        self.add_line(&mut ctx.messages, Some(*server_id), target, message);
+                    }
                 }
 
                 None
             }
+            MessageEvent::PendingMsg(server_id, source, target, content, reply_to) => {
+                self.add_line(
+                    &mut ctx.messages,
+                    Some(*server_id),
+                    target,
+                    MessageContent::message(Some(source.clone()), content.clone())
+                        .with_reply_to(reply_to.clone())
+                        .with_pending(),
+                );
+
+                None
+            }
+            MessageEvent::PendingAction(server_id, source, target, content) => {
+                self.add_line(
+                    &mut ctx.messages,
+                    Some(*server_id),
+                    target,
+                    MessageContent::action(source.clone(), content.clone()).with_pending(),
+                );
+
+                None
+            }
+            MessageEvent::History(server_id, target, history) => {
+                let channel = target.to_lowercase();
+                ctx.messages.insert_history(*server_id, &channel, history);
+                if channel == self.current_channel && Some(*server_id) == self.current_server_id {
+                    for _ in history {
+                        self.add_line_scroll();
+                    }
+                }
+                self.redraw = true;
+
+                None
+            }
             MessageEvent::JoinServer(server_id) => {
                 self.add_server_group(
                     &mut ctx.messages,
                     Some(*server_id),
-                    ctx.model
-                        .get_connection_config(*server_id)
-                        .as_ref()
-                        .map(|v| v.address.clone()),
+                    ctx.model.get_log_address(*server_id),
                 );
 
                 None
@@ -737,6 +942,11 @@ impl crate::component::EventHandler for DiscussWidget {
                 self.set_current_channel(*server_id, channel);
                 None
             }
+            MessageEvent::SelectReply(selected) => {
+                self.selected_msgid = selected.as_ref().map(|(msgid, _)| msgid.clone());
+                self.redraw = true;
+                None
+            }
             _ => None,
         }
     }
@@ -778,6 +988,8 @@ impl crate::component::EventHandler for DiscussWidget {
                             } else {
                                 None
                             }
+                        } else if button == MouseButton::Right {
+                            self.select_reply(&ctx.messages, mouse_event.row)
                         } else {
                             None
                         }
@@ -837,6 +1049,9 @@ impl crate::component::EventHandler for DiscussWidget {
                         self.scroll_page_down();
                         None
                     }
+                    KeyCode::Esc if self.selected_msgid.is_some() => {
+                        Some(MessageEvent::SelectReply(None))
+                    }
 
                     _ => None,
                 },
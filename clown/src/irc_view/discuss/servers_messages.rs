@@ -3,6 +3,7 @@ use crate::message_irc::log::message_logger::{
 };
 use crate::message_irc::message_content::MessageContent;
 use crate::message_irc::message_content::WordPos;
+use crate::state::history::{HistoryKind, HistoryMessage};
 use crate::state::server_id::ServerID;
 use ahash::AHashMap;
 
@@ -39,11 +40,29 @@ fn create_message(log: LoggedTimedMessage<'_>) -> MessageContent {
         LoggedMessage::Quit { source } => MessageContent::info(format!("{} has quit", source))
             .with_time(log.time)
             .with_log(),
+        LoggedMessage::Kick {
+            source,
+            target,
+            channel: _,
+            reason,
+        } => MessageContent::info(match reason {
+            Some(reason) => format!("{target} was kicked by {source} ({reason})"),
+            None => format!("{target} was kicked by {source}"),
+        })
+        .with_time(log.time)
+        .with_log(),
         LoggedMessage::NickChange { old, new } => {
             MessageContent::info(format!("{} has changed their nickname to {}", old, new))
                 .with_time(log.time)
                 .with_log()
         }
+        LoggedMessage::Mode {
+            source,
+            target,
+            modes,
+        } => MessageContent::info(format!("{source} has set mode for {target} to {modes}"))
+            .with_time(log.time)
+            .with_log(),
         LoggedMessage::Message { source, content } => {
             MessageContent::message(Some(source.to_string()), content.to_string())
                 .with_time(log.time)
@@ -52,6 +71,24 @@ fn create_message(log: LoggedTimedMessage<'_>) -> MessageContent {
     }
 }
 
+pub fn create_history_message(message: &HistoryMessage) -> MessageContent {
+    let content = match message.kind {
+        HistoryKind::Action => {
+            MessageContent::action(message.source.clone(), message.content.clone())
+        }
+        HistoryKind::Message => {
+            MessageContent::message(Some(message.source.clone()), message.content.clone())
+        }
+        HistoryKind::Notice => {
+            MessageContent::notice(Some(message.source.clone()), message.content.clone())
+        }
+    };
+    content
+        .with_time(message.time)
+        .with_msgid(message.msgid.clone())
+        .with_reply_to(message.reply_to.clone())
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Range {
     line: usize,
@@ -114,6 +151,79 @@ impl ServersMessages {
         }
     }
 
+    /// Remove the messages already received or already in the logs
+    pub fn filter_history(
+        &self,
+        server_id: ServerID,
+        channel: &str,
+        history: Vec<HistoryMessage>,
+    ) -> Vec<HistoryMessage> {
+        if let Some(server_group) = self.get_server_group(Some(server_id)) {
+            server_group.filter_history(&self.log_folder, channel, history)
+        } else {
+            history
+        }
+    }
+
+    /// Returns true if the echoed message was waiting for the server
+    pub fn confirm_pending(
+        &mut self,
+        server_id: ServerID,
+        channel: &str,
+        echo: &MessageContent,
+    ) -> bool {
+        self.get_server_group_mut(Some(server_id))
+            .and_then(|server_group| server_group.messages.get_mut(channel))
+            .is_some_and(|messages| messages.confirm_pending(echo))
+    }
+
+    /// Returns true if the message reacted to is in the buffer
+    pub fn add_reaction(
+        &mut self,
+        server_id: ServerID,
+        channel: &str,
+        msgid: &str,
+        source: &str,
+        reaction: &str,
+    ) -> bool {
+        self.get_server_group_mut(Some(server_id))
+            .and_then(|server_group| server_group.messages.get_mut(channel))
+            .is_some_and(|messages| messages.add_reaction(msgid, source, reaction))
+    }
+
+    /// Id of the last message of the buffer which can be reacted to
+    pub fn get_last_msgid(&self, server_id: ServerID, channel: &str) -> Option<&str> {
+        self.get_messages(Some(server_id), channel)
+            .and_then(|messages| messages.iter().rev().find_map(|v| v.get_msgid()))
+    }
+
+    pub fn insert_history(
+        &mut self,
+        server_id: ServerID,
+        channel: &str,
+        history: &[HistoryMessage],
+    ) {
+        if let Some(server_group) = self.get_server_group_mut(Some(server_id)) {
+            let messages = server_group
+                .messages
+                .entry(channel.to_string())
+                .or_default();
+            for message in history {
+                messages.insert_sorted(create_history_message(message));
+            }
+        }
+    }
+
+    /// Time of the last message known, used to request the missing history
+    pub fn get_last_message_time(
+        &self,
+        server_id: ServerID,
+        channel: &str,
+    ) -> Option<std::time::SystemTime> {
+        self.get_server_group(Some(server_id))
+            .and_then(|server_group| server_group.get_last_message_time(&self.log_folder, channel))
+    }
+
     fn server_id_position(server_id: Option<ServerID>) -> usize {
         server_id
             .map(|v| v.as_usize().saturating_add(1))
@@ -211,10 +321,59 @@ pub struct Messages {
 }
 
 impl Messages {
-    pub fn push_new(&mut self, in_message: MessageContent) {
+    pub fn push_new(&mut self, mut in_message: MessageContent) {
+        self.quote_parent(&mut in_message);
         self.messages.push(in_message);
     }
 
+    /// Keep the messages ordered by time, used by the messages coming from the history
+    pub fn insert_sorted(&mut self, mut in_message: MessageContent) {
+        self.quote_parent(&mut in_message);
+        let time = in_message.get_time();
+        let position = self.messages.partition_point(|v| v.get_time() <= time);
+        self.messages.insert(position, in_message);
+    }
+
+    /// The parent of a reply is searched in the messages already known
+    fn quote_parent(&self, in_message: &mut MessageContent) {
+        if let Some(reply_to) = in_message.get_reply_to()
+            && let Some(parent) = self.find_msgid(reply_to)
+        {
+            in_message.set_quote(parent);
+        }
+    }
+
+    pub fn find_msgid(&self, msgid: &str) -> Option<&MessageContent> {
+        self.iter()
+            .rev()
+            .find(|message| message.get_msgid() == Some(msgid))
+    }
+
+    fn confirm_pending(&mut self, echo: &MessageContent) -> bool {
+        self.messages
+            .iter_mut()
+            .any(|message| message.confirm_pending(echo))
+    }
+
+    fn add_reaction(&mut self, msgid: &str, source: &str, reaction: &str) -> bool {
+        self.messages
+            .iter_mut()
+            .rev()
+            .find(|message| message.get_msgid() == Some(msgid))
+            .is_some_and(|message| message.add_reaction(reaction, source))
+    }
+
+    fn contains(&self, in_message: &MessageContent) -> bool {
+        self.iter().any(|message| {
+            if let Some(msgid) = in_message.get_msgid()
+                && message.get_msgid() == Some(msgid)
+            {
+                return true;
+            }
+            message.is_same_message(in_message)
+        })
+    }
+
     pub fn is_empty(&self) -> bool {
         self.logged_messages.is_empty() && self.messages.is_empty()
     }
@@ -318,6 +477,75 @@ impl ChannelMessages {
         }
     }
 
+    fn log_path(&self, log_folder: &std::path::Path, channel: &str) -> Option<PathBuf> {
+        self.server_address.as_deref().map(|server_address| {
+            log_folder.join(MessageLogger::compute_filename(
+                server_address,
+                Some(channel),
+            ))
+        })
+    }
+
+    fn filter_history(
+        &self,
+        log_folder: &std::path::Path,
+        channel: &str,
+        history: Vec<HistoryMessage>,
+    ) -> Vec<HistoryMessage> {
+        let Some(earliest) = history.iter().map(|v| v.time).min() else {
+            return history;
+        };
+        //The logs are stored in seconds
+        let earliest = earliest
+            .checked_sub(std::time::Duration::from_secs(1))
+            .unwrap_or(earliest);
+
+        let mut known: Vec<MessageContent> = Vec::new();
+        if let Some(path) = self.log_path(log_folder, channel)
+            && let Ok(mut log_reader) = LogReader::try_from_path(&path)
+        {
+            known.extend(
+                log_reader
+                    .iter()
+                    .filter_map(|v| v.ok())
+                    .take_while(|v| v.get_message().time >= earliest)
+                    .map(|v| create_message(v.get_message().clone())),
+            );
+        }
+
+        let messages = self.messages.get(channel);
+        history
+            .into_iter()
+            .filter(|message| {
+                let content = create_history_message(message);
+                !messages.is_some_and(|v| v.contains(&content))
+                    && !known.iter().any(|v| v.is_same_message(&content))
+            })
+            .collect()
+    }
+
+    fn get_last_message_time(
+        &self,
+        log_folder: &std::path::Path,
+        channel: &str,
+    ) -> Option<std::time::SystemTime> {
+        let last_received = self
+            .messages
+            .get(channel)
+            .and_then(|v| v.messages.last())
+            .map(|v| v.get_time());
+        let last_logged = self
+            .log_path(log_folder, channel)
+            .and_then(|path| LogReader::try_from_path(&path).ok())
+            .and_then(|mut log_reader| {
+                log_reader
+                    .read(1)
+                    .ok()
+                    .and_then(|v| v.first().map(|v| v.time))
+            });
+        last_received.max(last_logged)
+    }
+
     fn rename(&mut self, old: &str, new: &str) {
         if let Some(messages) = self.messages.remove(old) {
             self.messages.insert(new.to_string(), messages);
@@ -472,4 +700,134 @@ mod tests {
         let msgs = storage.get_messages(None, "Status").unwrap();
         assert_eq!(msgs.get(0).unwrap().get_content(), "Connecting...");
     }
+
+    fn history_message(
+        seconds: u64,
+        msgid: Option<&str>,
+        source: &str,
+        content: &str,
+    ) -> HistoryMessage {
+        HistoryMessage {
+            time: std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds),
+            msgid: msgid.map(|v| v.to_string()),
+            reply_to: None,
+            kind: HistoryKind::Message,
+            source: source.to_string(),
+            target: "#rust".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reply_quote() {
+        let mut msgs = Messages::default();
+        msgs.push_new(
+            MessageContent::message(Some("alice".into()), "hello".to_string())
+                .with_msgid(Some("abc".into())),
+        );
+        msgs.push_new(
+            MessageContent::message(Some("bob".into()), "hi".to_string())
+                .with_reply_to(Some("abc".into())),
+        );
+        msgs.push_new(
+            MessageContent::message(Some("bob".into()), "what?".to_string())
+                .with_reply_to(Some("unknown".into())),
+        );
+
+        assert_eq!(msgs.get(1).unwrap().get_quote(), Some("alice: hello"));
+        assert_eq!(msgs.get(2).unwrap().get_quote(), None);
+    }
+
+    #[test]
+    fn test_insert_sorted() {
+        let mut msgs = Messages::default();
+        let at = |seconds| std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+        msgs.push_new(MessageContent::info("first".to_string()).with_time(at(10)));
+        msgs.push_new(MessageContent::info("third".to_string()).with_time(at(30)));
+        msgs.insert_sorted(MessageContent::info("second".to_string()).with_time(at(20)));
+        msgs.insert_sorted(MessageContent::info("fourth".to_string()).with_time(at(40)));
+
+        let combined: Vec<String> = msgs.iter().map(|m| m.get_content().to_string()).collect();
+        assert_eq!(combined, vec!["first", "second", "third", "fourth"]);
+    }
+
+    #[test]
+    fn test_filter_history_in_memory() {
+        let mut storage = ServersMessages::new(PathBuf::from("/tmp/clown-no-logs"));
+        let server_id = ServerID::new(0);
+        storage.add_server_group(Some(server_id), None);
+        storage.insert_history(
+            server_id,
+            "#rust",
+            &[
+                history_message(10, Some("a"), "alice", "hello"),
+                history_message(20, None, "bob", "hi"),
+            ],
+        );
+
+        let filtered = storage.filter_history(
+            server_id,
+            "#rust",
+            vec![
+                //Same msgid
+                history_message(12, Some("a"), "alice", "edited"),
+                //Same content and time
+                history_message(20, Some("b"), "bob", "hi"),
+                history_message(30, Some("c"), "bob", "new"),
+            ],
+        );
+        assert_eq!(filtered, vec![history_message(30, Some("c"), "bob", "new")]);
+    }
+
+    #[test]
+    fn test_filter_history_with_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let server_id = ServerID::new(0);
+        let name = MessageLogger::compute_filename("localhost", Some("#rust"));
+        std::fs::write(
+            dir.path().join(name),
+            "1970-01-01 00:00:10\talice hello\n1970-01-01 00:00:20\tbob hi\n",
+        )
+        .unwrap();
+
+        let mut storage = ServersMessages::new(dir.path().to_path_buf());
+        storage.add_server_group(Some(server_id), Some("localhost".to_string()));
+
+        assert_eq!(
+            storage.get_last_message_time(server_id, "#rust"),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(20))
+        );
+
+        let filtered = storage.filter_history(
+            server_id,
+            "#rust",
+            vec![
+                history_message(20, Some("b"), "bob", "hi"),
+                history_message(25, Some("c"), "bob", "new"),
+            ],
+        );
+        assert_eq!(filtered, vec![history_message(25, Some("c"), "bob", "new")]);
+    }
+
+    #[test]
+    fn test_confirm_pending() {
+        let mut storage = ServersMessages::new(PathBuf::from("/tmp"));
+        let server_id = ServerID::new(0);
+        storage.add_server_group(Some(server_id), None);
+        storage.add_message(
+            Some(server_id),
+            "#rust",
+            MessageContent::message(Some("me".into()), "hello".into()).with_pending(),
+        );
+
+        let echo = MessageContent::message(Some("me".into()), "hello".into());
+        assert!(!storage.confirm_pending(server_id, "#other", &echo));
+        assert!(storage.confirm_pending(server_id, "#rust", &echo));
+        //Already confirmed
+        assert!(!storage.confirm_pending(server_id, "#rust", &echo));
+
+        let msgs = storage.get_messages(Some(server_id), "#rust").unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert!(!msgs.get(0).unwrap().is_pending());
+    }
 }
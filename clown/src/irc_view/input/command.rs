@@ -1,4 +1,5 @@
 use crate::message_event::MessageEvent;
+use clown_core::isupport::ISupport;
 
 use strum::{EnumIter, EnumMessage, IntoEnumIterator, IntoStaticStr};
 
@@ -10,6 +11,20 @@ pub enum ConfigCommand {
     Get,
 }
 
+#[derive(Debug, Default, PartialEq)]
+pub enum DccCommand {
+    /// Nick and path of the file
+    Send(String, String),
+    /// Nick to chat with
+    Chat(String),
+    Get(usize),
+    /// Accept an offer and resume the file of the same name
+    Resume(usize),
+    Close(usize),
+    #[default]
+    List,
+}
+
 #[derive(IntoStaticStr, Debug, EnumIter, EnumMessage)]
 pub enum ClientCommand {
     #[strum(
@@ -30,13 +45,96 @@ pub enum ClientCommand {
     Spell(Option<String>),
     #[strum(message = "me", detailed_message = "To create an action")]
     Action(String),
-    #[strum(message = "join", detailed_message = "To join a channel: {channel}")]
+    #[strum(
+        message = "join",
+        detailed_message = "To join channels: {#a,#b} [{key1,key2}]"
+    )]
     Join(String),
     #[strum(
         message = "part",
         detailed_message = "To quit a channel: {channel} {reason}"
     )]
     Part(Option<String>, Option<String>),
+    #[strum(
+        message = "invite",
+        detailed_message = "To invite a user to a channel: {nick} {channel}, the current one by default"
+    )]
+    Invite(String, Option<String>),
+    #[strum(
+        message = "accept",
+        detailed_message = "To join the channel of an invitation: {channel}, the last one by default"
+    )]
+    Accept(Option<String>),
+    #[strum(
+        message = "kick",
+        detailed_message = "To kick a user: {channel} {nick} {reason}, the channel is optional"
+    )]
+    Kick(Option<String>, String, Option<String>),
+    #[strum(
+        message = "kickban",
+        detailed_message = "To ban the mask of a user then kick them: {channel} {nick} {reason}"
+    )]
+    KickBan(Option<String>, String, Option<String>),
+    #[strum(
+        message = "ban",
+        detailed_message = "To ban users or masks: {channel} {nick|mask}..., see moderation.ban_mask"
+    )]
+    Ban(Option<String>, Vec<String>),
+    #[strum(
+        message = "unban",
+        detailed_message = "To remove bans: {channel} {nick|mask}..."
+    )]
+    Unban(Option<String>, Vec<String>),
+    #[strum(
+        message = "quiet",
+        detailed_message = "To prevent users or masks from talking: {channel} {nick|mask}..."
+    )]
+    Quiet(Option<String>, Vec<String>),
+    #[strum(
+        message = "op",
+        detailed_message = "To give operator: {channel} {nick}..."
+    )]
+    Op(Option<String>, Vec<String>),
+    #[strum(
+        message = "deop",
+        detailed_message = "To take operator: {channel} {nick}..."
+    )]
+    Deop(Option<String>, Vec<String>),
+    #[strum(
+        message = "voice",
+        detailed_message = "To give voice: {channel} {nick}..."
+    )]
+    Voice(Option<String>, Vec<String>),
+    #[strum(
+        message = "devoice",
+        detailed_message = "To take voice: {channel} {nick}..."
+    )]
+    Devoice(Option<String>, Vec<String>),
+    #[strum(
+        message = "mode",
+        detailed_message = "To change modes: {target} {modes} {params}, the current channel by default"
+    )]
+    Mode(Option<String>, String),
+    #[strum(
+        message = "whois",
+        detailed_message = "To show the information about a user: {nick}"
+    )]
+    Whois(String),
+    #[strum(
+        message = "whowas",
+        detailed_message = "To show the information about a user who has left: {nick}"
+    )]
+    Whowas(String),
+    #[strum(
+        message = "list",
+        detailed_message = "To browse the channels of the server: {filter}"
+    )]
+    List(Option<String>),
+    #[strum(
+        message = "banlist",
+        detailed_message = "To show and edit the bans, exceptions and invite exceptions: {channel}"
+    )]
+    BanList(Option<String>),
     #[strum(
         message = "msg",
         detailed_message = "To send a message to a user or channel"
@@ -57,6 +155,21 @@ pub enum ClientCommand {
     CloseBuffer(Option<String> /*buffer name, if not current*/),
     #[strum(message = "search", detailed_message = "To search")]
     Search(String),
+    #[strum(
+        message = "react",
+        detailed_message = "To react to the last message of the buffer: {reaction}"
+    )]
+    React(String),
+    #[strum(
+        message = "ctcp",
+        detailed_message = "To send a CTCP query: {nick} {command} {params}, e.g. VERSION, PING, TIME"
+    )]
+    Ctcp(String, String, Option<String>),
+    #[strum(
+        message = "dcc",
+        detailed_message = "File transfers and chats: send {nick} {path}, chat {nick}, get {id}, resume {id}, close {id}, list"
+    )]
+    Dcc(DccCommand),
     Unknown(Option<String>),
 }
 
@@ -73,7 +186,8 @@ fn get_next_word(in_content: &str) -> Option<(&str, Option<&str>)> {
     }
 }
 
-pub fn parse_command(in_content: &str) -> Option<ClientCommand> {
+/// The channels are recognized with the CHANTYPES of the server
+pub fn parse_command(in_content: &str, isupport: &ISupport) -> Option<ClientCommand> {
     if let Some(next) = in_content.trim_ascii_start().strip_prefix('/') {
         if let Some((command, args)) = get_next_word(next) {
             match command.to_lowercase().as_str() {
@@ -97,6 +211,45 @@ pub fn parse_command(in_content: &str) -> Option<ClientCommand> {
                     ClientCommand::Join(v.to_string())
                 })),
                 "part" => Some(part(args)),
+                "kick" | "kickban" => Some(
+                    kick(args, command.eq_ignore_ascii_case("kickban"), isupport)
+                        .unwrap_or(ClientCommand::Unknown(None)),
+                ),
+                "ban" | "unban" | "quiet" | "op" | "deop" | "voice" | "devoice" => Some(
+                    moderation(&command.to_lowercase(), args, isupport)
+                        .unwrap_or(ClientCommand::Unknown(None)),
+                ),
+                "mode" => Some(mode(args)),
+                "invite" => Some(args.and_then(get_next_word).map_or(
+                    ClientCommand::Unknown(None),
+                    |(nick, channel)| {
+                        ClientCommand::Invite(
+                            nick.to_string(),
+                            channel.and_then(get_next_word).map(|v| v.0.to_string()),
+                        )
+                    },
+                )),
+                "accept" => Some(ClientCommand::Accept(
+                    args.and_then(get_next_word).map(|v| v.0.to_string()),
+                )),
+                "whois" | "whowas" => Some(args.and_then(get_next_word).map_or(
+                    ClientCommand::Unknown(None),
+                    |(nick, _)| {
+                        if command.eq_ignore_ascii_case("whois") {
+                            ClientCommand::Whois(nick.to_string())
+                        } else {
+                            ClientCommand::Whowas(nick.to_string())
+                        }
+                    },
+                )),
+                "list" => Some(ClientCommand::List(
+                    args.map(str::trim)
+                        .filter(|v| !v.is_empty())
+                        .map(|v| v.to_string()),
+                )),
+                "banlist" => Some(ClientCommand::BanList(
+                    args.and_then(get_next_word).map(|v| v.0.to_string()),
+                )),
                 "msg" => args.map_or(Some(ClientCommand::Unknown(None)), |v| {
                     privmsg(v).or(Some(ClientCommand::Unknown(None)))
                 }),
@@ -104,6 +257,11 @@ pub fn parse_command(in_content: &str) -> Option<ClientCommand> {
                     config_command(v).or(Some(ClientCommand::Unknown(None)))
                 }),
                 "close" => Some(ClientCommand::CloseBuffer(args.map(|v| v.to_string()))),
+                "react" => Some(args.map_or(ClientCommand::Unknown(None), |v| {
+                    ClientCommand::React(v.to_string())
+                })),
+                "ctcp" => Some(args.and_then(ctcp).unwrap_or(ClientCommand::Unknown(None))),
+                "dcc" => Some(dcc(args).unwrap_or(ClientCommand::Unknown(None))),
                 _ => Some(ClientCommand::Unknown(Some(command.to_string()))),
             }
         } else {
@@ -147,6 +305,87 @@ fn privmsg(message: &str) -> Option<ClientCommand> {
     }
 }
 
+fn ctcp(message: &str) -> Option<ClientCommand> {
+    let (target, content) = get_next_word(message)?;
+    let (query, params) = get_next_word(content?)?;
+    Some(ClientCommand::Ctcp(
+        target.to_string(),
+        query.to_string(),
+        params.map(|v| v.to_string()),
+    ))
+}
+
+fn dcc(message: Option<&str>) -> Option<ClientCommand> {
+    let Some((kind, rest)) = message.and_then(get_next_word) else {
+        return Some(ClientCommand::Dcc(DccCommand::List));
+    };
+    let command = match kind.to_lowercase().as_str() {
+        "send" => {
+            let (nick, path) = get_next_word(rest?)?;
+            DccCommand::Send(nick.to_string(), path?.to_string())
+        }
+        "chat" => DccCommand::Chat(get_next_word(rest?)?.0.to_string()),
+        "get" => DccCommand::Get(rest?.trim().parse().ok()?),
+        "resume" => DccCommand::Resume(rest?.trim().parse().ok()?),
+        "close" => DccCommand::Close(rest?.trim().parse().ok()?),
+        "list" => DccCommand::List,
+        _ => return None,
+    };
+    Some(ClientCommand::Dcc(command))
+}
+
+/// The channel is optional before the other arguments
+fn channel_and_rest<'a>(
+    message: Option<&'a str>,
+    isupport: &ISupport,
+) -> (Option<String>, Option<&'a str>) {
+    match message.and_then(get_next_word) {
+        Some((channel, rest)) if isupport.is_channel(channel) => (Some(channel.to_string()), rest),
+        _ => (None, message),
+    }
+}
+
+fn kick(message: Option<&str>, is_ban: bool, isupport: &ISupport) -> Option<ClientCommand> {
+    let (channel, rest) = channel_and_rest(message, isupport);
+    let (nick, reason) = get_next_word(rest?)?;
+    let (nick, reason) = (nick.to_string(), reason.map(|v| v.to_string()));
+    Some(if is_ban {
+        ClientCommand::KickBan(channel, nick, reason)
+    } else {
+        ClientCommand::Kick(channel, nick, reason)
+    })
+}
+
+fn moderation(command: &str, message: Option<&str>, isupport: &ISupport) -> Option<ClientCommand> {
+    let (channel, rest) = channel_and_rest(message, isupport);
+    let targets = rest?
+        .split_ascii_whitespace()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>();
+    let command = match command {
+        "ban" => ClientCommand::Ban(channel, targets),
+        "unban" => ClientCommand::Unban(channel, targets),
+        "quiet" => ClientCommand::Quiet(channel, targets),
+        "op" => ClientCommand::Op(channel, targets),
+        "deop" => ClientCommand::Deop(channel, targets),
+        "voice" => ClientCommand::Voice(channel, targets),
+        "devoice" => ClientCommand::Devoice(channel, targets),
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// The target is omitted when the arguments start with the modes
+fn mode(message: Option<&str>) -> ClientCommand {
+    match message.and_then(get_next_word) {
+        Some((target, modes)) if !target.starts_with(['+', '-']) => ClientCommand::Mode(
+            Some(target.to_string()),
+            modes.unwrap_or_default().to_string(),
+        ),
+        _ => ClientCommand::Mode(None, message.unwrap_or_default().to_string()),
+    }
+}
+
 fn part(message: Option<&str>) -> ClientCommand {
     if let Some(message) = message {
         if let Some((channel, reason)) = get_next_word(message) {
@@ -187,6 +426,11 @@ pub fn help() -> MessageEvent {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn parse_command(in_content: &str) -> Option<ClientCommand> {
+        super::parse_command(in_content, &ISupport::default())
+    }
+
     #[test]
     fn next_word() {
         let (word, rest) = get_next_word("/connect").unwrap();
@@ -234,6 +478,123 @@ mod tests {
         assert!(matches!(cmd, Some(ClientCommand::Join(ref s)) if s == "#rust"));
     }
 
+    #[test]
+    fn parse_invite() {
+        assert!(matches!(
+            parse_command("/invite alice #rust"),
+            Some(ClientCommand::Invite(ref nick, Some(ref chan)))
+            if nick == "alice" && chan == "#rust"
+        ));
+        assert!(matches!(
+            parse_command("/invite alice"),
+            Some(ClientCommand::Invite(ref nick, None)) if nick == "alice"
+        ));
+        assert!(matches!(
+            parse_command("/invite"),
+            Some(ClientCommand::Unknown(None))
+        ));
+        assert!(matches!(
+            parse_command("/accept"),
+            Some(ClientCommand::Accept(None))
+        ));
+    }
+
+    #[test]
+    fn parse_whois() {
+        assert!(matches!(
+            parse_command("/whois alice"),
+            Some(ClientCommand::Whois(ref nick)) if nick == "alice"
+        ));
+        assert!(matches!(
+            parse_command("/whowas bob"),
+            Some(ClientCommand::Whowas(ref nick)) if nick == "bob"
+        ));
+        assert!(matches!(
+            parse_command("/whois"),
+            Some(ClientCommand::Unknown(None))
+        ));
+    }
+
+    #[test]
+    fn parse_list() {
+        assert!(matches!(
+            parse_command("/list rust"),
+            Some(ClientCommand::List(Some(ref filter))) if filter == "rust"
+        ));
+        assert!(matches!(
+            parse_command("/list"),
+            Some(ClientCommand::List(None))
+        ));
+    }
+
+    #[test]
+    fn parse_banlist() {
+        assert!(matches!(
+            parse_command("/banlist #rust"),
+            Some(ClientCommand::BanList(Some(ref chan))) if chan == "#rust"
+        ));
+        assert!(matches!(
+            parse_command("/banlist"),
+            Some(ClientCommand::BanList(None))
+        ));
+    }
+
+    #[test]
+    fn parse_kick() {
+        assert!(matches!(
+            parse_command("/kick alice stop spamming"),
+            Some(ClientCommand::Kick(None, ref nick, Some(ref reason)))
+            if nick == "alice" && reason == "stop spamming"
+        ));
+        assert!(matches!(
+            parse_command("/kickban #rust alice"),
+            Some(ClientCommand::KickBan(Some(ref chan), ref nick, None))
+            if chan == "#rust" && nick == "alice"
+        ));
+        assert!(matches!(
+            parse_command("/kick #rust"),
+            Some(ClientCommand::Unknown(None))
+        ));
+
+        let mut isupport = ISupport::default();
+        isupport.update(&["CHANTYPES=#!"]);
+        assert!(matches!(
+            super::parse_command("/kick !rust alice", &isupport),
+            Some(ClientCommand::Kick(Some(ref chan), ref nick, None))
+            if chan == "!rust" && nick == "alice"
+        ));
+        assert!(matches!(
+            super::parse_command("/kick &rust alice", &isupport),
+            Some(ClientCommand::Kick(None, ref nick, Some(_))) if nick == "&rust"
+        ));
+    }
+
+    #[test]
+    fn parse_moderation() {
+        assert!(matches!(
+            parse_command("/op alice bob"),
+            Some(ClientCommand::Op(None, ref nicks)) if nicks == &["alice", "bob"]
+        ));
+        assert!(matches!(
+            parse_command("/unban #rust *!*@spam"),
+            Some(ClientCommand::Unban(Some(ref chan), ref masks))
+            if chan == "#rust" && masks == &["*!*@spam"]
+        ));
+        assert!(matches!(
+            parse_command("/devoice"),
+            Some(ClientCommand::Unknown(None))
+        ));
+        assert!(matches!(
+            parse_command("/mode +m"),
+            Some(ClientCommand::Mode(None, ref modes)) if modes == "+m"
+        ));
+        assert!(matches!(
+            parse_command("/mode #rust +l 10"),
+            Some(ClientCommand::Mode(Some(ref target), ref modes))
+            if target == "#rust" && modes == "+l 10"
+        ));
+    }
+
     #[test]
     fn parse_me_action() {
         let cmd = parse_command("/me waves");
@@ -250,6 +611,62 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_react() {
+        let cmd = parse_command("/react 👍");
+        assert!(matches!(cmd, Some(ClientCommand::React(ref s)) if s == "👍"));
+        assert!(matches!(
+            parse_command("/react"),
+            Some(ClientCommand::Unknown(None))
+        ));
+    }
+
+    #[test]
+    fn parse_ctcp() {
+        assert!(matches!(
+            parse_command("/ctcp alice version"),
+            Some(ClientCommand::Ctcp(ref nick, ref query, None))
+            if nick == "alice" && query == "version"
+        ));
+        assert!(matches!(
+            parse_command("/ctcp alice PING 42"),
+            Some(ClientCommand::Ctcp(_, _, Some(ref params))) if params == "42"
+        ));
+        assert!(matches!(
+            parse_command("/ctcp alice"),
+            Some(ClientCommand::Unknown(None))
+        ));
+    }
+
+    #[test]
+    fn parse_dcc() {
+        assert!(matches!(
+            parse_command("/dcc send bob /tmp/my notes.txt"),
+            Some(ClientCommand::Dcc(DccCommand::Send(ref nick, ref path)))
+            if nick == "bob" && path == "/tmp/my notes.txt"
+        ));
+        assert!(matches!(
+            parse_command("/dcc chat alice"),
+            Some(ClientCommand::Dcc(DccCommand::Chat(ref nick))) if nick == "alice"
+        ));
+        assert!(matches!(
+            parse_command("/dcc get 3"),
+            Some(ClientCommand::Dcc(DccCommand::Get(3)))
+        ));
+        assert!(matches!(
+            parse_command("/dcc resume 4"),
+            Some(ClientCommand::Dcc(DccCommand::Resume(4)))
+        ));
+        assert!(matches!(
+            parse_command("/dcc"),
+            Some(ClientCommand::Dcc(DccCommand::List))
+        ));
+        assert!(matches!(
+            parse_command("/dcc close abc"),
+            Some(ClientCommand::Unknown(None))
+        ));
+    }
+
     #[test]
     fn parse_topic() {
         let cmd = parse_command("/topic new topic");
@@ -20,14 +20,23 @@ pub struct InputCompletion {
 
     //Users per channel, can be changed a lot
     channels: ahash::AHashMap<KeyServerChannel, Trie>,
+
+    //Bans and quiets per channel, for /unban
+    masks: ahash::AHashMap<KeyServerChannel, Trie>,
 }
 
+/// Commands completing the nicknames of the channel after their name
+const MODERATION_COMMANDS: [&str; 8] = [
+    "/kick", "/kickban", "/ban", "/quiet", "/op", "/deop", "/voice", "/devoice",
+];
+
 impl Default for InputCompletion {
     fn default() -> Self {
         Self {
             commands: Trie::new(),
             config: Trie::new(),
             channels: AHashMap::default(),
+            masks: AHashMap::default(),
         }
     }
 }
@@ -76,12 +85,45 @@ impl InputCompletion {
     }
 
     pub fn remove_channel(&mut self, server_id: ServerID, channel: &str) {
-        let channel = Self::sanitize_key(channel);
+        let key = KeyServerChannel {
+            channel: Self::sanitize_key(channel),
+            server_id: Some(server_id),
+        };
+        self.channels.remove(&key);
+        self.masks.remove(&key);
+    }
 
-        self.channels.remove(&KeyServerChannel {
-            channel: channel.to_string(),
+    pub fn add_mask(&mut self, server_id: ServerID, channel: &str, mask: String) {
+        self.masks
+            .entry(KeyServerChannel {
+                channel: Self::sanitize_key(channel),
+                server_id: Some(server_id),
+            })
+            .or_insert(Trie::new())
+            .add_word(mask);
+    }
+
+    pub fn disable_mask(&mut self, server_id: ServerID, channel: &str, mask: &str) {
+        if let Some(masks) = self.masks.get_mut(&KeyServerChannel {
+            channel: Self::sanitize_key(channel),
             server_id: Some(server_id),
-        });
+        }) {
+            masks.disable_word(mask);
+        }
+    }
+
+    pub fn list_masks(
+        &self,
+        server_id: Option<ServerID>,
+        channel: &str,
+        start_word: &str,
+    ) -> Option<Vec<String>> {
+        self.masks
+            .get(&KeyServerChannel {
+                channel: Self::sanitize_key(channel),
+                server_id,
+            })
+            .and_then(|v| v.list(start_word))
     }
 
     pub fn disable_user(&mut self, server_id: ServerID, channel: &str, user: &str) {
@@ -216,6 +258,7 @@ enum CompletionKind {
     Command,
     Config,
     Nickname,
+    Mask,
 }
 
 struct CompletionState {
@@ -290,6 +333,26 @@ impl Completion {
                     list.retain(|v| v.starts_with(last));
                     self.apply_state(list, CompletionKind::Config, start);
                 }
+                ["/unban", ..] if slice.len() > 1 || phrase.ends_with(' ') => {
+                    if let Some(list) = self.input_completion.list_masks(
+                        self.server_id,
+                        &self.current_channel,
+                        last,
+                    ) {
+                        self.apply_state(list, CompletionKind::Mask, start);
+                    }
+                }
+                [command, ..]
+                    if MODERATION_COMMANDS.contains(command)
+                        && (slice.len() > 1 || phrase.ends_with(' ')) =>
+                {
+                    if let Some(list) =
+                        self.input_completion
+                            .list(self.server_id, &self.current_channel, last)
+                    {
+                        self.apply_state(list, CompletionKind::Nickname, start);
+                    }
+                }
                 ["/me", ..] => {
                     if let Some(list) =
                         self.input_completion
@@ -407,6 +470,47 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_moderation_commands() {
+        let mut comp = Completion::default();
+        let id = ServerID::new(0);
+        comp.input_completion.add_command("kick".into());
+        comp.input_completion.add_command("kickban".into());
+        comp.input_completion
+            .add_users(id, "#test", &vec!["tata".to_string()]);
+        comp.input_completion
+            .add_mask(id, "#test", "*!*@spam.example.com".to_string());
+        comp.input_completion
+            .add_mask(id, "#test", "*!*@troll".to_string());
+        comp.input_completion.disable_mask(id, "#test", "*!*@troll");
+        comp.current_channel = "#test".to_string();
+        comp.server_id = Some(id);
+
+        comp.set_completion(0, 5, "/kick");
+        assert_eq!(
+            comp.get_next_completion(true),
+            Some((1, "kick".to_string()))
+        );
+
+        comp.reset();
+        comp.set_completion(9, 10, "/kickban t");
+        assert_eq!(
+            comp.get_next_completion(false),
+            Some((9, "tata".to_string()))
+        );
+
+        comp.reset();
+        comp.set_completion(7, 8, "/unban *");
+        assert_eq!(
+            comp.get_next_completion(false),
+            Some((7, "*!*@spam.example.com".to_string()))
+        );
+        assert_eq!(
+            comp.get_next_completion(false),
+            Some((7, "*!*@spam.example.com".to_string()))
+        );
+    }
+
     #[test]
     fn test_insert() {
         let mut comp = Completion::default();
@@ -6,6 +6,7 @@ use {super::spell_checker::SpellChecker, crate::message_irc::message_content::Me
 
 use crate::component::Draw;
 use crate::message_event::MessageEvent;
+use crate::state::typing::TypingState;
 use crossterm::event::KeyCode;
 use ratatui::{
     Frame,
@@ -22,6 +23,8 @@ pub struct CInput {
     completion: Completion,
     area: Rect,
     redraw: bool,
+    typing_status: String,
+    reply_quote: Option<String>,
 
     #[cfg(feature = "spell-checker")]
     spell_checker: Option<SpellChecker>,
@@ -53,6 +56,22 @@ impl Draw for CInput {
 
         frame.render_widget(input.scroll((0, scroll as u16)), area);
 
+        //The reply is more important than the typing notifications
+        let status = match self.reply_quote.as_deref() {
+            Some(quote) => format!("Replying to {quote} (Esc to cancel)"),
+            None => self.typing_status.clone(),
+        };
+        if area.height > 1 && !status.is_empty() {
+            frame.render_widget(
+                Paragraph::new(status).style(Style::default().fg(ratatui::style::Color::DarkGray)),
+                Rect {
+                    y: area.y + 1,
+                    height: 1,
+                    ..area
+                },
+            );
+        }
+
         let x = self.input.visual_cursor().max(scroll) - scroll + 2;
         frame.set_cursor_position((area.x + x as u16, area.y))
     }
@@ -97,9 +116,15 @@ impl crate::component::EventHandler for CInput {
             MessageEvent::SelectChannel(server_id, channel) => {
                 self.completion.current_channel = channel.to_string();
                 self.completion.server_id = *server_id;
+                self.reply_quote = None;
 
                 None
             }
+            MessageEvent::SelectReply(selected) => {
+                self.reply_quote = selected.as_ref().map(|(_, quote)| quote.clone());
+                self.redraw = true;
+                None
+            }
             MessageEvent::SettingsDidChange => {
                 let (empty, middle) = ctx.model.get_completion_behaviour();
                 self.completion.set_completion_behaviour(
@@ -117,7 +142,41 @@ impl crate::component::EventHandler for CInput {
 
                 None
             }
-            MessageEvent::Part(server_id, channel, user) => {
+            MessageEvent::Mode(server_id, _, channel, changes) => {
+                //The bans and the quiets, for the completion of /unban
+                for change in changes.iter().filter(|v| matches!(v.mode, 'b' | 'q')) {
+                    let Some(mask) = change.parameter.as_deref() else {
+                        continue;
+                    };
+                    if change.is_set {
+                        self.completion.input_completion.add_mask(
+                            *server_id,
+                            channel,
+                            mask.to_string(),
+                        );
+                    } else {
+                        self.completion
+                            .input_completion
+                            .disable_mask(*server_id, channel, mask);
+                    }
+                }
+                None
+            }
+            MessageEvent::ModeList(server_id, channel, mode, entries)
+                if matches!(mode, 'b' | 'q') =>
+            {
+                //The lists asked with /banlist
+                for entry in entries {
+                    self.completion.input_completion.add_mask(
+                        *server_id,
+                        channel,
+                        entry.mask.clone(),
+                    );
+                }
+                None
+            }
+            MessageEvent::Part(server_id, channel, user)
+            | MessageEvent::Kick(server_id, channel, user, _, _) => {
                 if ctx.session.model.is_main_user(*server_id, user) {
                     self.completion
                         .input_completion
@@ -136,9 +195,12 @@ impl crate::component::EventHandler for CInput {
 
     fn handle_events(
         &mut self,
-        _ctx: &mut crate::state::context::Ctx,
+        ctx: &mut crate::state::context::Ctx,
         event: &crate::event_handler::Event,
     ) -> Option<MessageEvent> {
+        if matches!(event, crate::event_handler::Event::Tick) {
+            self.update_typing_status(ctx);
+        }
         match event {
             crate::event_handler::Event::Crossterm(event) => {
                 if let Some(key_event) = event.as_key_event() {
@@ -187,14 +249,16 @@ impl crate::component::EventHandler for CInput {
                         }
                         _ => {
                             self.completion.reset();
+                            let previous = self.input.get_value().to_string();
                             self.input.handle_key_events(&key_event);
-                            None
+                            self.typing_event(&previous)
                         }
                     }
                 } else if let crossterm::event::Event::Paste(content) = event {
                     self.redraw = true;
+                    let previous = self.input.get_value().to_string();
                     self.input.handle_paste(content.to_string());
-                    None
+                    self.typing_event(&previous)
                 } else {
                     None
                 }
@@ -265,6 +329,39 @@ impl CInput {
         spans
     }
 
+    /// Commands are not notified as typing
+    fn typing_event(&self, previous: &str) -> Option<MessageEvent> {
+        let value = self.input.get_value();
+        if value == previous || value.starts_with('/') {
+            None
+        } else if value.is_empty() {
+            Some(MessageEvent::SendTyping(TypingState::Done))
+        } else {
+            Some(MessageEvent::SendTyping(TypingState::Active))
+        }
+    }
+
+    fn update_typing_status(&mut self, ctx: &crate::state::context::Ctx) {
+        let typing_users = ctx
+            .session
+            .get_current_irc_server_model()
+            .and_then(|server| {
+                server
+                    .get_current_channel()
+                    .map(|channel| server.get_typing_users(channel))
+            })
+            .unwrap_or_default();
+        let status = match typing_users.as_slice() {
+            [] => String::new(),
+            [user] => format!("{user} is typing..."),
+            users => format!("{} are typing...", users.join(", ")),
+        };
+        if status != self.typing_status {
+            self.typing_status = status;
+            self.redraw = true;
+        }
+    }
+
     pub fn get_current_input(&self) -> &str {
         self.input.get_value()
     }
@@ -1,32 +1,50 @@
 use crate::component::Child;
 use crate::component::Component;
 use crate::event_handler::Event;
+use crate::irc_view::channel_list_widget::ChannelListWidget;
 use crate::irc_view::discuss::discuss_widget;
 use crate::irc_view::input::command;
 use crate::irc_view::input::command::ClientCommand;
 use crate::irc_view::input::command::help;
 use crate::irc_view::input::input_widget;
 use crate::irc_view::input::input_widget::CInput;
+use crate::irc_view::mode_list_widget::ModeListWidget;
 use crate::irc_view::search_widget;
 use crate::irc_view::search_widget::Query;
 use crate::irc_view::search_widget::QueryOption;
 use crate::irc_view::search_widget::SearchWidget;
 use crate::irc_view::tooltip_widget;
 use crate::irc_view::topic_widget;
+use crate::irc_view::transfers_widget;
 use crate::irc_view::users_widget;
 use crate::message_event::MessageEvent;
 use crate::message_irc::log::message_logger;
 use crate::message_irc::log::message_logger::MessageLogger;
 use crate::message_queue::MessageQueue;
+use crate::state::channel_list::ChannelListEntry;
+use crate::state::ctcp;
+use crate::state::dcc::{self, DccEvent, DccOutcome};
+use crate::state::history;
+use crate::state::history::HistoryMessage;
+use crate::state::invite;
 use crate::state::irc_model::IrcModel;
+use crate::state::irc_model::UserInfo;
+use crate::state::join;
 use crate::state::model::Model;
 use crate::state::model::RunningState;
 use crate::state::model::StoredConfig;
+use crate::state::moderation;
+use crate::state::perform;
 use crate::state::server_id::ServerID;
+use crate::state::services;
 use crate::state::session::Session;
+use crate::state::typing::TypingState;
+use crate::state::whois::{WhoisInfo, WhoisTarget};
 use crate::widget_view;
+use clown_core::bouncer::BouncerNetwork;
 use clown_core::command::Command;
-use clown_core::conn::ConnectionConfig;
+use clown_core::ctcp::Ctcp;
+use clown_core::dcc::DccRequest;
 use clown_core::response::Response;
 use clown_core::response::ResponseNumber;
 use ratatui::layout::Position;
@@ -48,9 +66,15 @@ enum ViewState {
     #[default]
     Discuss,
     Search,
+    ModeList,
+    ChannelList,
 }
 
 const LOG_FLUSH_CHECK_TIMER: u64 = 10;
+/// Time for the plaintext connection to be closed before the upgrade to TLS
+const STS_RECONNECT_DELAY: u64 = 1;
+/// ERR_NICKNAMEINUSE
+const NICKNAME_IN_USE: u16 = 433;
 
 pub struct MainView<'a> {
     input: Component<'a, CInput>,
@@ -58,11 +82,15 @@ pub struct MainView<'a> {
     list_users_view: Component<'a, users_widget::UsersWidget>,
     topic_view: Component<'a, topic_widget::TopicWidget>,
     tooltip_widget: Component<'a, tooltip_widget::ToolTipDiscussWidget>,
+    transfers_widget: Component<'a, transfers_widget::TransfersWidget>,
     search_widget: Component<'a, search_widget::SearchWidget>,
+    mode_list_widget: Component<'a, ModeListWidget>,
+    channel_list_widget: Component<'a, ChannelListWidget>,
 
     need_redraw: bool,
     has_focus: bool,
     state: ViewState,
+    reply_to: Option<String>, /*msgid of the message replied to*/
 
     //TODO move them into their own struct
     log_instant: std::time::Instant,
@@ -96,12 +124,16 @@ impl MainView<'_> {
             input,
             messages_display,
             tooltip_widget,
+            transfers_widget: Component::new("transfers", transfers_widget::TransfersWidget::new()),
             need_redraw: false,
             has_focus: true,
             log_instant: std::time::Instant::now(),
             logger: MessageLogger::new(log_dir),
             search_widget: Component::new("search", SearchWidget::default()),
+            mode_list_widget: Component::new("mode_list", ModeListWidget::default()),
+            channel_list_widget: Component::new("channel_list", ChannelListWidget::default()),
             state: ViewState::default(),
+            reply_to: None,
         }
     }
 
@@ -117,25 +149,26 @@ impl MainView<'_> {
         id
     }
 
-    fn children(&mut self) -> [Component<'_, Child<'_>>; 5] {
+    fn children(&mut self) -> [Component<'_, Child<'_>>; 6] {
         [
             self.input.to_child_mut(),
             self.messages_display.to_child_mut(),
             self.list_users_view.to_child_mut(),
             self.topic_view.to_child_mut(),
             self.tooltip_widget.to_child_mut(),
+            self.transfers_widget.to_child_mut(),
         ]
     }
 
     pub fn log(
         &mut self,
-        connection_config: Option<&ConnectionConfig>,
+        server_address: Option<&str>,
         irc_model: Option<&IrcModel>,
         message: &MessageEvent,
     ) -> anyhow::Result<()> {
-        if let Some(connection_config) = connection_config {
+        if let Some(server_address) = server_address {
             self.logger
-                .write_message(&connection_config.address, irc_model, message)
+                .write_message(server_address, irc_model, message)
         } else {
             Ok(())
         }
@@ -150,10 +183,14 @@ impl MainView<'_> {
         ctx: &mut crate::state::context::Ctx,
         content: &str,
     ) -> Option<MessageEvent> {
-        if let Some(parsed_message) = command::parse_command(content) {
+        let parsed_message = match ctx.session.get_current_irc_server_model() {
+            Some(server) => command::parse_command(content, server.get_isupport()),
+            None => command::parse_command(content, &clown_core::isupport::ISupport::default()),
+        };
+        if let Some(parsed_message) = parsed_message {
             match parsed_message {
                 command::ClientCommand::Connect(server_name) => {
-                    Self::handle_cmd_connect(server_name, &ctx.model)
+                    Self::handle_cmd_connect(server_name, ctx)
                 }
                 command::ClientCommand::Quit(message) => {
                     Self::handle_cmd_quit(message, &mut ctx.session)
@@ -172,6 +209,54 @@ impl MainView<'_> {
                 command::ClientCommand::Part(channel, reason) => {
                     Self::handle_cmd_part(channel, reason, &mut ctx.session)
                 }
+                command::ClientCommand::Invite(nick, channel) => {
+                    Self::handle_cmd_invite(nick, channel, &mut ctx.session)
+                }
+                command::ClientCommand::Accept(channel) => {
+                    Self::handle_cmd_accept(channel, &mut ctx.session)
+                }
+                command::ClientCommand::Kick(channel, nick, reason) => {
+                    Self::handle_cmd_kick(channel, nick, reason, false, ctx)
+                }
+                command::ClientCommand::KickBan(channel, nick, reason) => {
+                    Self::handle_cmd_kick(channel, nick, reason, true, ctx)
+                }
+                command::ClientCommand::Ban(channel, targets) => {
+                    Self::handle_cmd_ban(channel, targets, true, false, ctx)
+                }
+                command::ClientCommand::Unban(channel, targets) => {
+                    Self::handle_cmd_ban(channel, targets, false, false, ctx)
+                }
+                command::ClientCommand::Quiet(channel, targets) => {
+                    Self::handle_cmd_ban(channel, targets, true, true, ctx)
+                }
+                command::ClientCommand::Op(channel, nicks) => {
+                    Self::handle_cmd_member_mode(channel, nicks, true, 'o', &mut ctx.session)
+                }
+                command::ClientCommand::Deop(channel, nicks) => {
+                    Self::handle_cmd_member_mode(channel, nicks, false, 'o', &mut ctx.session)
+                }
+                command::ClientCommand::Voice(channel, nicks) => {
+                    Self::handle_cmd_member_mode(channel, nicks, true, 'v', &mut ctx.session)
+                }
+                command::ClientCommand::Devoice(channel, nicks) => {
+                    Self::handle_cmd_member_mode(channel, nicks, false, 'v', &mut ctx.session)
+                }
+                command::ClientCommand::Mode(target, modes) => {
+                    Self::handle_cmd_mode(target, modes, &mut ctx.session)
+                }
+                command::ClientCommand::Whois(nick) => {
+                    Self::handle_cmd_whois(nick, false, &mut ctx.session)
+                }
+                command::ClientCommand::Whowas(nick) => {
+                    Self::handle_cmd_whois(nick, true, &mut ctx.session)
+                }
+                command::ClientCommand::List(filter) => {
+                    Self::handle_cmd_list(filter, &mut ctx.session)
+                }
+                command::ClientCommand::BanList(channel) => {
+                    Self::handle_cmd_ban_list(channel, &mut ctx.session)
+                }
                 command::ClientCommand::Action(content) => {
                     Self::handle_cmd_action(content, &mut ctx.session)
                 }
@@ -191,18 +276,28 @@ impl MainView<'_> {
                     self.state = ViewState::Search;
                     Self::handle_search(content, None, &ctx.session)
                 }
+                command::ClientCommand::React(reaction) => Self::handle_cmd_react(reaction, ctx),
+                command::ClientCommand::Dcc(dcc_command) => Self::handle_cmd_dcc(dcc_command, ctx),
+                command::ClientCommand::Ctcp(target, query, params) => {
+                    Self::handle_cmd_ctcp(target, query, params, &mut ctx.session)
+                }
             }
         } else {
-            Self::handle_plain_text(content, &mut ctx.session)
+            Self::handle_plain_text(content, &mut ctx.session, self.reply_to.take())
         }
     }
 
-    fn handle_cmd_connect(server_name: Option<String>, model: &Model) -> Option<MessageEvent> {
-        Some(MessageEvent::Connect(
-            server_name
-                .and_then(|v| model.find_id_from_name(&v))
-                .unwrap_or(ServerID::new(0)),
-        ))
+    fn handle_cmd_connect(
+        server_name: Option<String>,
+        ctx: &mut crate::state::context::Ctx,
+    ) -> Option<MessageEvent> {
+        let server_id = server_name
+            .and_then(|v| ctx.model.find_id_from_name(&v))
+            .unwrap_or(ServerID::new(0));
+        //A manual attempt starts a new series of retries
+        ctx.session
+            .reset_retry(server_id, ctx.model.get_reconnect(server_id));
+        Some(MessageEvent::Connect(server_id))
     }
 
     fn handle_cmd_quit(message: Option<String>, session: &mut Session) -> Option<MessageEvent> {
@@ -255,6 +350,142 @@ impl MainView<'_> {
         None
     }
 
+    fn handle_cmd_invite(
+        nick: String,
+        channel: Option<String>,
+        session: &mut Session,
+    ) -> Option<MessageEvent> {
+        if let Err(e) = session.send_command_invite(nick, channel) {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    fn handle_cmd_accept(channel: Option<String>, session: &mut Session) -> Option<MessageEvent> {
+        let Some((server_id, invite)) = session.take_invite(channel.as_deref()) else {
+            return Some(MessageEvent::from_error(anyhow::anyhow!(
+                "No invitation to accept"
+            )));
+        };
+        let key = session
+            .get_channel_key(server_id, &invite.channel)
+            .map(|v| v.to_string());
+        for command in join::commands(vec![(invite.channel, key)]) {
+            if let Err(e) = session.send_command(server_id, command) {
+                return Some(MessageEvent::from_error(e));
+            }
+        }
+        None
+    }
+
+    /// The ban is set before the kick, the user cannot join again in between
+    fn handle_cmd_kick(
+        channel: Option<String>,
+        nick: String,
+        reason: Option<String>,
+        is_ban: bool,
+        ctx: &mut crate::state::context::Ctx,
+    ) -> Option<MessageEvent> {
+        if is_ban {
+            let masks = ctx
+                .session
+                .get_ban_masks(ctx.model.get_ban_mask(), vec![nick.clone()]);
+            if let Err(e) = ctx
+                .session
+                .send_command_bans(channel.clone(), true, false, masks)
+            {
+                return Some(MessageEvent::from_error(e));
+            }
+        }
+        if let Err(e) = ctx.session.send_command_kick(channel, nick, reason) {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    fn handle_cmd_ban(
+        channel: Option<String>,
+        targets: Vec<String>,
+        is_set: bool,
+        is_quiet: bool,
+        ctx: &mut crate::state::context::Ctx,
+    ) -> Option<MessageEvent> {
+        let masks = ctx.session.get_ban_masks(ctx.model.get_ban_mask(), targets);
+        if let Err(e) = ctx
+            .session
+            .send_command_bans(channel, is_set, is_quiet, masks)
+        {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    fn handle_cmd_member_mode(
+        channel: Option<String>,
+        nicks: Vec<String>,
+        is_set: bool,
+        mode: char,
+        session: &mut Session,
+    ) -> Option<MessageEvent> {
+        let changes = moderation::changes(is_set, mode, nicks);
+        if let Err(e) = session.send_command_modes(channel, changes) {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    fn handle_cmd_mode(
+        target: Option<String>,
+        modes: String,
+        session: &mut Session,
+    ) -> Option<MessageEvent> {
+        if let Err(e) = session.send_command_mode(target, modes) {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    /// The reply is shown in the buffer of the command
+    fn handle_cmd_whois(
+        nick: String,
+        is_whowas: bool,
+        session: &mut Session,
+    ) -> Option<MessageEvent> {
+        let Some(irc_server) = session.get_current_irc_server_model() else {
+            return Some(MessageEvent::from_error(anyhow::anyhow!("Not connected")));
+        };
+        let server_id = irc_server.get_server_id();
+        let buffer = irc_server
+            .get_current_channel()
+            .filter(|v| *v != irc_server.get_server_name())
+            .map(|v| v.to_string());
+        if let Err(e) =
+            session.send_command_whois(server_id, nick, is_whowas, WhoisTarget::Buffer(buffer))
+        {
+            return Some(MessageEvent::from_error(e));
+        }
+        None
+    }
+
+    fn handle_cmd_list(filter: Option<String>, session: &mut Session) -> Option<MessageEvent> {
+        match session.send_command_list() {
+            Ok(server_id) => Some(MessageEvent::ChannelListOpen(
+                server_id,
+                filter.unwrap_or_default(),
+            )),
+            Err(e) => Some(MessageEvent::from_error(e)),
+        }
+    }
+
+    fn handle_cmd_ban_list(channel: Option<String>, session: &mut Session) -> Option<MessageEvent> {
+        match session.send_command_mode_lists(channel) {
+            Ok((server_id, channel, modes)) => {
+                Some(MessageEvent::ModeListOpen(server_id, channel, modes))
+            }
+            Err(e) => Some(MessageEvent::from_error(e)),
+        }
+    }
+
     fn handle_cmd_part(
         channel: Option<String>,
         reason: Option<String>,
@@ -267,23 +498,86 @@ impl MainView<'_> {
     }
 
     fn handle_cmd_action(content: String, session: &mut Session) -> Option<MessageEvent> {
+        if let Some(status) = session.get_current_status()
+            && let Some(nick) = status.channel.as_deref().and_then(dcc::chat_nick)
+        {
+            let server_id = status.server_id;
+            let nickname = status.nickname.to_string();
+            let nick = nick.to_string();
+            let line = Ctcp::new("ACTION", Some(content.clone())).to_string();
+            return Some(match session.dcc.send_chat(server_id, &nick, line) {
+                Ok(()) => MessageEvent::ActionMsg(
+                    server_id,
+                    nickname,
+                    dcc::chat_buffer(&nick),
+                    content,
+                    None,
+                ),
+                Err(e) => MessageEvent::from_error(e),
+            });
+        }
         if let Err(e) = session.send_command_action(content.to_string()) {
             return Some(MessageEvent::from_error(e));
         }
         if let Some(status) = session.get_current_status()
             && let Some(status_channel) = status.channel
         {
-            Some(MessageEvent::ActionMsg(
-                status.server_id,
-                status.nickname.to_string(),
-                status_channel.to_string(),
-                content,
-            ))
+            let server_id = status.server_id;
+            let nickname = status.nickname.to_string();
+            let target = status_channel.to_string();
+            if session.is_capability_enabled(server_id, "echo-message") {
+                Some(MessageEvent::PendingAction(
+                    server_id, nickname, target, content,
+                ))
+            } else {
+                Some(MessageEvent::ActionMsg(
+                    server_id, nickname, target, content, None,
+                ))
+            }
         } else {
             None
         }
     }
 
+    /// The passwords of our messages to NickServ are neither shown nor logged
+    fn mask_password(ctx: &crate::state::context::Ctx, msg: MessageEvent) -> MessageEvent {
+        let masked = |server_id: ServerID, target: &str, content: String| {
+            ctx.model
+                .get_services(server_id)
+                .and_then(|v| services::mask_password(target, &content, &v.nickserv))
+                .unwrap_or(content)
+        };
+        match msg {
+            MessageEvent::PrivMsg(server_id, source, target, content, msgid, reply_to)
+                if ctx.session.model.is_main_user(server_id, &source) =>
+            {
+                let content = masked(server_id, &target, content);
+                MessageEvent::PrivMsg(server_id, source, target, content, msgid, reply_to)
+            }
+            MessageEvent::PendingMsg(server_id, source, target, content, reply_to) => {
+                let content = masked(server_id, &target, content);
+                MessageEvent::PendingMsg(server_id, source, target, content, reply_to)
+            }
+            msg => msg,
+        }
+    }
+
+    /// With echo-message, our message is pending until the server sends it back
+    fn own_message(
+        session: &Session,
+        server_id: ServerID,
+        nickname: String,
+        target: String,
+        content: String,
+        reply_to: Option<String>,
+    ) -> MessageEvent {
+        if session.is_capability_enabled(server_id, "echo-message") {
+            MessageEvent::PendingMsg(server_id, nickname, target, content, reply_to)
+        } else {
+            MessageEvent::PrivMsg(server_id, nickname, target, content, None, reply_to)
+        }
+    }
+
     fn handle_cmd_privmsg(
         channel: String,
         content: String,
@@ -295,9 +589,161 @@ impl MainView<'_> {
         )) {
             return Some(MessageEvent::from_error(e));
         }
-        session
-            .get_current_status()
-            .map(|v| MessageEvent::PrivMsg(v.server_id, v.nickname.to_string(), channel, content))
+        session.get_current_status().map(|v| {
+            Self::own_message(
+                session,
+                v.server_id,
+                v.nickname.to_string(),
+                channel,
+                content,
+                None,
+            )
+        })
+    }
+
+    fn handle_cmd_ctcp(
+        target: String,
+        query: String,
+        params: Option<String>,
+        session: &mut Session,
+    ) -> Option<MessageEvent> {
+        let mut ctcp = Ctcp::new(&query, params);
+        //The reply gives back the time to measure the lag
+        if ctcp.command == "PING" && ctcp.params.is_none() {
+            ctcp.params = Some(ctcp::ping_params(std::time::SystemTime::now()));
+        }
+        let server_id = session.get_current_status()?.server_id;
+        if let Err(e) = session.send_command(
+            server_id,
+            Command::PrivMsg(target.clone(), ctcp.to_string()),
+        ) {
+            return Some(MessageEvent::from_error(e));
+        }
+        Some(MessageEvent::AddMessageViewInfo(
+            Some(server_id),
+            None,
+            crate::message_irc::message_content::MessageKind::Info,
+            format!("CTCP {} sent to {target}", ctcp.command),
+        ))
+    }
+
+    fn handle_cmd_dcc(
+        dcc_command: command::DccCommand,
+        ctx: &mut crate::state::context::Ctx,
+    ) -> Option<MessageEvent> {
+        let info = |content: String| {
+            MessageEvent::AddMessageViewInfo(
+                None,
+                None,
+                crate::message_irc::message_content::MessageKind::Info,
+                content,
+            )
+        };
+        let configured_address = ctx.model.get_dcc_config().address.clone();
+        match dcc_command {
+            command::DccCommand::Send(nick, path) => {
+                let server_id = ctx.session.get_current_status()?.server_id;
+                let passive = ctx.model.get_dcc_config().passive;
+                let result = ctx
+                    .session
+                    .get_dcc_address(server_id, configured_address.as_deref())
+                    .and_then(|address| {
+                        ctx.session.dcc.offer(
+                            server_id,
+                            &nick,
+                            std::path::Path::new(&path),
+                            address,
+                            passive,
+                        )
+                    })
+                    .and_then(|offer| {
+                        ctx.session.send_command(
+                            server_id,
+                            Command::PrivMsg(nick.clone(), offer.to_string()),
+                        )
+                    });
+                Some(match result {
+                    Ok(()) => info(format!("DCC: {path} offered to {nick}")),
+                    Err(e) => MessageEvent::from_error(e),
+                })
+            }
+            command::DccCommand::Get(id) | command::DccCommand::Resume(id) => {
+                let resume = matches!(dcc_command, command::DccCommand::Resume(_));
+                let Some((server_id, nick)) = ctx
+                    .session
+                    .dcc
+                    .get(id)
+                    .map(|v| (v.server_id, v.nick.clone()))
+                else {
+                    return Some(info(format!("DCC {id}: no such transfer")));
+                };
+                let download_dir = ctx.model.get_download_dir();
+                let result = ctx
+                    .session
+                    .get_dcc_address(server_id, configured_address.as_deref())
+                    .and_then(|address| ctx.session.dcc.accept(id, &download_dir, address, resume))
+                    .and_then(|reply| match reply {
+                        Some(reply) => ctx
+                            .session
+                            .send_command(server_id, Command::PrivMsg(nick, reply.to_string())),
+                        None => Ok(()),
+                    });
+                Some(match result {
+                    Ok(()) => info(format!("DCC {id}: accepted")),
+                    Err(e) => MessageEvent::from_error(e),
+                })
+            }
+            command::DccCommand::Chat(nick) => {
+                let server_id = ctx.session.get_current_status()?.server_id;
+                let passive = ctx.model.get_dcc_config().passive;
+                let result = ctx
+                    .session
+                    .get_dcc_address(server_id, configured_address.as_deref())
+                    .and_then(|address| {
+                        ctx.session
+                            .dcc
+                            .offer_chat(server_id, &nick, address, passive)
+                    })
+                    .and_then(|offer| {
+                        ctx.session.send_command(
+                            server_id,
+                            Command::PrivMsg(nick.clone(), offer.to_string()),
+                        )
+                    });
+                Some(match result {
+                    Ok(()) => info(format!("DCC: chat offered to {nick}")),
+                    Err(e) => MessageEvent::from_error(e),
+                })
+            }
+            command::DccCommand::Close(id) => Some(info(if ctx.session.dcc.close(id) {
+                format!("DCC {id}: closed")
+            } else {
+                format!("DCC {id}: no such transfer")
+            })),
+            command::DccCommand::List => {
+                let mut output = String::from("DCC transfers:");
+                for transfer in ctx.session.dcc.transfers() {
+                    output.push_str(&format!(
+                        "\n{}: {} {} {}, {}",
+                        transfer.id,
+                        transfer.filename(),
+                        match transfer.direction {
+                            crate::state::dcc::TransferDirection::Send => "to",
+                            crate::state::dcc::TransferDirection::Receive => "from",
+                        },
+                        transfer.nick,
+                        transfer.state
+                    ));
+                }
+                for chat in ctx.session.dcc.chats() {
+                    output.push_str(&format!(
+                        "\n{}: chat with {}, {}",
+                        chat.id, chat.nick, chat.state
+                    ));
+                }
+                Some(info(output))
+            }
+        }
     }
 
     fn handle_cmd_config(
@@ -353,17 +799,73 @@ impl MainView<'_> {
         let channel = channel.or_else(|| status.and_then(|s| s.channel.map(|v| v.to_string())));
 
         if let Some(channel) = channel {
-            if channel.starts_with('#')
+            if server_id.is_some_and(|id| session.model.is_channel(id, &channel))
                 && let Err(e) = session.send_command_part(Some(channel.clone()), None)
             {
                 return Some(MessageEvent::from_error(e));
             }
+            if let Some(server_id) = server_id
+                && let Some(nick) = dcc::chat_nick(&channel)
+            {
+                session.dcc.close_chat(server_id, nick);
+            }
             Some(MessageEvent::CloseBuffer(server_id, channel))
         } else {
             None
         }
     }
 
+    fn handle_cmd_react(
+        reaction: String,
+        ctx: &mut crate::state::context::Ctx,
+    ) -> Option<MessageEvent> {
+        let status = ctx.session.get_current_status()?;
+        let server_id = status.server_id;
+        let nickname = status.nickname.to_string();
+        let channel = status.channel?.to_string();
+        if !ctx.session.is_capability_enabled(server_id, "message-tags") {
+            return Some(MessageEvent::AddMessageViewInfo(
+                None,
+                None,
+                crate::message_irc::message_content::MessageKind::Error,
+                "The server does not support message-tags".to_string(),
+            ));
+        }
+        let Some(msgid) = ctx
+            .messages
+            .get_last_msgid(server_id, &channel.to_lowercase())
+            .map(|v| v.to_string())
+        else {
+            return Some(MessageEvent::AddMessageViewInfo(
+                None,
+                None,
+                crate::message_irc::message_content::MessageKind::Error,
+                "No message to react to".to_string(),
+            ));
+        };
+
+        if let Err(e) = ctx.session.send_command(
+            server_id,
+            Command::Tagged(
+                vec![
+                    ("+draft/react".to_string(), reaction.clone()),
+                    ("+draft/reply".to_string(), msgid.clone()),
+                ],
+                Box::new(Command::TagMsg(channel.clone())),
+            ),
+        ) {
+            return Some(MessageEvent::from_error(e));
+        }
+        //Without echo-message, the reaction is displayed directly
+        if ctx.session.is_capability_enabled(server_id, "echo-message") {
+            None
+        } else {
+            Some(MessageEvent::Reaction(
+                server_id, nickname, channel, msgid, reaction,
+            ))
+        }
+    }
+
     fn handle_cmd_unknown(command_name: Option<String>) -> Option<MessageEvent> {
         Some(MessageEvent::AddMessageViewInfo(
             None,
@@ -392,29 +894,64 @@ impl MainView<'_> {
         }))
     }
 
-    fn handle_plain_text(content: &str, session: &mut Session) -> Option<MessageEvent> {
+    fn handle_plain_text(
+        content: &str,
+        session: &mut Session,
+        reply_to: Option<String>,
+    ) -> Option<MessageEvent> {
         if let Some(cstatus) = session.get_current_status().map(|v| v.to_owned())
             && let Some(status_channel) = cstatus.channel
         {
             let content = content.to_string();
-            if status_channel.eq_ignore_ascii_case(&cstatus.nickname) {
+            if let Some(nick) = dcc::chat_nick(&status_channel) {
+                //Sent directly to the nick, not through the server
+                match session
+                    .dcc
+                    .send_chat(cstatus.server_id, nick, content.clone())
+                {
+                    Err(e) => Some(MessageEvent::from_error(e)),
+                    Ok(()) => Some(MessageEvent::PrivMsg(
+                        cstatus.server_id,
+                        cstatus.nickname.to_string(),
+                        status_channel.to_string(),
+                        content,
+                        None,
+                        None,
+                    )),
+                }
+            } else if status_channel.eq_ignore_ascii_case(&cstatus.nickname) {
                 Some(MessageEvent::PrivMsg(
                     cstatus.server_id,
                     cstatus.nickname.to_string(),
                     status_channel.to_string(),
                     content,
+                    None,
+                    None,
                 ))
             } else {
-                match session.send_command_current_server(clown_core::command::Command::PrivMsg(
+                //The reply is sent as a client tag (+draft/reply)
+                let reply_to = reply_to
+                    .filter(|_| session.is_capability_enabled(cstatus.server_id, "message-tags"));
+                let command = clown_core::command::Command::PrivMsg(
                     status_channel.to_string(),
                     content.to_string(),
-                )) {
+                );
+                let command = match reply_to.as_ref() {
+                    Some(msgid) => clown_core::command::Command::Tagged(
+                        vec![("+draft/reply".to_string(), msgid.clone())],
+                        Box::new(command),
+                    ),
+                    None => command,
+                };
+                match session.send_command_current_server(command) {
                     Err(e) => Some(MessageEvent::from_error(e)),
-                    _ => Some(MessageEvent::PrivMsg(
+                    _ => Some(Self::own_message(
+                        session,
                         cstatus.server_id,
                         cstatus.nickname.to_string(),
                         status_channel.to_string(),
                         content,
+                        reply_to,
                     )),
                 }
             }
@@ -446,38 +983,481 @@ impl MainView<'_> {
                 if ctx.session.is_irc_finished(server_id) {
                     ctx.session.clear_connection(server_id);
                 }
-                if let Some(time) = ctx.session.get_duration_before_retry(server_id) {
+                //STS, the connection is reopened with TLS without waiting
+                if ctx.session.take_sts_upgrade(server_id) {
+                    messages.push_message_with_time(
+                        MessageEvent::Connect(server_id),
+                        std::time::Duration::from_secs(STS_RECONNECT_DELAY),
+                    );
+                } else if let Some(time) = ctx.session.schedule_retry(server_id) {
                     messages.push_message_with_time(MessageEvent::Connect(server_id), time);
+                } else {
+                    messages.push_message(MessageEvent::AddMessageViewInfo(
+                        Some(server_id),
+                        None,
+                        crate::message_irc::message_content::MessageKind::Error,
+                        "No retries left, use /connect to try again".to_string(),
+                    ));
+                }
+            }
+
+            let ids = ctx.session.iter_valid_connection_id().collect::<Vec<_>>();
+            for id in ids {
+                if let Err(e) = ctx.session.poll_notify(id, ctx.model.get_notify_list(id)) {
+                    messages.push_message(e.into());
+                }
+                let channels = ctx.session.poll_identification(id, false);
+                Self::join_channels(ctx, id, channels, messages);
+            }
+        }
+
+        messages.push_message(MessageEvent::PullIRC);
+    }
+
+    fn handle_tick(
+        &mut self,
+        ctx: &mut crate::state::context::Ctx,
+        event: &Event,
+        messages: &mut MessageQueue,
+    ) {
+        self.handle_irc(ctx, messages);
+        for event in ctx.session.dcc.poll() {
+            messages.push_message(match event {
+                DccEvent::Info(server_id, buffer, message) => {
+                    if let Some(buffer) = buffer.as_ref() {
+                        ctx.session.model.open_query(server_id, buffer);
+                    }
+                    MessageEvent::AddMessageViewInfo(
+                        Some(server_id),
+                        buffer,
+                        crate::message_irc::message_content::MessageKind::Info,
+                        message,
+                    )
+                }
+                DccEvent::ChatLine(server_id, nick, line) => {
+                    let buffer = dcc::chat_buffer(&nick);
+                    match Ctcp::parse(&line).filter(|v| v.is_action()) {
+                        Some(action) => MessageEvent::ActionMsg(
+                            server_id,
+                            nick,
+                            buffer,
+                            action.params.unwrap_or_default(),
+                            None,
+                        ),
+                        None => MessageEvent::PrivMsg(server_id, nick, buffer, line, None, None),
+                    }
+                }
+            });
+        }
+        if self.log_instant.elapsed() > std::time::Duration::from_secs(LOG_FLUSH_CHECK_TIMER) {
+            if let Err(e) = self.flush_log() {
+                tracing::error!(error = %e, "Log flush failed");
+            }
+            self.log_instant = std::time::Instant::now();
+        }
+        for mut child in self.children() {
+            if let Some(message) = child.handle_events(ctx, event) {
+                messages.push_message(message);
+            }
+        }
+        if self.state == ViewState::Search
+            && let Some(message) = self.search_widget.handle_events(ctx, event)
+        {
+            messages.push_message(message);
+        }
+    }
+
+    /// Ask the missing messages since the last one received or logged
+    fn request_history(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        channel: &str,
+    ) -> anyhow::Result<()> {
+        let last_time = ctx
+            .messages
+            .get_last_message_time(server_id, &channel.to_lowercase());
+        if ctx
+            .session
+            .is_capability_enabled(server_id, "draft/chathistory")
+        {
+            let reference = last_time
+                .map(|time| format!("timestamp={}", history::format_server_time(time)))
+                .unwrap_or_else(|| "*".to_string());
+
+            ctx.session.send_command(
+                server_id,
+                Command::ChatHistory(
+                    "LATEST".to_string(),
+                    channel.to_string(),
+                    reference,
+                    history::HISTORY_LIMIT,
+                ),
+            )
+        } else if ctx
+            .session
+            .is_capability_enabled(server_id, "znc.in/playback")
+        {
+            //The ZNC buffer is not replayed automatically with this capability
+            let since = last_time
+                .map(history::format_playback_time)
+                .unwrap_or_else(|| "0".to_string());
+            ctx.session.send_command(
+                server_id,
+                Command::PrivMsg("*playback".to_string(), format!("PLAY {channel} {since}")),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// A soju bouncer announces its networks, each one gets its own server
+    fn handle_bouncer(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        sub_command: &str,
+        parameters: &[String],
+        messages: &mut MessageQueue,
+    ) {
+        //Only the connection which is not bound to a network manages them
+        if !sub_command.eq_ignore_ascii_case("NETWORK")
+            || ctx.model.get_bouncer_network(server_id).is_some()
+        {
+            return;
+        }
+        let Some(network) = BouncerNetwork::parse(parameters).filter(|v| !v.is_deleted) else {
+            return;
+        };
+        match ctx
+            .model
+            .add_bouncer_network(server_id, &network.id, network.name.as_deref())
+        {
+            Ok(Some(id)) => {
+                ctx.session.add_server();
+                messages.push_message(MessageEvent::AddMessageViewInfo(
+                    Some(server_id),
+                    None,
+                    crate::message_irc::message_content::MessageKind::Info,
+                    format!("New network '{}'", ctx.model.get_name(id)),
+                ));
+                messages.push_message(MessageEvent::Connect(id));
+            }
+            Ok(None) => {}
+            Err(e) => messages.push_message(e.into()),
+        }
+    }
+
+    fn handle_batch(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        reference: &str,
+        parameters: &[String],
+        messages: &mut MessageQueue,
+    ) {
+        let Some(batches) = ctx.session.get_batches_mut(server_id) else {
+            return;
+        };
+        if let Some(reference) = reference.strip_prefix('+') {
+            if let Some((kind, parameters)) = parameters.split_first() {
+                batches.start(reference, kind, parameters);
+            }
+        } else if let Some(reference) = reference.strip_prefix('-')
+            && let Some(batch) = batches.end(reference)
+            && batch.is_history()
+            && let Some(target) = batch.parameters.first()
+        {
+            let history =
+                ctx.messages
+                    .filter_history(server_id, &target.to_lowercase(), batch.messages);
+            if !history.is_empty() {
+                messages.push_message(MessageEvent::History(server_id, target.clone(), history));
+            }
+        }
+    }
+
+    /// The queries are answered by a NOTICE and shown in the server buffer
+    fn handle_ctcp_query(
+        server_id: ServerID,
+        source: &str,
+        query: &Ctcp,
+        ctx: &mut crate::state::context::Ctx,
+        messages: &mut MessageQueue,
+    ) {
+        if let Some(request) = DccRequest::parse(query) {
+            Self::handle_dcc_request(server_id, source, request, ctx, messages);
+            return;
+        }
+        //Our own queries come back with echo-message
+        if ctx.session.model.is_main_user(server_id, source) {
+            return;
+        }
+        let settings = ctx.model.get_ctcp_config().clone();
+        let answer = match ctx.session.reply_ctcp(server_id, source, query, &settings) {
+            Ok(true) => "",
+            Ok(false) => ", not answered",
+            Err(e) => {
+                tracing::error!(error = %e, "CTCP reply");
+                ", not answered"
+            }
+        };
+        messages.push_message(MessageEvent::AddMessageViewInfo(
+            Some(server_id),
+            None,
+            crate::message_irc::message_content::MessageKind::Info,
+            format!("CTCP {} from {source}{answer}", query.command),
+        ));
+    }
+
+    /// Offers are only accepted with /dcc get
+    fn handle_dcc_request(
+        server_id: ServerID,
+        source: &str,
+        request: DccRequest,
+        ctx: &mut crate::state::context::Ctx,
+        messages: &mut MessageQueue,
+    ) {
+        //Our own offers come back with echo-message
+        if ctx.session.model.is_main_user(server_id, source) {
+            return;
+        }
+        let outcome = ctx
+            .session
+            .get_dcc_address(server_id, ctx.model.get_dcc_config().address.as_deref())
+            .and_then(|address| {
+                ctx.session
+                    .dcc
+                    .handle_request(server_id, source, request, address)
+            });
+        let info = match outcome {
+            Ok(DccOutcome::Offered(id)) if ctx.session.dcc.get_chat(id).is_some() => Some(format!(
+                "DCC {id}: {source} offers a chat, /dcc get {id} to accept or /dcc close {id} to refuse"
+            )),
+            Ok(DccOutcome::Started(id)) if ctx.session.dcc.get_chat(id).is_some() => {
+                Some(format!("DCC {id}: chat with {source} started"))
+            }
+            Ok(DccOutcome::Offered(id)) => ctx.session.dcc.get(id).map(|transfer| {
+                let size = transfer
+                    .size
+                    .map(|v| format!(" ({})", transfers_widget::format_size(v)))
+                    .unwrap_or_default();
+                format!(
+                    "DCC {id}: {source} offers {}{size}, /dcc get {id} to accept or /dcc close {id} to refuse",
+                    transfer.filename()
+                )
+            }),
+            Ok(DccOutcome::Reply(reply)) => ctx
+                .session
+                .send_command(server_id, Command::PrivMsg(source.to_string(), reply.to_string()))
+                .err()
+                .map(|e| format!("DCC: cannot answer {source}, {e}")),
+            Ok(DccOutcome::Started(id)) => Some(format!("DCC {id}: transfer with {source} started")),
+            Ok(DccOutcome::Ignored) => None,
+            Err(e) => Some(format!("DCC from {source}: {e}")),
+        };
+        if let Some(info) = info {
+            messages.push_message(MessageEvent::AddMessageViewInfo(
+                Some(server_id),
+                None,
+                crate::message_irc::message_content::MessageKind::Info,
+                info,
+            ));
+        }
+    }
+
+    fn join_channels(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        channels: Vec<String>,
+        messages: &mut MessageQueue,
+    ) {
+        //The key of the configuration wins over the one remembered from the session
+        let channels = channels
+            .into_iter()
+            .map(|channel| {
+                let key = ctx
+                    .model
+                    .get_channel_key(server_id, &channel)
+                    .or_else(|| ctx.session.get_channel_key(server_id, &channel))
+                    .map(str::to_string);
+                (channel, key)
+            })
+            .collect();
+        for command in join::commands(channels) {
+            if let Err(e) = ctx.session.send_command(server_id, command) {
+                messages.push_message(e.into());
+            }
+        }
+    }
+
+    /// Our invitations wait for /accept unless allowed by the config,
+    /// the ones of the other users (invite-notify) are shown in the channel
+    fn handle_invite(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        source: String,
+        nick: String,
+        channel: String,
+        messages: &mut MessageQueue,
+    ) {
+        if !ctx.session.model.is_main_user(server_id, &nick) {
+            messages.push_message(MessageEvent::AddMessageViewInfo(
+                Some(server_id),
+                Some(channel.clone()),
+                crate::message_irc::message_content::MessageKind::Info,
+                format!("{source} has invited {nick} to {channel}"),
+            ));
+            return;
+        }
+        let info = if invite::is_allowed(ctx.model.get_accept_invites(server_id), &source, &channel)
+        {
+            Self::join_channels(ctx, server_id, vec![channel.clone()], messages);
+            format!("{source} invites you to {channel}, joining")
+        } else {
+            let info = format!("{source} invites you to {channel}, /accept {channel} to join");
+            ctx.session.add_invite(server_id, source, channel);
+            info
+        };
+        messages.push_message(MessageEvent::AddMessageViewInfo(
+            Some(server_id),
+            None,
+            crate::message_irc::message_content::MessageKind::Info,
+            info,
+        ));
+    }
+
+    /// The notices of the services go to the server buffer, NickServ confirms the identification
+    fn handle_services_notice(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        source: String,
+        message: String,
+        messages: &mut MessageQueue,
+    ) {
+        if ctx
+            .model
+            .get_services(server_id)
+            .is_some_and(|v| source.eq_ignore_ascii_case(&v.nickserv))
+            && services::parse_reply(&message).is_some()
+        {
+            let channels = ctx.session.poll_identification(server_id, true);
+            Self::join_channels(ctx, server_id, channels, messages);
+        }
+        let server_name = ctx.session.model.get_server_name(server_id).to_string();
+        messages.push_message(MessageEvent::Notice(
+            server_id,
+            source,
+            server_name,
+            message,
+        ));
+    }
+
+    /// IDENTIFY to NickServ, the channels wait for it if needed
+    fn handle_services_login(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        nickname: &str,
+        channels: Vec<String>,
+        messages: &mut MessageQueue,
+    ) {
+        let settings = ctx
+            .model
+            .get_services(server_id)
+            .cloned()
+            .unwrap_or_default();
+        let primary = ctx
+            .model
+            .get_nickname(server_id)
+            .unwrap_or(nickname)
+            .to_string();
+        let commands = services::login_commands(&settings, &primary, nickname);
+        if commands.is_empty() {
+            Self::join_channels(ctx, server_id, channels, messages);
+            return;
+        }
+        for command in commands {
+            if let Err(e) = ctx.session.send_hidden_command(server_id, command) {
+                messages.push_message(e.into());
+            }
+        }
+        if settings.recover == crate::state::config::Recover::Ghost
+            && !primary.eq_ignore_ascii_case(nickname)
+            && let Some(generation) = ctx.session.connection_generation(server_id)
+        {
+            messages.push_message_with_time(
+                MessageEvent::Perform(server_id, generation, format!("/nick {primary}")),
+                services::GHOST_NICK_DELAY,
+            );
+        }
+        if settings.delay_join {
+            ctx.session.wait_identification(server_id);
+        }
+        Self::join_channels(ctx, server_id, channels, messages);
+    }
+
+    /// The targets of MONITOR replies can be nick!user@host
+    fn handle_monitor(
+        server_id: ServerID,
+        targets: &[String],
+        is_online: bool,
+        messages: &mut MessageQueue,
+    ) {
+        for target in targets {
+            let nick = target.split_once('!').map_or(target.as_str(), |v| v.0);
+            messages.push_message(MessageEvent::Notify(server_id, nick.to_string(), is_online));
+        }
+    }
+
+    fn update_whois(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        nick: &str,
+        update: impl FnOnce(&mut WhoisInfo),
+    ) {
+        if let Some(whois) = ctx.session.get_whois_mut(server_id) {
+            update(whois.reply(nick));
+        }
+    }
+
+    /// The replies are shown together, in the tooltip or in a buffer
+    fn handle_end_of_whois(
+        ctx: &mut crate::state::context::Ctx,
+        server_id: ServerID,
+        nick: &str,
+        messages: &mut MessageQueue,
+    ) {
+        let Some((info, target)) = ctx
+            .session
+            .get_whois_mut(server_id)
+            .and_then(|v| v.finish(nick))
+        else {
+            return;
+        };
+        match target {
+            WhoisTarget::ToolTip => {
+                messages.push_message(MessageEvent::ToolTip(info.lines().join("\n")));
+            }
+            WhoisTarget::Buffer(buffer) => {
+                for line in info.lines() {
+                    messages.push_message(MessageEvent::AddMessageViewInfo(
+                        Some(server_id),
+                        buffer.clone(),
+                        crate::message_irc::message_content::MessageKind::Info,
+                        line,
+                    ));
                 }
             }
         }
-
-        messages.push_message(MessageEvent::PullIRC);
     }
 
-    fn handle_tick(
-        &mut self,
+    /// The entries of a list are sent to the popup once they are all received
+    fn handle_end_of_mode_list(
         ctx: &mut crate::state::context::Ctx,
-        event: &Event,
+        server_id: ServerID,
+        channel: String,
+        mode: char,
         messages: &mut MessageQueue,
     ) {
-        self.handle_irc(ctx, messages);
-        if self.log_instant.elapsed() > std::time::Duration::from_secs(LOG_FLUSH_CHECK_TIMER) {
-            if let Err(e) = self.flush_log() {
-                tracing::error!(error = %e, "Log flush failed");
-            }
-            self.log_instant = std::time::Instant::now();
-        }
-        for mut child in self.children() {
-            if let Some(message) = child.handle_events(ctx, event) {
-                messages.push_message(message);
-            }
-        }
-        if self.state == ViewState::Search
-            && let Some(message) = self.search_widget.handle_events(ctx, event)
-        {
-            messages.push_message(message);
-        }
+        let entries = ctx.session.finish_mode_list(server_id, &channel, mode);
+        messages.push_message(MessageEvent::ModeList(server_id, channel, mode, entries));
     }
 
     fn update_pull_irc(
@@ -486,34 +1466,94 @@ impl MainView<'_> {
         messages: &mut MessageQueue,
     ) {
         let mut server_to_init = vec![];
-        for (server_id, recieved) in ctx.session.pull_all_server_message() {
+        let received_messages = ctx.session.pull_all_server_message().collect::<Vec<_>>();
+        for (server_id, recieved) in received_messages {
+            //Messages sent inside a history batch are displayed when the batch ends
+            if let Some(reference) = recieved.tag("batch")
+                && let Some(batches) = ctx.session.get_batches_mut(server_id)
+                && batches.is_history(&reference)
+            {
+                if let Some(message) = HistoryMessage::from_server_message(&recieved) {
+                    batches.push(&reference, message);
+                }
+                continue;
+            }
+
             let reply = recieved.reply();
             let source = recieved.source().map(|v| v.to_string());
+            let user_host = recieved.source_user_host();
+            let msgid = recieved.tag("msgid").map(|v| v.to_string());
+            //Channel which has sent the command (labeled-response)
+            let label_target = ctx
+                .session
+                .route_labeled_reply(server_id, &recieved, &reply);
 
             debug!("server_id : {:?}, {:?}", server_id, recieved);
             //log_info_sync(format!("{reply:?}\n").as_str());
             match reply {
                 Response::Cmd(command) => match command {
                     Command::PrivMsg(target, content) => {
+                        //Echo of a password sent to NickServ
+                        if ctx.session.is_hidden_echo(server_id, &recieved) {
+                            continue;
+                        }
                         if let Some(source) = source {
-                            if content.starts_with("\x01ACTION") {
-                                if let Some(parsed_content) = content.get(8..content.len() - 1) {
+                            if let Some(ctcp) = Ctcp::parse(&content) {
+                                if ctcp.is_action() {
                                     messages.push_message(MessageEvent::ActionMsg(
                                         server_id,
                                         source,
                                         target,
-                                        parsed_content.to_string(),
+                                        ctcp.params.unwrap_or_default(),
+                                        msgid,
                                     ));
+                                } else {
+                                    Self::handle_ctcp_query(
+                                        server_id, &source, &ctcp, ctx, messages,
+                                    );
                                 }
                             } else {
                                 messages.push_message(MessageEvent::PrivMsg(
-                                    server_id, source, target, content,
+                                    server_id,
+                                    source,
+                                    target,
+                                    content,
+                                    msgid,
+                                    recieved.tag("+draft/reply").map(|v| v.to_string()),
                                 ));
                             }
                         } else {
                             tracing::error!(error = %MessageError::MissingSource, "PrivMSG");
                         }
                     }
+                    Command::TagMsg(target) => {
+                        if let Some(source) = source {
+                            if let Some(state) = recieved
+                                .tag("+typing")
+                                .and_then(|v| TypingState::from_tag(&v))
+                            {
+                                messages.push_message(MessageEvent::Typing(
+                                    server_id,
+                                    source.clone(),
+                                    target.clone(),
+                                    state,
+                                ));
+                            }
+                            if let Some(reaction) = recieved.tag("+draft/react")
+                                && let Some(reply_to) = recieved.tag("+draft/reply")
+                            {
+                                messages.push_message(MessageEvent::Reaction(
+                                    server_id,
+                                    source,
+                                    target,
+                                    reply_to.to_string(),
+                                    reaction.to_string(),
+                                ));
+                            }
+                        } else {
+                            tracing::error!(error = %MessageError::MissingSource, "TagMsg");
+                        }
+                    }
                     Command::Nick(new_user) => {
                         if let Some(source) = source
                             && let Some(nickname) = ctx.model.get_nickname(server_id)
@@ -534,9 +1574,33 @@ impl MainView<'_> {
                     Command::Notice(target, message) => {
                         //Display a notice directly to the user current channel
                         if let Some(source) = source {
-                            messages.push_message(MessageEvent::Notice(
-                                server_id, target, source, message,
-                            ));
+                            let is_service = ctx
+                                .model
+                                .get_services(server_id)
+                                .is_some_and(|v| services::is_service(&source, &v.nickserv));
+                            if is_service {
+                                Self::handle_services_notice(
+                                    ctx, server_id, source, message, messages,
+                                );
+                            } else if let Some(reply) = Ctcp::parse(&message) {
+                                //Our own replies come back with echo-message
+                                if !ctx.session.model.is_main_user(server_id, &source) {
+                                    messages.push_message(MessageEvent::AddMessageViewInfo(
+                                        Some(server_id),
+                                        None,
+                                        crate::message_irc::message_content::MessageKind::Info,
+                                        ctcp::describe_reply(
+                                            &source,
+                                            &reply,
+                                            std::time::SystemTime::now(),
+                                        ),
+                                    ));
+                                }
+                            } else {
+                                messages.push_message(MessageEvent::Notice(
+                                    server_id, target, source, message,
+                                ));
+                            }
                         }
                     }
                     Command::Topic(channel, topic) => {
@@ -562,7 +1626,7 @@ impl MainView<'_> {
                             tracing::error!(error = %MessageError::MissingSource, "Part");
                         }
                     }
-                    Command::Join(channel) => {
+                    Command::Join(ref channel) | Command::ExtendedJoin(ref channel, _, _) => {
                         if let Some(source) = source {
                             //Create a new 'user' as IRC-Server
                             messages.push_message(MessageEvent::Join(
@@ -570,21 +1634,137 @@ impl MainView<'_> {
                                 channel.clone(),
                                 source.clone(),
                             ));
+                            if let Some(user_host) = user_host {
+                                messages.push_message(MessageEvent::UserInfo(
+                                    server_id,
+                                    source.clone(),
+                                    UserInfo::Host(user_host),
+                                ));
+                            }
+                            if let Command::ExtendedJoin(_, account, realname) = &command {
+                                messages.push_message(MessageEvent::UserInfo(
+                                    server_id,
+                                    source.clone(),
+                                    UserInfo::Account((account != "*").then(|| account.clone())),
+                                ));
+                                messages.push_message(MessageEvent::UserInfo(
+                                    server_id,
+                                    source.clone(),
+                                    UserInfo::RealName(realname.clone()),
+                                ));
+                            }
 
                             //If the server has received the join command and send it back
                             // we are allowed to select the channel if we are the current user
-                            if let Some(nickname) = ctx.model.get_nickname(server_id)
-                                && source.eq_ignore_ascii_case(nickname)
-                            {
+                            if ctx.session.model.is_main_user(server_id, &source) {
                                 messages.push_message(MessageEvent::SelectChannel(
                                     Some(server_id),
                                     channel.clone(),
                                 ));
+                                if let Err(e) = Self::request_history(ctx, server_id, channel) {
+                                    messages.push_message(e.into());
+                                }
+                                //The server replies with the modes of the channel
+                                if let Err(e) = ctx.session.send_command(
+                                    server_id,
+                                    Command::Mode(channel.clone(), String::new()),
+                                ) {
+                                    messages.push_message(e.into());
+                                }
                             }
                         } else {
                             tracing::error!(error = %MessageError::MissingSource, "Join");
                         }
                     }
+                    Command::Away(message) => {
+                        if let Some(source) = source {
+                            messages.push_message(MessageEvent::UserInfo(
+                                server_id,
+                                source,
+                                UserInfo::Away(message),
+                            ));
+                        }
+                    }
+                    Command::Account(account) => {
+                        if let Some(source) = source {
+                            messages.push_message(MessageEvent::UserInfo(
+                                server_id,
+                                source,
+                                UserInfo::Account((account != "*").then_some(account)),
+                            ));
+                        }
+                    }
+                    Command::ChgHost(user, host) => {
+                        if let Some(source) = source {
+                            messages.push_message(MessageEvent::UserInfo(
+                                server_id,
+                                source,
+                                UserInfo::Host(format!("{user}@{host}")),
+                            ));
+                        }
+                    }
+                    Command::SetName(realname) => {
+                        if let Some(source) = source {
+                            messages.push_message(MessageEvent::UserInfo(
+                                server_id,
+                                source,
+                                UserInfo::RealName(realname),
+                            ));
+                        }
+                    }
+                    Command::Mode(target, modes) => {
+                        if let Some(irc_server) = ctx.session.model.get_server(server_id) {
+                            let changes = if irc_server.is_channel(&target) {
+                                clown_core::mode::parse_channel_modes(
+                                    &modes,
+                                    irc_server.get_isupport(),
+                                )
+                            } else {
+                                clown_core::mode::parse_user_modes(&modes)
+                            };
+                            messages.push_message(MessageEvent::Mode(
+                                server_id, source, target, changes,
+                            ));
+                        }
+                    }
+                    Command::Kick(channel, nick, reason) => {
+                        if let Some(source) = source {
+                            //Join again if we are the one kicked
+                            if ctx.session.model.is_main_user(server_id, &nick)
+                                && ctx.model.is_autorejoin_by_id(server_id)
+                            {
+                                Self::join_channels(
+                                    ctx,
+                                    server_id,
+                                    vec![channel.clone()],
+                                    messages,
+                                );
+                            }
+                            messages.push_message(MessageEvent::Kick(
+                                server_id, channel, nick, source, reason,
+                            ));
+                        } else {
+                            tracing::error!(error = %MessageError::MissingSource, "Kick");
+                        }
+                    }
+                    Command::Invite(nick, channel) => {
+                        if let Some(source) = source {
+                            Self::handle_invite(ctx, server_id, source, nick, channel, messages);
+                        } else {
+                            tracing::error!(error = %MessageError::MissingSource, "Invite");
+                        }
+                    }
+                    Command::Cap(_) => {
+                        if let Err(e) = ctx.session.handle_capabilities(server_id) {
+                            tracing::error!(error = %e, "Cannot save the STS policy");
+                        }
+                    }
+                    Command::Batch(reference, parameters) => {
+                        Self::handle_batch(ctx, server_id, &reference, &parameters, messages);
+                    }
+                    Command::Bouncer(sub_command, parameters) => {
+                        Self::handle_bouncer(ctx, server_id, &sub_command, &parameters, messages);
+                    }
                     Command::Error(err) => {
                         messages.push_message(MessageEvent::AddMessageViewInfo(
                             Some(server_id),
@@ -597,7 +1777,7 @@ impl MainView<'_> {
                     Command::Unknown(content) => {
                         messages.push_message(MessageEvent::AddMessageViewInfo(
                             Some(server_id),
-                            None,
+                            label_target,
                             crate::message_irc::message_content::MessageKind::Error,
                             content,
                         ));
@@ -618,7 +1798,6 @@ impl MainView<'_> {
                     ResponseNumber::YourHost(content)
                     | ResponseNumber::Created(content)
                     | ResponseNumber::MyInfo(content)
-                    | ResponseNumber::Bounce(content)
                     | ResponseNumber::LUserClient(content)
                     | ResponseNumber::LUserOp(content)
                     | ResponseNumber::LUserUnknown(content)
@@ -635,6 +1814,106 @@ impl MainView<'_> {
                             content,
                         ));
                     }
+                    ResponseNumber::ISupport(tokens, content) => {
+                        messages.push_message(MessageEvent::AddMessageViewInfo(
+                            Some(server_id),
+                            source.clone(),
+                            crate::message_irc::message_content::MessageKind::Normal,
+                            format!("{} {}", tokens.join(" "), content),
+                        ));
+                        messages.push_message(MessageEvent::ISupport(server_id, tokens));
+                    }
+                    ResponseNumber::ChannelModeIs(channel, modes) => {
+                        if let Some(irc_server) = ctx.session.model.get_server(server_id) {
+                            let changes = clown_core::mode::parse_channel_modes(
+                                &modes,
+                                irc_server.get_isupport(),
+                            );
+                            messages.push_message(MessageEvent::Mode(
+                                server_id, None, channel, changes,
+                            ));
+                        }
+                    }
+                    ResponseNumber::WhoisUser(nick, user, host, realname) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| {
+                            v.user_host = Some(format!("{user}@{host}"));
+                            v.realname = Some(realname).filter(|v| !v.is_empty());
+                        });
+                    }
+                    ResponseNumber::WhowasUser(nick, user, host, realname) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| {
+                            v.user_host = Some(format!("{user}@{host}"));
+                            v.realname = Some(realname).filter(|v| !v.is_empty());
+                            v.is_whowas = true;
+                        });
+                    }
+                    ResponseNumber::WhoisServer(nick, server, info) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| {
+                            v.server = Some(if info.is_empty() {
+                                server
+                            } else {
+                                format!("{server} ({info})")
+                            });
+                        });
+                    }
+                    ResponseNumber::WhoisOperator(nick, _) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| v.is_operator = true);
+                    }
+                    ResponseNumber::WhoisIdleTime(nick, idle, signon) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| {
+                            v.idle = Some(idle);
+                            v.signon = signon;
+                        });
+                    }
+                    ResponseNumber::WhoisChannels(nick, channels) => {
+                        //Long lists of channels are sent in several replies
+                        Self::update_whois(ctx, server_id, &nick, |v| v.channels.extend(channels));
+                    }
+                    ResponseNumber::WhoisAccount(nick, account) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| v.account = Some(account));
+                    }
+                    ResponseNumber::WhoisActually(nick, actual_host) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| {
+                            v.actual_host = Some(actual_host)
+                        });
+                    }
+                    ResponseNumber::WhoisSecure(nick) => {
+                        Self::update_whois(ctx, server_id, &nick, |v| v.is_secure = true);
+                    }
+                    ResponseNumber::EndOfWhois(nick) | ResponseNumber::EndOfWhowas(nick) => {
+                        Self::handle_end_of_whois(ctx, server_id, &nick, messages);
+                    }
+                    ResponseNumber::List(channel, users, topic) => {
+                        if let Some(channel_list) = ctx.session.get_channel_list_mut(server_id) {
+                            channel_list.push(ChannelListEntry::new(channel, users, &topic));
+                        }
+                    }
+                    ResponseNumber::ListEnd(_) => {
+                        if let Some(channel_list) = ctx.session.get_channel_list_mut(server_id) {
+                            channel_list.finish();
+                        }
+                    }
+                    ResponseNumber::BanList(channel, entry) => {
+                        ctx.session
+                            .push_mode_list_entry(server_id, &channel, 'b', entry);
+                    }
+                    ResponseNumber::ExceptionList(channel, entry) => {
+                        ctx.session
+                            .push_mode_list_entry(server_id, &channel, 'e', entry);
+                    }
+                    ResponseNumber::InviteList(channel, entry) => {
+                        ctx.session
+                            .push_mode_list_entry(server_id, &channel, 'I', entry);
+                    }
+                    ResponseNumber::EndOfBanList(channel) => {
+                        Self::handle_end_of_mode_list(ctx, server_id, channel, 'b', messages);
+                    }
+                    ResponseNumber::EndOfExceptionList(channel) => {
+                        Self::handle_end_of_mode_list(ctx, server_id, channel, 'e', messages);
+                    }
+                    ResponseNumber::EndOfInviteList(channel) => {
+                        Self::handle_end_of_mode_list(ctx, server_id, channel, 'I', messages);
+                    }
                     ResponseNumber::NameReply(_symbol, channel, list_users) => {
                         messages.push_message(MessageEvent::UpdateUsers(
                             server_id, channel, list_users,
@@ -644,10 +1923,65 @@ impl MainView<'_> {
                         messages
                             .push_message(MessageEvent::SetTopic(server_id, None, channel, topic));
                     }
-                    ResponseNumber::Err(_, content) => {
+                    ResponseNumber::Ison(online) => {
+                        //The reply only contains the nicknames online
+                        for nick in ctx.model.get_notify_list(server_id) {
+                            messages.push_message(MessageEvent::Notify(
+                                server_id,
+                                nick.clone(),
+                                online.iter().any(|v| v.eq_ignore_ascii_case(nick)),
+                            ));
+                        }
+                    }
+                    ResponseNumber::MonOnline(targets) => {
+                        Self::handle_monitor(server_id, &targets, true, messages);
+                    }
+                    ResponseNumber::MonOffline(targets) => {
+                        Self::handle_monitor(server_id, &targets, false, messages);
+                    }
+                    ResponseNumber::Invite(nick, channel) => {
+                        messages.push_message(MessageEvent::AddMessageViewInfo(
+                            Some(server_id),
+                            Some(channel.clone()),
+                            crate::message_irc::message_content::MessageKind::Info,
+                            format!("{nick} has been invited to {channel}"),
+                        ));
+                    }
+                    ResponseNumber::NeedReggedNick(channel, content) => {
+                        let content = if ctx.session.delay_join(server_id, &channel) {
+                            format!("{channel} needs an account, joined once identified")
+                        } else {
+                            content
+                        };
+                        messages.push_message(MessageEvent::AddMessageViewInfo(
+                            Some(server_id),
+                            label_target,
+                            crate::message_irc::message_content::MessageKind::Error,
+                            content,
+                        ));
+                    }
+                    ResponseNumber::LoggedIn(content) => {
+                        let channels = ctx.session.poll_identification(server_id, true);
+                        Self::join_channels(ctx, server_id, channels, messages);
                         messages.push_message(MessageEvent::AddMessageViewInfo(
                             Some(server_id),
                             None,
+                            crate::message_irc::message_content::MessageKind::Info,
+                            content,
+                        ));
+                    }
+                    ResponseNumber::Err(code, content) => {
+                        //Another nickname is tried, the registration cannot be done without one
+                        if code == NICKNAME_IN_USE
+                            && let Some(nickname) = ctx.session.alternative_nick(server_id)
+                            && let Err(e) =
+                                ctx.session.send_command(server_id, Command::Nick(nickname))
+                        {
+                            messages.push_message(e.into());
+                        }
+                        messages.push_message(MessageEvent::AddMessageViewInfo(
+                            Some(server_id),
+                            label_target,
                             crate::message_irc::message_content::MessageKind::Error,
                             content,
                         ));
@@ -659,7 +1993,7 @@ impl MainView<'_> {
                 Response::Unknown(content) => {
                     messages.push_message(MessageEvent::AddMessageViewInfo(
                         Some(server_id),
-                        None,
+                        label_target,
                         crate::message_irc::message_content::MessageKind::Error,
                         content,
                     ));
@@ -667,18 +2001,79 @@ impl MainView<'_> {
             };
         }
 
+        //A long LIST is displayed while it loads, one batch per pull
+        let server_ids = ctx.session.iter_valid_connection_id().collect::<Vec<_>>();
+        for id in server_ids {
+            if let Some((entries, is_complete)) =
+                ctx.session.get_channel_list_mut(id).and_then(|v| v.take())
+            {
+                messages.push_message(MessageEvent::ChannelList(id, entries, is_complete));
+            }
+        }
+
         for id in server_to_init {
-            ctx.session.reset_retry(id);
-            if ctx.model.is_autojoin_by_id(id) {
-                for channel in ctx.model.get_channels(id) {
-                    if let Err(e) = ctx
-                        .session
-                        .send_command(id, clown_core::command::Command::Join(channel.to_string()))
-                    {
-                        messages.push_message(e.into());
-                    }
+            ctx.session.reset_retry(id, ctx.model.get_reconnect(id));
+            ctx.session.set_endpoint_working(id);
+            ctx.session.start_notify(id);
+            if ctx
+                .session
+                .is_capability_enabled(id, "soju.im/bouncer-networks")
+                && ctx.model.get_bouncer_network(id).is_none()
+                && let Err(e) = ctx
+                    .session
+                    .send_command(id, Command::Bouncer("LISTNETWORKS".to_string(), Vec::new()))
+            {
+                messages.push_message(e.into());
+            }
+            let Some(current_nick) = ctx
+                .session
+                .model
+                .get_server(id)
+                .map(|v| v.get_current_nick().to_string())
+            else {
+                continue;
+            };
+            //Registered with another nickname, ours was used
+            let nickname = ctx
+                .session
+                .registered_nick(id)
+                .unwrap_or(current_nick.clone());
+            if !nickname.eq_ignore_ascii_case(&current_nick) {
+                messages.push_message(MessageEvent::ReplaceUser(
+                    id,
+                    current_nick,
+                    nickname.clone(),
+                ));
+            }
+            let entries =
+                perform::schedule(ctx.model.get_perform(id), &nickname, ctx.model.get_name(id));
+            if let Some(generation) = ctx.session.connection_generation(id) {
+                for (delay, entry) in entries {
+                    messages.push_message_with_time(
+                        MessageEvent::Perform(id, generation, entry),
+                        delay,
+                    );
+                }
+            }
+            let (joined, queries) = ctx.session.take_open_buffers(id);
+            let mut channels = if ctx.model.is_autojoin_by_id(id) {
+                ctx.model
+                    .get_channels(id)
+                    .map(|v| v.to_string())
+                    .collect::<Vec<String>>()
+            } else {
+                Vec::new()
+            };
+            //The channels joined before losing the connection, even interactively
+            for channel in joined {
+                if !channels.iter().any(|v| v.eq_ignore_ascii_case(&channel)) {
+                    channels.push(channel);
                 }
             }
+            Self::handle_services_login(ctx, id, &nickname, channels, messages);
+            for query in queries {
+                ctx.session.model.open_query(id, &query);
+            }
         }
     }
 }
@@ -693,6 +2088,14 @@ impl widget_view::WidgetView for MainView<'_> {
             return true;
         }
 
+        if self.state == ViewState::ModeList && self.mode_list_widget.need_redraw() {
+            return true;
+        }
+
+        if self.state == ViewState::ChannelList && self.channel_list_widget.need_redraw() {
+            return true;
+        }
+
         for child in self.children().iter_mut() {
             if child.need_redraw() {
                 return true;
@@ -717,6 +2120,7 @@ impl widget_view::WidgetView for MainView<'_> {
                             0
                         }), // Topic area
                         Constraint::Percentage(100), // Messages area
+                        Constraint::Length(transfers_widget::TransfersWidget::height(ctx)), // DCC
                         Constraint::Length(2),       // Input area
                     ])
                     .split(frame.area());
@@ -745,7 +2149,11 @@ impl widget_view::WidgetView for MainView<'_> {
                 }
 
                 // Render widgets
-                if let Some(input_area) = main_layout.get(2) {
+                if let Some(transfers_area) = main_layout.get(2) {
+                    self.transfers_widget.render(ctx, frame, *transfers_area);
+                }
+
+                if let Some(input_area) = main_layout.get(3) {
                     self.input.render(ctx, frame, *input_area);
                 }
 
@@ -754,6 +2162,8 @@ impl widget_view::WidgetView for MainView<'_> {
                 }
             }
             ViewState::Search => self.search_widget.render(ctx, frame, frame.area()),
+            ViewState::ModeList => self.mode_list_widget.render(ctx, frame, frame.area()),
+            ViewState::ChannelList => self.channel_list_widget.render(ctx, frame, frame.area()),
         }
     }
 
@@ -782,6 +2192,20 @@ impl widget_view::WidgetView for MainView<'_> {
                                 messages.push_message(new_message);
                             }
                         }
+                        ViewState::ModeList => {
+                            if let Some(new_message) =
+                                self.mode_list_widget.handle_events(ctx, event)
+                            {
+                                messages.push_message(new_message);
+                            }
+                        }
+                        ViewState::ChannelList => {
+                            if let Some(new_message) =
+                                self.channel_list_widget.handle_events(ctx, event)
+                            {
+                                messages.push_message(new_message);
+                            }
+                        }
                     }
                 }
             }
@@ -817,6 +2241,16 @@ impl widget_view::WidgetView for MainView<'_> {
                         messages.push_message(new_message);
                     }
                 }
+                ViewState::ModeList => {
+                    if let Some(new_message) = self.mode_list_widget.handle_events(ctx, event) {
+                        messages.push_message(new_message);
+                    }
+                }
+                ViewState::ChannelList => {
+                    if let Some(new_message) = self.channel_list_widget.handle_events(ctx, event) {
+                        messages.push_message(new_message);
+                    }
+                }
             },
             Event::Tick => {
                 self.handle_tick(ctx, event, messages);
@@ -831,13 +2265,19 @@ impl widget_view::WidgetView for MainView<'_> {
         msg: MessageEvent,
         messages: &mut MessageQueue,
     ) {
+        let msg = Self::mask_password(ctx, msg);
         match &msg {
             MessageEvent::MessageInput(content) => {
+                let is_replying = self.reply_to.is_some();
                 for m in content.split(['\r', '\n']).filter(|s| !s.is_empty()) {
                     if let Some(v) = self.update_input(ctx, m) {
                         messages.push_message(v);
                     }
                 }
+                //The reply has been sent
+                if is_replying && self.reply_to.is_none() {
+                    messages.push_message(MessageEvent::SelectReply(None));
+                }
                 return; // Usually, we don't need to propagate raw input to children here
             }
             #[allow(clippy::print_stdout)]
@@ -849,6 +2289,90 @@ impl widget_view::WidgetView for MainView<'_> {
                 self.state = ViewState::Discuss;
                 return;
             }
+            MessageEvent::ModeListOpen(..) => {
+                self.state = ViewState::ModeList;
+            }
+            MessageEvent::ModeListEnd() => {
+                self.state = ViewState::Discuss;
+                return;
+            }
+            MessageEvent::WhoisToolTip(server_id, nick) => {
+                //What is known is shown until the reply
+                if let Some(user) = ctx
+                    .session
+                    .model
+                    .get_server(*server_id)
+                    .and_then(|v| v.get_user(nick))
+                {
+                    messages.push_message(MessageEvent::Hover(user.describe()));
+                }
+                //A recent reply is reused and a pending request is not sent again
+                let now = std::time::Instant::now();
+                if let Some(whois) = ctx.session.get_whois_mut(*server_id) {
+                    if let Some(info) = whois.cached(nick, now) {
+                        messages.push_message(MessageEvent::ToolTip(info.lines().join("\n")));
+                        return;
+                    }
+                    if whois.is_requested(nick, now) {
+                        return;
+                    }
+                }
+                if let Err(e) = ctx.session.send_command_whois(
+                    *server_id,
+                    nick.clone(),
+                    false,
+                    WhoisTarget::ToolTip,
+                ) {
+                    messages.push_message(e.into());
+                }
+                return;
+            }
+            MessageEvent::ChannelListOpen(..) => {
+                self.state = ViewState::ChannelList;
+            }
+            MessageEvent::ChannelListEnd() => {
+                self.state = ViewState::Discuss;
+                return;
+            }
+            MessageEvent::ChannelListJoin(server_id, channel) => {
+                self.state = ViewState::Discuss;
+                if let Err(e) = ctx
+                    .session
+                    .send_command(*server_id, Command::Join(channel.clone()))
+                {
+                    messages.push_message(e.into());
+                }
+                return;
+            }
+            MessageEvent::ModeListChange(server_id, channel, changes) => {
+                if let Err(e) =
+                    ctx.session
+                        .send_channel_modes(*server_id, channel.clone(), changes.clone())
+                {
+                    messages.push_message(e.into());
+                }
+                return;
+            }
+            MessageEvent::Perform(server_id, generation, entry) => {
+                //Scheduled before a reconnection, it was already run or will be again
+                if ctx.session.connection_generation(*server_id) != Some(*generation) {
+                    return;
+                }
+                match perform::to_command(entry) {
+                    Some(command) => {
+                        if let Err(e) = ctx.session.send_command(*server_id, command) {
+                            messages.push_message(e.into());
+                        }
+                    }
+                    None => messages.push_message(MessageEvent::AddMessageViewInfo(
+                        Some(*server_id),
+                        None,
+                        crate::message_irc::message_content::MessageKind::Error,
+                        format!("Invalid perform entry {entry}"),
+                    )),
+                }
+                return;
+            }
             MessageEvent::Search(..) => {
                 if let Some(v) = self.search_widget.handle_actions(ctx, &msg) {
                     messages.push_message(v);
@@ -865,35 +2389,50 @@ impl widget_view::WidgetView for MainView<'_> {
 
                 messages.push_message(MessageEvent::JoinServer(*server_id));
 
-                messages.push_message(MessageEvent::AddMessageViewInfo(
-                    Some(*server_id),
-                    None,
-                    crate::message_irc::message_content::MessageKind::Info,
-                    format!("Try to connect to '{}'...", addr),
-                ));
                 let server_name = ctx.model.get_name(*server_id).to_string();
                 messages.push_message(MessageEvent::SelectChannel(
                     Some(*server_id),
                     server_name.clone(),
                 ));
 
-                if let Some(conn_cfg) = ctx.model.get_connection_config(*server_id)
+                ctx.session
+                    .set_reconnect_policy(*server_id, ctx.model.get_reconnect(*server_id));
+                let conn_cfgs = ctx.model.get_connection_configs(*server_id);
+                if !conn_cfgs.is_empty()
                     && let Some(login_cfg) = ctx.model.get_login_config(*server_id)
                 {
                     if ctx.session.is_irc_finished(*server_id) {
-                        if let Err(e) = ctx.session.init_connection(*server_id, conn_cfg, login_cfg)
+                        let random = ctx.model.is_random_endpoints(*server_id);
+                        match ctx
+                            .session
+                            .init_connection(*server_id, conn_cfgs, random, login_cfg)
                         {
-                            tracing::error!(error =%e);
-                            messages.push_message(MessageEvent::AddMessageViewInfo(
-                                Some(*server_id),
-                                None,
-                                crate::message_irc::message_content::MessageKind::Error,
-                                e.to_string(),
-                            ));
-                        } else {
-                            let nick = ctx.model.get_nickname(*server_id).unwrap_or("No Nick");
-                            ctx.session
-                                .init_irc_model(nick.to_string(), *server_id, server_name);
+                            Err(e) => {
+                                tracing::error!(error =%e);
+                                messages.push_message(MessageEvent::AddMessageViewInfo(
+                                    Some(*server_id),
+                                    None,
+                                    crate::message_irc::message_content::MessageKind::Error,
+                                    e.to_string(),
+                                ));
+                            }
+                            Ok(endpoint) => {
+                                messages.push_message(MessageEvent::AddMessageViewInfo(
+                                    Some(*server_id),
+                                    None,
+                                    crate::message_irc::message_content::MessageKind::Info,
+                                    format!(
+                                        "Try to connect to '{}:{}'...",
+                                        endpoint.address, endpoint.port
+                                    ),
+                                ));
+                                let nick = ctx.model.get_nickname(*server_id).unwrap_or("No Nick");
+                                ctx.session.init_irc_model(
+                                    nick.to_string(),
+                                    *server_id,
+                                    server_name,
+                                );
+                            }
                         }
                     } else {
                         messages.push_message(MessageEvent::AddMessageViewInfo(
@@ -934,11 +2473,41 @@ impl widget_view::WidgetView for MainView<'_> {
                 self.update_pull_irc(ctx, messages);
                 return;
             }
+            MessageEvent::SettingsDidChange => {
+                let ids = ctx.session.iter_valid_connection_id().collect::<Vec<_>>();
+                for id in ids {
+                    if let Err(e) = ctx.session.update_notify(id, ctx.model.get_notify_list(id)) {
+                        messages.push_message(e.into());
+                    }
+                }
+            }
+            MessageEvent::SelectReply(selected) => {
+                self.reply_to = selected.as_ref().map(|(msgid, _)| msgid.clone());
+            }
+            MessageEvent::SelectChannel(..) => {
+                self.reply_to = None;
+            }
+            MessageEvent::SendTyping(state) => {
+                if let Some(server_id) = ctx.session.model.current_id
+                    && ctx.session.is_capability_enabled(server_id, "message-tags")
+                    && let Some(channel) = ctx
+                        .session
+                        .get_current_irc_server_model()
+                        .and_then(|v| v.get_current_channel())
+                        .filter(|v| !v.eq_ignore_ascii_case(ctx.model.get_name(server_id)))
+                        .filter(|v| dcc::chat_nick(v).is_none())
+                        .map(|v| v.to_string())
+                    && let Err(e) = ctx.session.send_typing(server_id, &channel, *state)
+                {
+                    tracing::error!(error = %e, "Cannot send typing");
+                }
+                return;
+            }
             MessageEvent::QuitAll(reason) => {
                 for id in ctx.session.iter_valid_connection_id() {
                     if let Some(nickname) = ctx.model.get_nickname(id)
                         && let Err(e) = self.log(
-                            ctx.model.get_connection_config(id).as_ref(),
+                            ctx.model.get_log_address(id).as_deref(),
                             Some(&ctx.session.model),
                             &MessageEvent::Quit(id, nickname.to_string(), reason.clone()),
                         )
@@ -950,9 +2519,12 @@ impl widget_view::WidgetView for MainView<'_> {
             }
             // Handle Logging for IRC events
             MessageEvent::ActionMsg(id, ..)
+            | MessageEvent::History(id, ..)
             | MessageEvent::Join(id, ..)
             | MessageEvent::JoinServer(id, ..)
+            | MessageEvent::Mode(id, ..)
             | MessageEvent::Part(id, ..)
+            | MessageEvent::Kick(id, ..)
             | MessageEvent::Quit(id, ..)
             | MessageEvent::ReplaceUser(id, ..)
             | MessageEvent::PrivMsg(id, ..)
@@ -960,7 +2532,7 @@ impl widget_view::WidgetView for MainView<'_> {
             | MessageEvent::Notice(id, ..)
             | MessageEvent::SetTopic(id, ..) => {
                 if let Err(e) = self.log(
-                    ctx.model.get_connection_config(*id).as_ref(),
+                    ctx.model.get_log_address(*id).as_deref(),
                     Some(&ctx.session.model),
                     &msg,
                 ) {
@@ -970,6 +2542,18 @@ impl widget_view::WidgetView for MainView<'_> {
             _ => {}
         }
 
+        //The lists are refreshed by the replies and the MODE changes
+        if self.state == ViewState::ModeList
+            && let Some(new_msg) = self.mode_list_widget.handle_actions(ctx, &msg)
+        {
+            messages.push_message(new_msg);
+        }
+        if self.state == ViewState::ChannelList
+            && let Some(new_msg) = self.channel_list_widget.handle_actions(ctx, &msg)
+        {
+            messages.push_message(new_msg);
+        }
+
         for child in self.children().iter_mut() {
             if let Some(new_msg) = child.handle_actions(ctx, &msg) {
                 messages.push_message(new_msg);
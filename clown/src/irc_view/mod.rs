@@ -1,11 +1,14 @@
+mod channel_list_widget;
 pub mod color_user;
 pub mod discuss;
 pub mod main_view;
 
 mod input;
+mod mode_list_widget;
 pub mod search_widget;
 mod tooltip_widget;
 mod topic_widget;
+mod transfers_widget;
 mod users_widget;
 #[cfg(feature = "website-preview")]
 mod website_preview;
@@ -0,0 +1,499 @@
+use crate::component::{Draw, EventHandler};
+use crate::irc_view::input::text_input;
+use crate::message_event::MessageEvent;
+use crate::state::server_id::ServerID;
+use crate::state::{mode_list, moderation};
+use clown_core::response::ListEntry;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Row, Table, TableState, Tabs};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+const HELP: &str = "Enter: add  Space: mark  Del: remove  Tab: next list  Esc: close";
+
+fn mode_name(mode: char) -> &'static str {
+    match mode {
+        'b' => "Bans",
+        'e' => "Exceptions",
+        'I' => "Invite exceptions",
+        _ => "List",
+    }
+}
+
+/// Local date of an entry, the servers send a unix timestamp
+fn format_date(set_at: Option<u64>) -> String {
+    set_at
+        .and_then(|v| i64::try_from(v).ok())
+        .and_then(|v| chrono::DateTime::from_timestamp(v, 0))
+        .map(|v| {
+            v.with_timezone(&chrono::Local)
+                .format(DATE_FORMAT)
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// The setter is often sent as nick!user@host
+fn setter_nick(setter: &str) -> &str {
+    setter.split('!').next().unwrap_or(setter)
+}
+
+struct ModeList {
+    mode: char,
+    /// None until the end of the list is received
+    entries: Option<Vec<ListEntry>>,
+}
+
+/// Bans, exceptions and invite exceptions of a channel, one tab per list
+#[derive(Default)]
+pub struct ModeListWidget {
+    area: Rect,
+    server_id: Option<ServerID>,
+    channel: String,
+    lists: Vec<ModeList>,
+    tab: usize,
+    table_state: TableState,
+    /// Masks removed together with Del
+    marked: Vec<String>,
+    input: text_input::InputWidget,
+    need_redraw: bool,
+}
+
+impl ModeListWidget {
+    fn is_for(&self, server_id: ServerID, channel: &str) -> bool {
+        self.server_id == Some(server_id) && self.channel.eq_ignore_ascii_case(channel)
+    }
+
+    fn current_entries(&self) -> &[ListEntry] {
+        self.lists
+            .get(self.tab)
+            .and_then(|v| v.entries.as_deref())
+            .unwrap_or_default()
+    }
+
+    fn current_mode(&self) -> Option<char> {
+        self.lists.get(self.tab).map(|v| v.mode)
+    }
+
+    /// Keep the selection on an entry and forget the masks which are gone
+    fn fix_selection(&mut self) {
+        let len = self.current_entries().len();
+        let selected = match self.table_state.selected() {
+            _ if len == 0 => None,
+            Some(selected) => Some(selected.min(len - 1)),
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+        let entries = self
+            .lists
+            .get(self.tab)
+            .and_then(|v| v.entries.as_deref())
+            .unwrap_or_default();
+        self.marked
+            .retain(|mask| entries.iter().any(|v| v.mask.eq_ignore_ascii_case(mask)));
+        self.need_redraw = true;
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        if self.lists.is_empty() {
+            return;
+        }
+        self.tab = tab % self.lists.len();
+        self.marked.clear();
+        self.table_state.select(None);
+        self.fix_selection();
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let selected = self.table_state.selected().unwrap_or_default();
+        self.table_state.select(Some(if down {
+            selected.saturating_add(1)
+        } else {
+            selected.saturating_sub(1)
+        }));
+        self.fix_selection();
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(mask) = self
+            .table_state
+            .selected()
+            .and_then(|v| self.current_entries().get(v))
+            .map(|v| v.mask.clone())
+        else {
+            return;
+        };
+        match self.marked.iter().position(|v| *v == mask) {
+            Some(position) => {
+                self.marked.remove(position);
+            }
+            None => self.marked.push(mask),
+        }
+        self.move_selection(true);
+    }
+
+    /// The marked masks, or the selected one
+    fn remove(&mut self) -> Option<MessageEvent> {
+        let server_id = self.server_id?;
+        let mode = self.current_mode()?;
+        let masks = if self.marked.is_empty() {
+            let selected = self.table_state.selected()?;
+            vec![self.current_entries().get(selected)?.mask.clone()]
+        } else {
+            std::mem::take(&mut self.marked)
+        };
+        self.need_redraw = true;
+        Some(MessageEvent::ModeListChange(
+            server_id,
+            self.channel.clone(),
+            moderation::changes(false, mode, masks),
+        ))
+    }
+
+    fn add(&mut self) -> Option<MessageEvent> {
+        let server_id = self.server_id?;
+        let mode = self.current_mode()?;
+        let mask = self.input.get_value().trim().to_string();
+        if mask.is_empty() || mask.contains(char::is_whitespace) {
+            return None;
+        }
+        self.input.reset();
+        self.need_redraw = true;
+        Some(MessageEvent::ModeListChange(
+            server_id,
+            self.channel.clone(),
+            moderation::changes(true, mode, vec![mask]),
+        ))
+    }
+
+    fn handle_key(&mut self, key: &KeyEvent) -> Option<MessageEvent> {
+        match key.code {
+            KeyCode::Esc => Some(MessageEvent::ModeListEnd()),
+            KeyCode::Tab => {
+                self.select_tab(self.tab.saturating_add(1));
+                None
+            }
+            KeyCode::BackTab => {
+                self.select_tab(self.tab.saturating_add(self.lists.len().saturating_sub(1)));
+                None
+            }
+            KeyCode::Up => {
+                self.move_selection(false);
+                None
+            }
+            KeyCode::Down => {
+                self.move_selection(true);
+                None
+            }
+            //A mask never contains a space
+            KeyCode::Char(' ') => {
+                self.toggle_mark();
+                None
+            }
+            KeyCode::Delete => self.remove(),
+            KeyCode::Enter => self.add(),
+            _ => {
+                self.input.handle_key_events(key);
+                self.need_redraw = true;
+                None
+            }
+        }
+    }
+
+    fn rows(&self) -> Vec<Row<'_>> {
+        let marked_style = Style::default().fg(Color::Yellow);
+        self.current_entries()
+            .iter()
+            .map(|entry| {
+                let is_marked = self
+                    .marked
+                    .iter()
+                    .any(|v| v.eq_ignore_ascii_case(&entry.mask));
+                Row::new([
+                    format!("{} {}", if is_marked { "*" } else { " " }, entry.mask),
+                    entry
+                        .setter
+                        .as_deref()
+                        .map(setter_nick)
+                        .unwrap_or_default()
+                        .to_string(),
+                    format_date(entry.set_at),
+                ])
+                .style(if is_marked {
+                    marked_style
+                } else {
+                    Style::default()
+                })
+            })
+            .collect()
+    }
+}
+
+impl Draw for ModeListWidget {
+    fn render(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        frame: &mut ratatui::prelude::Frame<'_>,
+        area: Rect,
+    ) {
+        self.need_redraw = false;
+        self.area = area;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),       // Lists
+                Constraint::Percentage(100), // Entries
+                Constraint::Length(2),       // Input area
+            ])
+            .split(area);
+
+        if let Some(tabs_area) = layout.first() {
+            let titles = self.lists.iter().map(|list| {
+                let count = list
+                    .entries
+                    .as_ref()
+                    .map_or("…".to_string(), |v| v.len().to_string());
+                format!("{} ({count})", mode_name(list.mode))
+            });
+            let tabs = Tabs::new(titles)
+                .select(self.tab)
+                .highlight_style(Style::default().fg(Color::Cyan))
+                .block(ratatui::widgets::Block::default().title(format!("{} ", self.channel)));
+            frame.render_widget(tabs, *tabs_area);
+        }
+
+        if let Some(table_area) = layout.get(1) {
+            let is_loaded = self
+                .lists
+                .get(self.tab)
+                .is_some_and(|v| v.entries.is_some());
+            if is_loaded {
+                let table = Table::new(
+                    self.rows(),
+                    [
+                        Constraint::Min(20),    // Mask
+                        Constraint::Length(16), // Setter
+                        Constraint::Length(16), // Date
+                    ],
+                )
+                .header(
+                    Row::new(["  Mask", "Set by", "Date"])
+                        .style(Style::default().add_modifier(Modifier::BOLD)),
+                )
+                .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .column_spacing(1);
+                let mut table_state = self.table_state;
+                frame.render_stateful_widget(table, *table_area, &mut table_state);
+                self.table_state = table_state;
+            } else {
+                frame.render_widget(Paragraph::new("Loading…"), *table_area);
+            }
+        }
+
+        if let Some(input_area) = layout.get(2) {
+            let width = input_area.width.max(3) - 3;
+            let scroll = self.input.compute_visual_scroll(width as usize);
+            let input = Paragraph::new(vec![
+                Line::from(vec![
+                    Span::from("+ ").style(Style::default().fg(Color::Cyan)),
+                    Span::from(self.input.get_value()),
+                ]),
+                Line::from(Span::from(HELP).style(Style::default().fg(Color::DarkGray))),
+            ]);
+            frame.render_widget(input.scroll((0, scroll as u16)), *input_area);
+
+            let x = self.input.visual_cursor().max(scroll) - scroll + 2;
+            frame.set_cursor_position((input_area.x + x as u16, input_area.y))
+        }
+    }
+}
+
+impl EventHandler for ModeListWidget {
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    fn handle_actions(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        event: &MessageEvent,
+    ) -> Option<MessageEvent> {
+        match event {
+            MessageEvent::ModeListOpen(server_id, channel, modes) => {
+                *self = Self {
+                    server_id: Some(*server_id),
+                    channel: channel.clone(),
+                    lists: modes
+                        .iter()
+                        .map(|mode| ModeList {
+                            mode: *mode,
+                            entries: None,
+                        })
+                        .collect(),
+                    need_redraw: true,
+                    ..Default::default()
+                };
+            }
+            MessageEvent::ModeList(server_id, channel, mode, entries)
+                if self.is_for(*server_id, channel) =>
+            {
+                if let Some(list) = self.lists.iter_mut().find(|v| v.mode == *mode) {
+                    list.entries = Some(entries.clone());
+                    self.fix_selection();
+                }
+            }
+            //The lists follow the changes made here or by the others
+            MessageEvent::Mode(server_id, source, target, changes)
+                if self.is_for(*server_id, target) =>
+            {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|v| v.as_secs())
+                    .unwrap_or_default();
+                for change in changes {
+                    if let Some(entries) = self
+                        .lists
+                        .iter_mut()
+                        .find(|v| v.mode == change.mode)
+                        .and_then(|v| v.entries.as_mut())
+                    {
+                        mode_list::apply(entries, change, source.as_deref(), now);
+                    }
+                }
+                self.fix_selection();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_events(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        event: &crate::event_handler::Event,
+    ) -> Option<MessageEvent> {
+        match event {
+            crate::event_handler::Event::Crossterm(crossterm_event) => match crossterm_event {
+                crossterm::event::Event::Key(key) => self.handle_key(key),
+                crossterm::event::Event::Resize(_x, _y) => {
+                    self.need_redraw = true;
+                    None
+                }
+                crossterm::event::Event::Mouse(mouse_event) => match mouse_event.kind {
+                    crossterm::event::MouseEventKind::ScrollDown => {
+                        self.move_selection(true);
+                        None
+                    }
+                    crossterm::event::MouseEventKind::ScrollUp => {
+                        self.move_selection(false);
+                        None
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn need_redraw(&self) -> bool {
+        self.need_redraw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clown_core::mode::ModeChange;
+    use crossterm::event::KeyModifiers;
+
+    const TEST_SERVER_ID: ServerID = ServerID::new(0);
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn entry(mask: &str) -> ListEntry {
+        ListEntry {
+            mask: mask.to_string(),
+            setter: Some("alice!al@host".to_string()),
+            set_at: Some(1700000000),
+        }
+    }
+
+    fn opened_widget() -> ModeListWidget {
+        let mut widget = ModeListWidget {
+            server_id: Some(TEST_SERVER_ID),
+            channel: "#rust".to_string(),
+            lists: vec![
+                ModeList {
+                    mode: 'b',
+                    entries: Some(vec![entry("*!*@a"), entry("*!*@b"), entry("*!*@c")]),
+                },
+                ModeList {
+                    mode: 'e',
+                    entries: None,
+                },
+            ],
+            ..Default::default()
+        };
+        widget.fix_selection();
+        widget
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_date(None), "");
+        assert_eq!(format_date(Some(1700000000)).len(), DATE_FORMAT.len() + 2);
+        assert_eq!(setter_nick("alice!al@host"), "alice");
+        assert_eq!(setter_nick("irc.example.com"), "irc.example.com");
+    }
+
+    #[test]
+    fn test_remove_entries() {
+        let mut widget = opened_widget();
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Delete)),
+            Some(MessageEvent::ModeListChange(server_id, ref channel, ref changes))
+                if server_id == TEST_SERVER_ID && channel == "#rust" && changes.len() == 1
+                    && !changes[0].is_set && changes[0].parameter.as_deref() == Some("*!*@a")
+        ));
+
+        widget.handle_key(&key(KeyCode::Char(' ')));
+        widget.handle_key(&key(KeyCode::Down));
+        widget.handle_key(&key(KeyCode::Char(' ')));
+        assert_eq!(widget.marked, vec!["*!*@a", "*!*@c"]);
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Delete)),
+            Some(MessageEvent::ModeListChange(_, _, ref changes)) if changes.len() == 2
+        ));
+        assert!(widget.marked.is_empty());
+    }
+
+    #[test]
+    fn test_add_entry() {
+        let mut widget = opened_widget();
+        widget.handle_key(&key(KeyCode::Tab));
+        assert_eq!(widget.current_mode(), Some('e'));
+        for c in "*!*@friend".chars() {
+            widget.handle_key(&key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Enter)),
+            Some(MessageEvent::ModeListChange(_, _, ref changes))
+                if changes == &vec![ModeChange {
+                    is_set: true,
+                    mode: 'e',
+                    parameter: Some("*!*@friend".to_string()),
+                }]
+        ));
+        assert!(widget.input.get_value().is_empty());
+        assert!(widget.handle_key(&key(KeyCode::Enter)).is_none());
+        assert!(matches!(
+            widget.handle_key(&key(KeyCode::Esc)),
+            Some(MessageEvent::ModeListEnd())
+        ));
+    }
+}
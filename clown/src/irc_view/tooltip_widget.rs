@@ -43,20 +43,25 @@ impl Draw for MessagePreview {
                 Constraint::Length(10), // Title
             ])
             .split(inner_area);
-        let mut title = self.content.clone();
-
         if let Some(title_layout) = main_layout.first() {
             let width = title_layout.width as usize;
-            if title.chars().count() > width {
-                // Keep room for "..."
-                title = title
-                    .chars()
-                    .take(width.saturating_sub(3))
-                    .collect::<String>()
-                    + "...";
-            }
-            let span = ratatui::text::Span::raw(title);
-            frame.render_widget(span, *title_layout);
+            let lines = self
+                .content
+                .lines()
+                .map(|line| {
+                    if line.chars().count() > width {
+                        // Keep room for "..."
+                        line.chars()
+                            .take(width.saturating_sub(3))
+                            .collect::<String>()
+                            + "..."
+                    } else {
+                        line.to_string()
+                    }
+                })
+                .map(ratatui::text::Line::raw)
+                .collect::<Vec<_>>();
+            frame.render_widget(ratatui::widgets::Paragraph::new(lines), *title_layout);
 
             if self.need_redraw {
                 self.need_redraw = false;
@@ -170,6 +175,11 @@ impl crate::component::EventHandler for ToolTipDiscussWidget {
                 }
                 None
             }
+            MessageEvent::ToolTip(content) => {
+                self.set_message(Box::new(MessagePreview::from_string(content.clone())));
+                self.need_redraw = true;
+                None
+            }
 
             _ => None,
         }
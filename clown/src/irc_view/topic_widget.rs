@@ -2,6 +2,7 @@ use crate::component::{Draw, EventHandler};
 use crate::message_irc::message_parser::to_spans;
 use crate::state::server_id::ServerID;
 use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 
@@ -23,9 +24,46 @@ impl Draw for TopicWidget {
         let model = &ctx.session.model;
         if let Some(server_model) = model.get_current_server()
             && let Some(channel) = server_model.get_current_channel()
-            && let Some(topic) = self.get_topic(server_model.get_server_id(), channel)
         {
-            let line: Line<'_> = to_spans(topic, None).into_iter().map(Span::from).collect();
+            let mut line = Line::default();
+            if let Some(modes) = server_model.get_channel_modes(channel) {
+                line.push_span(Span::styled(
+                    format!("[{modes}] "),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(topic) = self.get_topic(server_model.get_server_id(), channel) {
+                line.extend(to_spans(topic, None).into_iter().map(Span::from));
+            }
+            //The server buffer shows when the next attempt to reconnect is made,
+            //or the endpoint in use
+            let is_server_buffer = channel.eq_ignore_ascii_case(server_model.get_server_name());
+            if is_server_buffer
+                && let Some(countdown) = ctx
+                    .session
+                    .get_retry_countdown(server_model.get_server_id())
+            {
+                let attempt = match countdown.max_retries {
+                    Some(max_retries) => format!("{}/{max_retries}", countdown.attempt),
+                    None => countdown.attempt.to_string(),
+                };
+                line.push_span(Span::styled(
+                    format!(
+                        "Reconnecting in {}s (attempt {attempt})",
+                        countdown.remaining.as_secs()
+                    ),
+                    Style::default().fg(Color::Yellow),
+                ));
+            } else if is_server_buffer
+                && let Some(connection) = ctx.session.get_connection(server_model.get_server_id())
+                && !connection.task.is_finished()
+            {
+                let endpoint = &connection.connection_config;
+                line.push_span(Span::styled(
+                    format!("Connected to {}:{}", endpoint.address, endpoint.port),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
             frame.render_widget(Paragraph::new(line), area);
         }
     }
@@ -85,14 +123,22 @@ impl EventHandler for TopicWidget {
                 self.need_redraw = true;
                 None
             }
+            MessageEvent::Mode(..) => {
+                self.need_redraw = true;
+                None
+            }
             _ => None,
         }
     }
     fn handle_events(
         &mut self,
-        _ctx: &mut crate::state::context::Ctx,
-        _event: &crate::event_handler::Event,
+        ctx: &mut crate::state::context::Ctx,
+        event: &crate::event_handler::Event,
     ) -> Option<MessageEvent> {
+        //The countdown before reconnecting
+        if matches!(event, crate::event_handler::Event::Tick) && ctx.session.has_retry_countdown() {
+            self.need_redraw = true;
+        }
         None
     }
 }
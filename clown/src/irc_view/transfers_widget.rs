@@ -0,0 +1,179 @@
+use crate::component::{Draw, EventHandler};
+use crate::message_event::MessageEvent;
+use crate::state::dcc::{Transfer, TransferDirection, TransferState};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+/// No more than 4 transfers are listed above the input
+pub const MAX_VISIBLE_TRANSFERS: usize = 4;
+const BAR_WIDTH: u64 = 10;
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        unit = next;
+        if value < 1024.0 || next == "GiB" {
+            break;
+        }
+        value /= 1024.0;
+    }
+    if unit == "B" {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {unit}")
+    }
+}
+
+fn progress_bar(percent: u64) -> String {
+    let filled = (percent.min(100) * BAR_WIDTH / 100) as usize;
+    let empty = BAR_WIDTH as usize - filled;
+    format!("{}{}", "█".repeat(filled), "░".repeat(empty))
+}
+
+fn transfer_line(transfer: &Transfer) -> Line<'static> {
+    let (arrow, preposition) = match transfer.direction {
+        TransferDirection::Send => ("↑", "to"),
+        TransferDirection::Receive => ("↓", "from"),
+    };
+    let color = match transfer.state {
+        TransferState::Offered => Color::Yellow,
+        TransferState::Done => Color::Green,
+        TransferState::Failed(_) | TransferState::Cancelled => Color::Red,
+        TransferState::Waiting | TransferState::Transferring => Color::Cyan,
+    };
+    let size = transfer
+        .size
+        .map(|v| format!("/{}", format_size(v)))
+        .unwrap_or_default();
+    let mut line = Line::default();
+    line.push_span(Span::styled(
+        format!("[{}] {arrow} ", transfer.id),
+        Style::default().fg(Color::DarkGray),
+    ));
+    line.push_span(Span::raw(format!(
+        "{} {preposition} {} ",
+        transfer.filename(),
+        transfer.nick
+    )));
+    if let Some(percent) = transfer.progress() {
+        line.push_span(Span::styled(
+            format!("{} {percent:>3}% ", progress_bar(percent)),
+            Style::default().fg(color),
+        ));
+    }
+    line.push_span(Span::raw(format!(
+        "{}{size} ",
+        format_size(transfer.transferred)
+    )));
+    let state = if transfer.state == TransferState::Offered {
+        format!("/dcc get {} to accept", transfer.id)
+    } else {
+        transfer.state.to_string()
+    };
+    line.push_span(Span::styled(state, Style::default().fg(color)));
+    line
+}
+
+/// DCC transfers with their progress
+pub struct TransfersWidget {
+    need_redraw: bool,
+    area: Rect,
+}
+
+impl TransfersWidget {
+    pub fn new() -> Self {
+        Self {
+            need_redraw: true,
+            area: Rect::default(),
+        }
+    }
+
+    /// Lines taken by the list in the layout
+    pub fn height(ctx: &crate::state::context::Ctx) -> u16 {
+        let count = ctx
+            .session
+            .dcc
+            .visible_transfers()
+            .take(MAX_VISIBLE_TRANSFERS)
+            .count();
+        u16::try_from(count).unwrap_or_default()
+    }
+}
+
+impl Draw for TransfersWidget {
+    fn render(
+        &mut self,
+        ctx: &mut crate::state::context::Ctx,
+        frame: &mut ratatui::Frame<'_>,
+        area: Rect,
+    ) {
+        self.need_redraw = false;
+        self.area = area;
+        if area.height == 0 {
+            return;
+        }
+        //The most recent transfers are kept
+        let transfers: Vec<_> = ctx.session.dcc.visible_transfers().collect();
+        let lines = transfers
+            .iter()
+            .skip(transfers.len().saturating_sub(MAX_VISIBLE_TRANSFERS))
+            .map(|v| transfer_line(v))
+            .collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+impl EventHandler for TransfersWidget {
+    fn need_redraw(&self) -> bool {
+        self.need_redraw
+    }
+
+    fn get_area(&self) -> Rect {
+        self.area
+    }
+
+    fn handle_actions(
+        &mut self,
+        _ctx: &mut crate::state::context::Ctx,
+        _event: &MessageEvent,
+    ) -> Option<MessageEvent> {
+        None
+    }
+
+    fn handle_events(
+        &mut self,
+        ctx: &mut crate::state::context::Ctx,
+        event: &crate::event_handler::Event,
+    ) -> Option<MessageEvent> {
+        //The finished transfers disappear after a while
+        if matches!(event, crate::event_handler::Event::Tick)
+            && (ctx.session.dcc.take_changed() || self.area.height > 0)
+        {
+            self.need_redraw = true;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MiB");
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0), "░░░░░░░░░░");
+        assert_eq!(progress_bar(55), "█████░░░░░");
+        assert_eq!(progress_bar(150), "██████████");
+    }
+}
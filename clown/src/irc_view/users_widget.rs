@@ -1,5 +1,6 @@
 use crate::component::Draw;
 use crate::state::server_id::ServerID;
+use clown_core::isupport::ISupport;
 
 use crossterm::event::KeyModifiers;
 use ratatui::{
@@ -69,6 +70,8 @@ pub struct UsersWidget {
     list_state: ListStateWidget,
     area: Rect,
     need_redraw: bool,
+    /// The ranks may have changed since the last render
+    need_sort: bool,
 }
 
 impl UsersWidget {
@@ -79,6 +82,7 @@ impl UsersWidget {
             list_sections: Vec::new(),
             list_state: ListStateWidget::new(),
             need_redraw: true,
+            need_sort: false,
         }
     }
 
@@ -121,6 +125,7 @@ impl UsersWidget {
 
     fn set_users<'a>(
         &mut self,
+        isupport: &ISupport,
         server_id: Option<ServerID>,
         section: &str,
         list_users: impl Iterator<Item = &'a str>,
@@ -128,7 +133,7 @@ impl UsersWidget {
         let section_index = self.add_section_index(server_id, section);
 
         for user in list_users {
-            self.add_user(section_index, user);
+            self.add_user(isupport, section_index, user);
         }
     }
 
@@ -138,8 +143,8 @@ impl UsersWidget {
         old: &str,
         new: &str,
     ) {
-        let old = Self::sanitize_name(old);
-        let new = Self::sanitize_name(new);
+        let old = Self::sanitize_name(irc_model.get_isupport(), old);
+        let new = Self::sanitize_name(irc_model.get_isupport(), new);
         let global_section_id = self
             .get_global_section(Some(irc_model.get_server_id()))
             .map(|v| v.section_info.id);
@@ -168,11 +173,17 @@ impl UsersWidget {
         }
     }
 
-    fn remove_user_section(&mut self, server_id: Option<ServerID>, section: &str, user: &str) {
+    fn remove_user_section(
+        &mut self,
+        isupport: &ISupport,
+        server_id: Option<ServerID>,
+        section: &str,
+        user: &str,
+    ) {
         if let Some(section_index) = self.get_section_index(server_id, section)
             && let Some(section) = self.list_sections.get_mut(section_index)
         {
-            let user = Self::sanitize_name(user);
+            let user = Self::sanitize_name(isupport, user);
             section.remove_user(user);
         }
     }
@@ -219,21 +230,28 @@ impl UsersWidget {
         }
     }
 
-    fn sanitize_name(user: &str) -> &str {
-        user.strip_prefix('@').unwrap_or(user)
+    fn sanitize_name<'a>(isupport: &ISupport, user: &'a str) -> &'a str {
+        let user = isupport.strip_prefix(user);
+        user.split_once('!').map(|(nick, _)| nick).unwrap_or(user)
     }
 
-    fn add_user_with_section(&mut self, server_id: Option<ServerID>, section: &str, user: &str) {
+    fn add_user_with_section(
+        &mut self,
+        isupport: &ISupport,
+        server_id: Option<ServerID>,
+        section: &str,
+        user: &str,
+    ) {
         if let Some(section_index) = self.get_section_index(server_id, section) {
-            self.add_user(section_index, user);
+            self.add_user(isupport, section_index, user);
         } else {
             let section_index = self.add_section_index(server_id, section);
-            self.add_user(section_index, user);
+            self.add_user(isupport, section_index, user);
         }
     }
 
-    fn add_user(&mut self, section_index: usize, user: &str) {
-        let user = UsersWidget::sanitize_name(user);
+    fn add_user(&mut self, isupport: &ISupport, section_index: usize, user: &str) {
+        let user = UsersWidget::sanitize_name(isupport, user);
         if let Some(section) = self.list_sections.get_mut(section_index) {
             section.set_user_position(user);
         }
@@ -252,13 +270,18 @@ impl UsersWidget {
             .position(|v| v.section_info.server_id == server_id)
     }
 
-    fn add_user_global_section(&mut self, server_id: Option<ServerID>, user: &str) {
-        if user.starts_with("#") {
+    fn add_user_global_section(
+        &mut self,
+        isupport: &ISupport,
+        server_id: Option<ServerID>,
+        user: &str,
+    ) {
+        if isupport.is_channel(user) {
             return;
         }
 
         if let Some(global_section_index) = self.get_global_section_index(server_id) {
-            self.add_user(global_section_index, user);
+            self.add_user(isupport, global_section_index, user);
         }
     }
 
@@ -276,14 +299,41 @@ impl UsersWidget {
         }
     }
 
-    fn close_buffer(&mut self, server_id: Option<ServerID>, name: &str) {
-        if name.starts_with("#") {
+    fn close_buffer(&mut self, isupport: &ISupport, server_id: Option<ServerID>, name: &str) {
+        if isupport.is_channel(name) {
             self.close_channel_buffer(server_id, name);
         } else {
             self.close_user_buffer(server_id, name);
         }
     }
 
+    //Users with the highest rank are displayed first, keep the previous order otherwise
+    fn sort_users(&mut self, irc_model: &crate::state::irc_model::IrcModel) {
+        for section in self.list_sections.iter_mut() {
+            if let Some(server_id) = section.section_info.server_id
+                && let Some(irc_server) = irc_model.get_server(server_id)
+            {
+                let channel = &section.section_info.name;
+                section
+                    .order_user
+                    .sort_by_cached_key(|user| irc_server.get_user_rank(user, channel));
+            }
+        }
+    }
+
+    /// Section and item of a row of the list, the users of a section follow its name
+    fn item_at(&self, row: usize) -> Option<(usize, usize)> {
+        let mut start = 0;
+        for section in 0..self.nb_sections() {
+            let nb_items = self.nb_items(section);
+            if row < start + nb_items {
+                return Some((section, row - start));
+            }
+            start += nb_items;
+        }
+        None
+    }
+
     fn update_selected(
         &mut self,
         previous_selected: (usize, usize),
@@ -323,6 +373,9 @@ impl Draw for UsersWidget {
             self.need_redraw = false;
         }
         self.area = area;
+        if std::mem::take(&mut self.need_sort) {
+            self.sort_users(&ctx.session.model);
+        }
         self.list_state.render(
             &ctx.model,
             &ctx.session.model,
@@ -384,6 +437,7 @@ impl ListStateWidget {
         &'a self,
         depth: usize,
         color: ratatui::style::Color,
+        prefix: Option<char>,
         title: &'a str,
         is_highlighted: bool,
         is_selected: bool,
@@ -401,6 +455,9 @@ impl ListStateWidget {
             style = style.bg(Color::LightBlue);
         }
         spans.push(Span::raw(*INDENT.get(depth).unwrap_or(&" ")));
+        if let Some(prefix) = prefix {
+            spans.push(Span::styled(prefix.to_string(), style));
+        }
         spans.push(Span::styled(title, style));
         spans
     }
@@ -421,6 +478,7 @@ impl ListStateWidget {
             let item = ListItem::from(Line::from(self.add_item(
                 0,
                 model.get_color(&section.section_info.name),
+                None,
                 &section.section_info.name,
                 section.section_info.highlight,
                 (self.current_section == section_i) && (self.current_selected == 0),
@@ -430,9 +488,16 @@ impl ListStateWidget {
                 && let Some(irc_server) = irc_model.get_server(server_id)
             {
                 for (i, user_name) in section.order_user.iter().enumerate() {
+                    //Away users are dimmed
+                    let color = if irc_server.get_user(user_name).is_some_and(|v| v.is_away()) {
+                        Color::DarkGray
+                    } else {
+                        model.get_color(user_name)
+                    };
                     let spans = self.add_item(
                         1,
-                        model.get_color(user_name),
+                        color,
+                        irc_server.get_user_prefix(user_name, &section.section_info.name),
                         user_name,
                         irc_server.has_unread_message(user_name),
                         (self.current_section == section_i) && (self.current_selected == (i + 1)),
@@ -443,12 +508,45 @@ impl ListStateWidget {
                 }
             }
         }
+
+        //The notify list cannot be selected, it is displayed after the sections
+        for irc_server in irc_model.servers.iter().flatten() {
+            let notify = irc_server.get_notify_list();
+            if notify.is_empty() {
+                continue;
+            }
+            items.push(ListItem::from(Line::from(Span::styled(
+                format!("Notify {}", irc_server.get_server_name()),
+                Style::default().fg(Color::Gray),
+            ))));
+            for (nick, is_online) in notify {
+                let color = if *is_online {
+                    model.get_color(nick)
+                } else {
+                    Color::DarkGray
+                };
+                let spans = self.add_item(1, color, None, nick, false, false);
+                items.push(ListItem::from(Line::from(spans)));
+            }
+        }
         let list = List::new(items);
         frame.render_widget(list, area);
     }
 }
 
 use crate::message_event::MessageEvent;
+
+/// ISUPPORT of the server, the default one until it is known
+fn isupport(
+    ctx: &crate::state::context::Ctx,
+    server_id: Option<ServerID>,
+) -> std::borrow::Cow<'_, ISupport> {
+    server_id
+        .and_then(|id| ctx.session.model.get_server(id))
+        .map(|v| std::borrow::Cow::Borrowed(v.get_isupport()))
+        .unwrap_or_default()
+}
+
 impl crate::component::EventHandler for UsersWidget {
     fn get_area(&self) -> Rect {
         self.area
@@ -464,11 +562,13 @@ impl crate::component::EventHandler for UsersWidget {
         match event {
             MessageEvent::UpdateUsers(server_id, channel, list_users) => {
                 self.set_users(
+                    &isupport(ctx, Some(*server_id)),
                     Some(*server_id),
                     channel,
                     list_users.iter().map(|v| v.as_ref()),
                 );
                 self.need_redraw = true;
+                self.need_sort = true;
 
                 None
             }
@@ -486,18 +586,30 @@ impl crate::component::EventHandler for UsersWidget {
                 }
                 None
             }
-            MessageEvent::PrivMsg(server_id, source, target, _)
-            | MessageEvent::ActionMsg(server_id, source, target, _) => {
+            MessageEvent::PrivMsg(server_id, source, target, _, _, _)
+            | MessageEvent::ActionMsg(server_id, source, target, _, _) => {
                 if let Some(irc_server) = ctx.session.model.get_server(*server_id) {
                     let target = irc_server.get_target(source, target);
 
-                    self.add_user_global_section(Some(*server_id), target);
+                    self.add_user_global_section(
+                        irc_server.get_isupport(),
+                        Some(*server_id),
+                        target,
+                    );
                     self.highlight_user(Some(*server_id), target);
                     self.need_redraw = true;
                 }
                 None
             }
             MessageEvent::AddMessageViewInfo(server_id, channel, _, _) => {
+                //The buffer of a DCC chat is opened by its first message
+                if let Some(buffer) = channel
+                    .as_deref()
+                    .filter(|v| crate::state::dcc::chat_nick(v).is_some())
+                {
+                    self.add_user_global_section(&isupport(ctx, *server_id), *server_id, buffer);
+                    self.need_redraw = true;
+                }
                 if let Some(server_id) = server_id
                     && let Some(server_name) = ctx
                         .session
@@ -509,12 +621,18 @@ impl crate::component::EventHandler for UsersWidget {
 
                 None
             }
-            MessageEvent::Part(server_id, channel, user) => {
+            MessageEvent::Part(server_id, channel, user)
+            | MessageEvent::Kick(server_id, channel, user, _, _) => {
                 if let Some(irc_server) = ctx.session.model.get_server(*server_id) {
                     if irc_server.is_main_user(user) {
                         self.remove_all_users_section(Some(*server_id), channel);
                     } else {
-                        self.remove_user_section(Some(*server_id), channel, user);
+                        self.remove_user_section(
+                            irc_server.get_isupport(),
+                            Some(*server_id),
+                            channel,
+                            user,
+                        );
                     }
                     self.need_redraw = true;
                 }
@@ -531,9 +649,15 @@ impl crate::component::EventHandler for UsersWidget {
             }
             MessageEvent::Join(server_id, channel, user) => {
                 let section_index = self.add_section_index(Some(*server_id), channel);
-                self.add_user_with_section(Some(*server_id), channel, user);
+                self.add_user_with_section(
+                    &isupport(ctx, Some(*server_id)),
+                    Some(*server_id),
+                    channel,
+                    user,
+                );
                 self.list_state.current_section = section_index;
                 self.need_redraw = true;
+                self.need_sort = true;
 
                 None
             }
@@ -541,11 +665,31 @@ impl crate::component::EventHandler for UsersWidget {
                 if let Some(index) = self.get_section_index(*server_id, channel) {
                     self.list_state.current_section = index;
                 }
+                self.need_redraw = true;
+                //The WHOIS of the selected user is shown in the tooltip
+                server_id
+                    .filter(|id| {
+                        ctx.session.model.get_server(*id).is_some_and(|irc_server| {
+                            !irc_server.is_channel(channel)
+                                && irc_server.get_user(channel).is_some()
+                        })
+                    })
+                    .map(|id| MessageEvent::WhoisToolTip(id, channel.clone()))
+            }
+            //The ranks of the users are updated by the model after the widgets
+            MessageEvent::Mode(_, _, _, _) => {
+                self.need_redraw = true;
+                self.need_sort = true;
+                None
+            }
+            MessageEvent::UserInfo(_, _, _)
+            | MessageEvent::Notify(_, _, _)
+            | MessageEvent::SettingsDidChange => {
                 self.need_redraw = true;
                 None
             }
             MessageEvent::CloseBuffer(server_id, name) => {
-                self.close_buffer(*server_id, name);
+                self.close_buffer(&isupport(ctx, *server_id), *server_id, name);
                 self.need_redraw = true;
                 None
             }
@@ -593,6 +737,19 @@ impl crate::component::EventHandler for UsersWidget {
                 .update_selected(previous_selected)
                 .map(|(a, b)| MessageEvent::SelectChannel(a, b));
         }
+        if let crate::event_handler::Event::Crossterm(crossterm::event::Event::Mouse(mouse)) = event
+            && mouse.kind
+                == crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left)
+            && let Some((section, item)) =
+                self.item_at(usize::from(mouse.row.saturating_sub(self.area.y)))
+        {
+            let previous_selected = self.list_state.selected();
+            self.list_state.current_section = section;
+            self.list_state.current_selected = item;
+            return self
+                .update_selected(previous_selected)
+                .map(|(a, b)| MessageEvent::SelectChannel(a, b));
+        }
         None
     }
 }
@@ -735,6 +892,8 @@ mod tests {
             "a".to_string(),
             user_name.to_string(),
             "Message".to_string(),
+            None,
+            None,
         );
         widget_test.handle_action(&action);
 
@@ -782,6 +941,8 @@ mod tests {
             "a".to_string(),
             user_name.to_string(),
             "Message".to_string(),
+            None,
+            None,
         );
         widget_test.handle_action(&action);
 
@@ -855,6 +1016,134 @@ mod tests {
         assert_eq!(users_widget.list_sections[0].section_info.name, "#rust");
     }
 
+    #[test]
+    fn test_select_user_shows_info() {
+        let irc_model = crate::state::irc_model::IrcModel::new_single_server(
+            1,
+            TEST_SERVER_ID,
+            "IRC-Server".into(),
+            "farine".to_string(),
+        );
+        let mut widget_test = WidgetTest {
+            ctx: ctx_with_model(irc_model),
+            users_widget: UsersWidget::new(),
+        };
+        widget_test.join_server();
+        widget_test.join_channel_users("#rust", vec!["@alice!al@host.com"]);
+        assert_eq!(
+            widget_test.users_widget.list_sections[1].order_user,
+            vec!["alice".to_string()]
+        );
+        widget_test.handle_action(&MessageEvent::UserInfo(
+            TEST_SERVER_ID,
+            "alice".to_string(),
+            crate::state::irc_model::UserInfo::Away(Some("lunch".to_string())),
+        ));
+
+        let event = widget_test.users_widget.handle_actions(
+            &mut widget_test.ctx,
+            &MessageEvent::SelectChannel(Some(TEST_SERVER_ID), "alice".to_string()),
+        );
+        assert_eq!(
+            event,
+            Some(MessageEvent::WhoisToolTip(
+                TEST_SERVER_ID,
+                "alice".to_string()
+            ))
+        );
+        //Shown until the WHOIS is received
+        assert_eq!(
+            widget_test
+                .ctx
+                .session
+                .model
+                .get_server(TEST_SERVER_ID)
+                .and_then(|v| v.get_user("alice"))
+                .map(|v| v.describe()),
+            Some("alice\nal@host.com\nAway: lunch".to_string())
+        );
+
+        //Clicking on the user selects it
+        let click = |row| {
+            crate::event_handler::Event::Crossterm(crossterm::event::Event::Mouse(
+                crossterm::event::MouseEvent {
+                    kind: crossterm::event::MouseEventKind::Down(
+                        crossterm::event::MouseButton::Left,
+                    ),
+                    column: 1,
+                    row,
+                    modifiers: KeyModifiers::NONE,
+                },
+            ))
+        };
+        assert_eq!(widget_test.users_widget.item_at(2), Some((1, 1)));
+        assert_eq!(widget_test.users_widget.item_at(3), None);
+        assert_eq!(
+            widget_test
+                .users_widget
+                .handle_events(&mut widget_test.ctx, &click(2)),
+            Some(MessageEvent::SelectChannel(
+                Some(TEST_SERVER_ID),
+                "alice".to_string()
+            ))
+        );
+        assert_eq!(
+            widget_test
+                .users_widget
+                .handle_events(&mut widget_test.ctx, &click(2)),
+            None
+        );
+
+        let event = widget_test.users_widget.handle_actions(
+            &mut widget_test.ctx,
+            &MessageEvent::SelectChannel(Some(TEST_SERVER_ID), "#rust".to_string()),
+        );
+        assert_eq!(event, None);
+    }
+
+    #[test]
+    fn test_sort_users_by_rank() {
+        let irc_model = crate::state::irc_model::IrcModel::new_single_server(
+            1,
+            TEST_SERVER_ID,
+            "IRC-Server".into(),
+            "farine".to_string(),
+        );
+        let mut widget_test = WidgetTest {
+            ctx: ctx_with_model(irc_model),
+            users_widget: UsersWidget::new(),
+        };
+        widget_test.join_server();
+        widget_test.join_channel_users("#rust", vec!["alice", "+bob", "carl", "@dan", "%eve"]);
+        // % is not a prefix of the default PREFIX=(ov)@+
+        assert_eq!(
+            widget_test.users_widget.list_sections[1].order_user,
+            vec!["alice", "bob", "carl", "dan", "%eve"]
+        );
+
+        widget_test
+            .users_widget
+            .sort_users(&widget_test.ctx.session.model);
+        assert_eq!(
+            widget_test.users_widget.list_sections[1].order_user,
+            vec!["dan", "bob", "alice", "carl", "%eve"]
+        );
+
+        widget_test.handle_action(&MessageEvent::ISupport(
+            TEST_SERVER_ID,
+            vec!["PREFIX=(qaohv)~&@%+".to_string()],
+        ));
+        widget_test.join_channel_users("#linux", vec!["alice", "+bob", "~carl", "%eve"]);
+        assert!(widget_test.users_widget.need_sort);
+        widget_test
+            .users_widget
+            .sort_users(&widget_test.ctx.session.model);
+        assert_eq!(
+            widget_test.users_widget.list_sections[2].order_user,
+            vec!["carl", "eve", "bob", "alice"]
+        );
+    }
+
     #[test]
     fn test_close_buffer_logic() {
         let mut widget = UsersWidget::new();
@@ -879,5 +1168,17 @@ mod tests {
             0,
             "Section should be removed after closing channel buffer"
         );
+
+        // 3. A channel of CHANTYPES other than #
+        widget.handle_actions(
+            &mut ctx,
+            &MessageEvent::Join(TEST_SERVER_ID, "&local".to_string(), "alice".to_string()),
+        );
+        assert_eq!(widget.nb_sections(), 1);
+        widget.handle_actions(
+            &mut ctx,
+            &MessageEvent::CloseBuffer(Some(TEST_SERVER_ID), "&local".to_string()),
+        );
+        assert_eq!(widget.nb_sections(), 0);
     }
 }
@@ -100,9 +100,19 @@ async fn main() -> anyhow::Result<()> {
     let log_dir = crate::project_path::ProjectPath::log_dir()
         .or_else(|| std::env::current_dir().ok())
         .ok_or_else(|| std::io::Error::other("could not determine log directory"))?;
+    let mut session = Session::new(model.get_server_count());
+    session.set_build_version(format!(
+        "clown {} ({}, {})",
+        build::PKG_VERSION,
+        build::SHORT_COMMIT,
+        build::BUILD_OS
+    ));
+    if let Some(sts_path) = ProjectPath::sts_path() {
+        session.load_sts_policies(sts_path);
+    }
     let mut ctx = crate::state::context::Ctx {
         messages: ServersMessages::new(log_dir),
-        session: Session::new(model.get_server_count()),
+        session,
         model,
     };
     let mut current_view = Views::Main(main_view::MainView::new());
@@ -10,10 +10,12 @@ pub enum MessageEvent {
         String,
     ),
     PrivMsg(
-        ServerID, /*server id */
-        String,   /*source */
-        String,   /*target*/
-        String,   /*content*/
+        ServerID,       /*server id */
+        String,         /*source */
+        String,         /*target*/
+        String,         /*content*/
+        Option<String>, /*msgid*/
+        Option<String>, /*msgid of the message replied to*/
     ),
     Notice(
         ServerID, /*server id */
@@ -22,11 +24,30 @@ pub enum MessageEvent {
         String,   /*content*/
     ),
     ActionMsg(
+        ServerID,       /*server id */
+        String,         /*source */
+        String,         /*target*/
+        String,         /*content*/
+        Option<String>, /*msgid*/
+    ),
+    PendingMsg(
+        ServerID,       /*server id */
+        String,         /*source */
+        String,         /*target*/
+        String,         /*content*/
+        Option<String>, /*msgid of the message replied to*/
+    ),
+    PendingAction(
         ServerID, /*server id */
         String,   /*source */
         String,   /*target*/
         String,   /*content*/
     ),
+    History(
+        ServerID,                                   /*server id */
+        String,                                     /*target*/
+        Vec<crate::state::history::HistoryMessage>, /*messages */
+    ),
     SelectChannel(Option<ServerID> /*server id */, String),
     UpdateUsers(
         ServerID,    /*server id */
@@ -44,6 +65,38 @@ pub enum MessageEvent {
         String,   /*user */
     ),
     JoinServer(ServerID /*server id */),
+    ISupport(ServerID /*server id */, Vec<String> /*features */),
+    Mode(
+        ServerID,                          /*server id */
+        Option<String>,                    /*source */
+        String,                            /*target */
+        Vec<clown_core::mode::ModeChange>, /*changes */
+    ),
+    UserInfo(
+        ServerID,                          /*server id */
+        String,                            /*user */
+        crate::state::irc_model::UserInfo, /*info */
+    ),
+    Notify(
+        ServerID, /*server id */
+        String,   /*user */
+        bool,     /*is online */
+    ),
+    Typing(
+        ServerID,                          /*server id */
+        String,                            /*source */
+        String,                            /*target */
+        crate::state::typing::TypingState, /*state */
+    ),
+    SendTyping(crate::state::typing::TypingState),
+    SelectReply(Option<(String /*msgid*/, String /*quote*/)>),
+    Reaction(
+        ServerID, /*server id */
+        String,   /*source */
+        String,   /*target */
+        String,   /*msgid of the message*/
+        String,   /*reaction */
+    ),
     SetTopic(
         ServerID,       /*server id */
         Option<String>, /*source*/
@@ -51,10 +104,15 @@ pub enum MessageEvent {
         String,         /*topic */
     ),
     HoverURL(String /* URL */),
-    #[allow(dead_code)]
-    Hover(String), //currently not used, but the skeleton can be used anywhere
+    Hover(String),
+    /// Replaces the content of the tooltip, even if it is opened
+    ToolTip(String),
+    /// Ask the WHOIS of a user, the reply is shown in the tooltip
+    WhoisToolTip(ServerID /*server id */, String /*nick */),
     PullIRC,
     Connect(ServerID),
+    /// Entry of the perform list to run on the server, dropped if the connection has changed
+    Perform(ServerID, usize /*connection generation */, String),
     DisConnect(ServerID),
     OpenWeb(String),
     SpellChecker(Option<String>),
@@ -68,12 +126,46 @@ pub enum MessageEvent {
         String,         /*user*/
         Option<String>, /*reason*/
     ),
+    Kick(
+        ServerID,       /*server id */
+        String,         /*channel */
+        String,         /*kicked user */
+        String,         /*source */
+        Option<String>, /*reason*/
+    ),
     QuitAll(Option<String> /*reason*/),
     SettingsDidChange,
     Bel,
     CloseBuffer(Option<ServerID> /*server id */, String /*name */),
     Search(super::irc_view::search_widget::Query),
     SearchEnd(),
+    /// The popup of the lists of a channel, with the modes requested
+    ModeListOpen(
+        ServerID,  /*server id */
+        String,    /*channel */
+        Vec<char>, /*modes */
+    ),
+    ModeList(
+        ServerID,                             /*server id */
+        String,                               /*channel */
+        char,                                 /*mode */
+        Vec<clown_core::response::ListEntry>, /*entries */
+    ),
+    ModeListChange(
+        ServerID,                          /*server id */
+        String,                            /*channel */
+        Vec<clown_core::mode::ModeChange>, /*changes */
+    ),
+    ModeListEnd(),
+    /// The channels of the server, with the filter given to /list
+    ChannelListOpen(ServerID /*server id */, String /*filter */),
+    ChannelList(
+        ServerID,                                          /*server id */
+        Vec<crate::state::channel_list::ChannelListEntry>, /*entries */
+        bool,                                              /*is complete */
+    ),
+    ChannelListJoin(ServerID /*server id */, String /*channel */),
+    ChannelListEnd(),
 }
 
 impl MessageEvent {
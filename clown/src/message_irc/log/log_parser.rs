@@ -93,6 +93,25 @@ fn parse_topic(input: &[u8]) -> IResult<&[u8], LoggedMessage<'static>> {
     ))
 }
 
+fn parse_mode(input: &[u8]) -> IResult<&[u8], LoggedMessage<'static>> {
+    let (input, source) =
+        map_res(take_till(|c: u8| c.is_ascii_whitespace()), buf_to_str).parse(input)?;
+    let (input, _) = tag(" has set mode for ").parse(input)?;
+    let (input, target) =
+        map_res(take_till(|c: u8| c.is_ascii_whitespace()), buf_to_str).parse(input)?;
+    let (input, _) = tag(" to ").parse(input)?;
+    let (input, modes) = map_res(nom::combinator::rest, buf_to_str).parse(input)?;
+
+    Ok((
+        input,
+        LoggedMessage::Mode {
+            source: std::borrow::Cow::Owned(source.to_string()),
+            target: std::borrow::Cow::Owned(target.to_string()),
+            modes: std::borrow::Cow::Owned(modes.to_string()),
+        },
+    ))
+}
+
 fn parse_join<'a>(input: &'a [u8], source: &str) -> IResult<&'a [u8], LoggedMessage<'static>> {
     let (input, _) = tag(" has joined ").parse(input)?;
 
@@ -159,6 +178,34 @@ fn parse_quit<'a>(input: &'a [u8], source: &str) -> IResult<&'a [u8], LoggedMess
     ))
 }
 
+fn parse_kick<'a>(input: &'a [u8], target: &str) -> IResult<&'a [u8], LoggedMessage<'static>> {
+    let (input, _) = tag(" was kicked from ").parse(input)?;
+    let (input, channel) =
+        map_res(take_till(|c: u8| c.is_ascii_whitespace()), buf_to_str).parse(input)?;
+    let (input, _) = tag(" by ").parse(input)?;
+    //The reason is optional, the source can be at the end of the line
+    let (input, source) = map_res(
+        nom::bytes::complete::take_till(|c: u8| c.is_ascii_whitespace()),
+        buf_to_str,
+    )
+    .parse(input)?;
+    let (input, reason) = map_res(nom::combinator::rest, buf_to_str).parse(input)?;
+    let reason = reason
+        .strip_prefix(" (")
+        .and_then(|v| v.strip_suffix(')'))
+        .map(|v| std::borrow::Cow::Owned(v.to_string()));
+
+    Ok((
+        input,
+        LoggedMessage::Kick {
+            source: std::borrow::Cow::Owned(source.to_string()),
+            target: std::borrow::Cow::Owned(target.to_string()),
+            channel: std::borrow::Cow::Owned(channel.to_string()),
+            reason,
+        },
+    ))
+}
+
 fn parse_event(input: &[u8]) -> IResult<&[u8], LoggedMessage<'static>> {
     alt((
         preceded(tag("\t<--\t "), parse_outgoing_subevents),
@@ -177,6 +224,7 @@ fn parse_outgoing_subevents(input: &[u8]) -> IResult<&[u8], LoggedMessage<'stati
         |i| parse_nick(i, source),
         |i| parse_part(i, source),
         |i| parse_quit(i, source),
+        |i| parse_kick(i, source),
     ))
     .parse(input)
 }
@@ -189,7 +237,7 @@ fn parse_ingoing_subevents(input: &[u8]) -> IResult<&[u8], LoggedMessage<'static
 }
 
 fn parse_network_subevents(input: &[u8]) -> IResult<&[u8], LoggedMessage<'static>> {
-    alt((|i| parse_topic(i),)).parse(input)
+    alt((|i| parse_topic(i), |i| parse_mode(i))).parse(input)
 }
 
 pub fn parse(input: &[u8]) -> anyhow::Result<LoggedTimedMessage<'static>> {
@@ -315,6 +363,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_kick() {
+        let raw = b"2024-05-20 15:00:00\t<--\t Bob was kicked from #rust by Alice";
+        let result = parse(raw).expect("Should parse kick");
+
+        if let LoggedMessage::Kick {
+            source,
+            target,
+            channel,
+            reason,
+        } = result.message
+        {
+            assert_eq!(source, "Alice");
+            assert_eq!(target, "Bob");
+            assert_eq!(channel, "#rust");
+            assert_eq!(reason, None);
+        } else {
+            panic!("Expected Kick message");
+        }
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let raw = b"2024-05-20 16:00:00\t--\t Dave has set mode for #help to +o-v alice bob";
+        let result = parse(raw).expect("Should parse mode");
+
+        if let LoggedMessage::Mode {
+            source,
+            target,
+            modes,
+        } = result.message
+        {
+            assert_eq!(source, "Dave");
+            assert_eq!(target, "#help");
+            assert_eq!(modes, "+o-v alice bob");
+        } else {
+            panic!("Expected Mode message");
+        }
+    }
+
     #[test]
     fn test_invalid_date() {
         let raw = b"not-a-date\t-->\t Alice has joined #rust-lang";
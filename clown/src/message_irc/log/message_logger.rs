@@ -1,5 +1,6 @@
 use super::log_parser;
 use crate::message_event::MessageEvent;
+use crate::state::history::HistoryKind;
 use ahash::AHashMap;
 use std::borrow::Cow;
 use std::collections::VecDeque;
@@ -31,10 +32,21 @@ pub enum LoggedMessage<'a> {
     Quit {
         source: Cow<'a, str>,
     },
+    Kick {
+        source: Cow<'a, str>,
+        target: Cow<'a, str>,
+        channel: Cow<'a, str>,
+        reason: Option<Cow<'a, str>>,
+    },
     NickChange {
         old: Cow<'a, str>,
         new: Cow<'a, str>,
     },
+    Mode {
+        source: Cow<'a, str>,
+        target: Cow<'a, str>,
+        modes: Cow<'a, str>,
+    },
     Message {
         source: Cow<'a, str>,
         content: Cow<'a, str>,
@@ -135,43 +147,99 @@ impl LogWriter {
         self.last_data_written.elapsed() > std::time::Duration::from_mins(LOG_OPENED_TIMER_MINUTES)
     }
 
+    #[cfg(test)]
     fn write(&mut self, data: impl std::fmt::Display) -> std::io::Result<()> {
-        writeln!(self.buffer, "{}\t{}", Self::get_current_time(), data)?;
+        self.write_with_time(std::time::SystemTime::now(), data)
+    }
+
+    fn write_with_time(
+        &mut self,
+        time: std::time::SystemTime,
+        data: impl std::fmt::Display,
+    ) -> std::io::Result<()> {
+        writeln!(self.buffer, "{}\t{}", Self::format_time(time), data)?;
 
         Ok(())
     }
 
-    fn get_current_time() -> impl std::fmt::Display {
-        let now = chrono::Utc::now();
-        now.format("%Y-%m-%d %H:%M:%S")
+    fn format_time(time: std::time::SystemTime) -> impl std::fmt::Display {
+        let time: chrono::DateTime<chrono::Utc> = time.into();
+        time.format("%Y-%m-%d %H:%M:%S")
     }
 
     fn write_message(&mut self, message: LoggedMessage<'_>) -> std::io::Result<()> {
-        match message {
+        self.write_timed_message(LoggedTimedMessage {
+            time: std::time::SystemTime::now(),
+            message,
+        })
+    }
+
+    //The time is kept when the message comes from the history of the server
+    fn write_timed_message(
+        &mut self,
+        timed_message: LoggedTimedMessage<'_>,
+    ) -> std::io::Result<()> {
+        let time = timed_message.time;
+        match timed_message.message {
             LoggedMessage::Topic {
                 source,
                 channel,
                 content,
-            } => self.write(format_args!(
-                "--\t {} has changed topic for {} to \"{}\"",
-                source, channel, content
-            )),
-            LoggedMessage::Join { source, channel } => {
-                self.write(format_args!("-->\t {} has joined {}", source, channel))
-            }
+            } => self.write_with_time(
+                time,
+                format_args!(
+                    "--\t {} has changed topic for {} to \"{}\"",
+                    source, channel, content
+                ),
+            ),
+            LoggedMessage::Join { source, channel } => self.write_with_time(
+                time,
+                format_args!("-->\t {} has joined {}", source, channel),
+            ),
             LoggedMessage::Part { source, channel } => {
-                self.write(format_args!("<--\t {} has left {}", source, channel))
+                self.write_with_time(time, format_args!("<--\t {} has left {}", source, channel))
             }
-            LoggedMessage::Quit { source } => self.write(format_args!("<--\t {} has quit", source)),
-            LoggedMessage::NickChange { old, new } => self.write(format_args!(
-                "<--\t {} has changed their nickname to {}",
-                old, new
-            )),
+            LoggedMessage::Quit { source } => {
+                self.write_with_time(time, format_args!("<--\t {} has quit", source))
+            }
+            LoggedMessage::Kick {
+                source,
+                target,
+                channel,
+                reason: Some(reason),
+            } => self.write_with_time(
+                time,
+                format_args!(
+                    "<--\t {} was kicked from {} by {} ({})",
+                    target, channel, source, reason
+                ),
+            ),
+            LoggedMessage::Kick {
+                source,
+                target,
+                channel,
+                reason: None,
+            } => self.write_with_time(
+                time,
+                format_args!("<--\t {} was kicked from {} by {}", target, channel, source),
+            ),
+            LoggedMessage::NickChange { old, new } => self.write_with_time(
+                time,
+                format_args!("<--\t {} has changed their nickname to {}", old, new),
+            ),
+            LoggedMessage::Mode {
+                source,
+                target,
+                modes,
+            } => self.write_with_time(
+                time,
+                format_args!("--\t {} has set mode for {} to {}", source, target, modes),
+            ),
             LoggedMessage::Message { source, content } => {
-                self.write(format_args!("{} {}", source, content))
+                self.write_with_time(time, format_args!("{} {}", source, content))
             }
             LoggedMessage::Action { source, content } => {
-                self.write(format_args!("* {} {}", source, content))
+                self.write_with_time(time, format_args!("* {} {}", source, content))
             }
         }
     }
@@ -592,6 +660,19 @@ impl MessageLogger {
         Ok(())
     }
 
+    fn write_timed_to_target(
+        &mut self,
+        server_address: &str,
+        target: Option<&str>,
+        data: LoggedTimedMessage<'_>,
+    ) -> anyhow::Result<()> {
+        let logger = self.init_buffer(server_address, target)?;
+        logger.write_timed_message(data)?;
+        logger.flush(false)?;
+
+        Ok(())
+    }
+
     pub fn write_message(
         &mut self,
         server_address: &str,
@@ -639,6 +720,20 @@ impl MessageLogger {
                 )?;
             }
 
+            MessageEvent::Kick(_, channel, user, source, reason) => {
+                self.write_to_target(
+                    server_address,
+                    Some(channel),
+                    LoggedMessage::Kick {
+                        source: Cow::Borrowed(source),
+                        target: Cow::Borrowed(user),
+                        channel: Cow::Borrowed(channel),
+                        reason: reason.as_deref().map(Cow::Borrowed),
+                    },
+                    true,
+                )?;
+            }
+
             MessageEvent::Quit(server_id, user, _) => {
                 if let Some(irc_model) = irc_model.as_ref() {
                     for channel in irc_model.get_all_joined_channel(*server_id, user) {
@@ -654,6 +749,23 @@ impl MessageLogger {
                 }
             }
 
+            //Only the changes of the channel modes are logged
+            MessageEvent::Mode(server_id, Some(source), target, changes)
+                if irc_model.is_some_and(|v| v.is_channel(*server_id, target))
+                    && !changes.is_empty() =>
+            {
+                self.write_to_target(
+                    server_address,
+                    Some(target),
+                    LoggedMessage::Mode {
+                        source: Cow::Borrowed(source),
+                        target: Cow::Borrowed(target),
+                        modes: Cow::Owned(clown_core::mode::format_modes(changes)),
+                    },
+                    false,
+                )?;
+            }
+
             MessageEvent::SetTopic(_, Some(source), channel, content) => {
                 self.write_to_target(
                     server_address,
@@ -668,7 +780,7 @@ impl MessageLogger {
             }
 
             MessageEvent::Notice(server_id, source, target, content)
-            | MessageEvent::PrivMsg(server_id, source, target, content) => {
+            | MessageEvent::PrivMsg(server_id, source, target, content, _, _) => {
                 if let Some(irc_model) = irc_model.as_ref()
                     && let Some(irc_server) = irc_model.get_server(*server_id)
                 {
@@ -686,7 +798,7 @@ impl MessageLogger {
                 }
             }
 
-            MessageEvent::ActionMsg(_, source, target, content) => {
+            MessageEvent::ActionMsg(_, source, target, content, _) => {
                 self.write_to_target(
                     server_address,
                     Some(target),
@@ -698,6 +810,29 @@ impl MessageLogger {
                 )?;
             }
 
+            MessageEvent::History(_, target, history) => {
+                for message in history {
+                    let logged = match message.kind {
+                        HistoryKind::Action => LoggedMessage::Action {
+                            source: Cow::Borrowed(&message.source),
+                            content: Cow::Borrowed(&message.content),
+                        },
+                        HistoryKind::Message | HistoryKind::Notice => LoggedMessage::Message {
+                            source: Cow::Borrowed(&message.source),
+                            content: Cow::Borrowed(&message.content),
+                        },
+                    };
+                    self.write_timed_to_target(
+                        server_address,
+                        Some(target),
+                        LoggedTimedMessage {
+                            time: message.time,
+                            message: logged,
+                        },
+                    )?;
+                }
+            }
+
             _ => {}
         };
 
@@ -914,4 +1049,71 @@ mod tests {
             "2026-03-28 09:42:01"
         );
     }
+
+    #[test]
+    fn test_write_timed_message_keeps_time() {
+        let dir = tempdir().expect("Cannot create dir");
+        let log_path = dir.path().join("test.log");
+
+        let mut logger = LogWriter::try_from_path(&log_path).unwrap();
+        logger
+            .write_timed_message(LoggedTimedMessage {
+                time: parse_utc_to_system_time("2024-05-20 14:30:05").unwrap(),
+                message: LoggedMessage::Message {
+                    source: Cow::Borrowed("alice"),
+                    content: Cow::Borrowed("hello"),
+                },
+            })
+            .unwrap();
+        logger.flush(true).unwrap();
+
+        let content = fs::read_to_string(log_path).unwrap();
+        assert_eq!(content, "2024-05-20 14:30:05\talice hello\n");
+    }
+
+    #[test]
+    fn test_write_kick_can_be_parsed() {
+        let dir = tempdir().expect("Cannot create dir");
+        let log_path = dir.path().join("test.log");
+
+        let mut logger = LogWriter::try_from_path(&log_path).unwrap();
+        logger
+            .write_message(LoggedMessage::Kick {
+                source: Cow::Borrowed("alice"),
+                target: Cow::Borrowed("bob"),
+                channel: Cow::Borrowed("#rust"),
+                reason: Some(Cow::Borrowed("no (spam)")),
+            })
+            .unwrap();
+        logger.flush(true).unwrap();
+
+        let content = fs::read_to_string(log_path).unwrap();
+        let parsed = log_parser::parse(content.trim_end().as_bytes()).unwrap();
+        assert!(matches!(parsed.message,
+            LoggedMessage::Kick { source, target, channel, reason }
+                if source == "alice" && target == "bob" && channel == "#rust"
+                    && reason.as_deref() == Some("no (spam)")));
+    }
+
+    #[test]
+    fn test_write_mode_can_be_parsed() {
+        let dir = tempdir().expect("Cannot create dir");
+        let log_path = dir.path().join("test.log");
+
+        let mut logger = LogWriter::try_from_path(&log_path).unwrap();
+        logger
+            .write_message(LoggedMessage::Mode {
+                source: Cow::Borrowed("alice"),
+                target: Cow::Borrowed("#rust"),
+                modes: Cow::Borrowed("+o bob"),
+            })
+            .unwrap();
+        logger.flush(true).unwrap();
+
+        let content = fs::read_to_string(log_path).unwrap();
+        let parsed = log_parser::parse(content.trim_end().as_bytes()).unwrap();
+        assert!(matches!(parsed.message,
+            LoggedMessage::Mode { source, target, modes }
+                if source == "alice" && target == "#rust" && modes == "+o bob"));
+    }
 }
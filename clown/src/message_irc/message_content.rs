@@ -67,6 +67,11 @@ pub struct MessageContent {
     width_without_format: usize,
     kind: MessageKind,
     is_log: bool,
+    msgid: Option<String>,                 /*IRCv3 message id*/
+    is_pending: bool,                      /*Waiting for the echo of the server*/
+    reactions: Vec<(String, Vec<String>)>, /*reaction, sources */
+    reply_to: Option<String>,              /*msgid of the parent message*/
+    quote: Option<String>,                 /*Compact parent message, if known*/
 }
 
 impl MessageContent {
@@ -83,6 +88,88 @@ impl MessageContent {
         self.is_log
     }
 
+    #[cfg(test)]
+    pub fn is_pending(&self) -> bool {
+        self.is_pending
+    }
+
+    /// The server has echoed the message
+    pub fn confirm_pending(&mut self, echo: &MessageContent) -> bool {
+        if self.is_pending && self.source == echo.source && self.content == echo.content {
+            self.is_pending = false;
+            self.msgid = echo.msgid.clone();
+            self.reply_to = echo.reply_to.clone();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn get_msgid(&self) -> Option<&str> {
+        self.msgid.as_deref()
+    }
+
+    pub fn get_reply_to(&self) -> Option<&str> {
+        self.reply_to.as_deref()
+    }
+
+    #[cfg(test)]
+    pub fn get_quote(&self) -> Option<&str> {
+        self.quote.as_deref()
+    }
+
+    pub fn set_quote(&mut self, parent: &MessageContent) {
+        self.quote = Some(parent.to_quote());
+    }
+
+    /// Compact form of the message, used when it is replied to
+    pub fn to_quote(&self) -> String {
+        match self.source.as_deref() {
+            Some(source) => format!("{}: {}", source, self.stripped_formatting()),
+            None => self.stripped_formatting().to_string(),
+        }
+    }
+
+    /// Returns false if the source has already sent this reaction
+    pub fn add_reaction(&mut self, reaction: &str, source: &str) -> bool {
+        if let Some((_, sources)) = self.reactions.iter_mut().find(|(r, _)| r == reaction) {
+            if sources.iter().any(|v| v.eq_ignore_ascii_case(source)) {
+                return false;
+            }
+            sources.push(source.to_string());
+        } else {
+            self.reactions
+                .push((reaction.to_string(), vec![source.to_string()]));
+        }
+        true
+    }
+
+    /// Compact line displayed under the message, "👍 2  🎉 1"
+    fn reactions_line(&self) -> Option<String> {
+        if self.reactions.is_empty() {
+            return None;
+        }
+        Some(
+            self.reactions
+                .iter()
+                .map(|(reaction, sources)| format!("{} {}", reaction, sources.len()))
+                .collect::<Vec<_>>()
+                .join("  "),
+        )
+    }
+
+    /// Same source and content sent during the same second (logs are stored in seconds)
+    pub fn is_same_message(&self, other: &MessageContent) -> bool {
+        let to_seconds = |time: std::time::SystemTime| {
+            time.duration_since(std::time::UNIX_EPOCH)
+                .map(|v| v.as_secs())
+                .unwrap_or_default()
+        };
+        self.source == other.source
+            && self.content == other.content
+            && to_seconds(self.time) == to_seconds(other.time)
+    }
+
     pub fn get_word_from_pos(&self, pos: &WordPos) -> Option<&str> {
         self.content.get(pos.byte_start..pos.byte_end)
     }
@@ -100,6 +187,11 @@ impl MessageContent {
             kind,
             time: std::time::SystemTime::now(),
             is_log: false,
+            msgid: None,
+            is_pending: false,
+            reactions: Vec::new(),
+            reply_to: None,
+            quote: None,
         }
     }
 
@@ -149,6 +241,21 @@ impl MessageContent {
         self
     }
 
+    pub fn with_pending(mut self) -> Self {
+        self.is_pending = true;
+        self
+    }
+
+    pub fn with_msgid(mut self, msgid: Option<String>) -> Self {
+        self.msgid = msgid;
+        self
+    }
+
+    pub fn with_reply_to(mut self, reply_to: Option<String>) -> Self {
+        self.reply_to = reply_to;
+        self
+    }
+
     // --- Finalizer ---
 
     pub fn build(self) -> MessageContent {
@@ -159,6 +266,11 @@ impl MessageContent {
             content: self.content,
             kind: self.kind,
             is_log: self.is_log,
+            msgid: self.msgid,
+            is_pending: self.is_pending,
+            reactions: self.reactions,
+            reply_to: self.reply_to,
+            quote: self.quote,
         }
     }
 
@@ -224,6 +336,11 @@ impl MessageContent {
             MessageKind::Action | MessageKind::Notice => Style::default().fg(Color::LightBlue),
             MessageKind::Normal | MessageKind::Highlight => Style::default(),
         };
+        let default_style = if self.is_pending {
+            default_style.fg(Color::DarkGray)
+        } else {
+            default_style
+        };
         let separator_style = if self.is_log {
             Style::default().fg(Color::Gray)
         } else {
@@ -232,6 +349,17 @@ impl MessageContent {
         let wrapped = self.wrap_spans(content_width as usize, Some(default_style));
         let mut visible_rows = Vec::with_capacity(wrapped.len());
         let time_length: u16 = time_format.as_ref().map(|v| v.length()).unwrap_or(0);
+        //The parent is quoted above the reply
+        if self.reply_to.is_some() {
+            let quote = self.quote.as_deref().unwrap_or("unknown message");
+            visible_rows.push([
+                Cell::from(spaces(time_length)),
+                Cell::from(spaces(nickname_length)),
+                Cell::from("┃ ").style(separator_style),
+                Cell::from(format!("↪ {quote}")).style(Style::default().fg(Color::DarkGray)),
+            ]);
+        }
+        let first_row = visible_rows.len();
         visible_rows.push([
             time_format
                 .map(|v| {
@@ -269,13 +397,22 @@ impl MessageContent {
         }
 
         for (i, w) in wrapped.into_iter().enumerate() {
-            if let Some(row) = visible_rows.get_mut(i)
+            if let Some(row) = visible_rows.get_mut(first_row + i)
                 && let Some(last) = row.last_mut()
             {
                 *last = Cell::from(Line::from(w.spans.clone()));
             }
         }
 
+        if let Some(reactions) = self.reactions_line() {
+            visible_rows.push([
+                Cell::from(spaces(time_length)),
+                Cell::from(spaces(nickname_length)),
+                Cell::from("┃ ").style(separator_style),
+                Cell::from(reactions).style(Style::default().fg(Color::DarkGray)),
+            ]);
+        }
+
         visible_rows.into_iter().map(Row::new)
     }
 
@@ -285,6 +422,8 @@ impl MessageContent {
 
     pub fn wrapped_line_count(&self, width: usize) -> usize {
         wrapped_line_count(&strip_irc_formatting_cow(&self.content), width)
+            + usize::from(!self.reactions.is_empty())
+            + usize::from(self.reply_to.is_some())
     }
 
     pub fn stripped_formatting<'a>(&'a self) -> Cow<'a, str> {
@@ -318,6 +457,33 @@ mod test {
         assert_eq!(message2.wrapped_line_count(12), 4);
     }
 
+    #[test]
+    fn test_reactions() {
+        let mut message = MessageContent::message(None, "hello".to_string());
+        assert_eq!(message.wrapped_line_count(10), 1);
+        assert!(message.add_reaction("👍", "alice"));
+        assert!(!message.add_reaction("👍", "Alice"));
+        assert!(message.add_reaction("👍", "bob"));
+        assert!(message.add_reaction("🎉", "bob"));
+        assert_eq!(message.reactions_line().as_deref(), Some("👍 2  🎉 1"));
+        assert_eq!(message.wrapped_line_count(10), 2);
+        assert_eq!(message.create_rows(10, None, None, 4).count(), 2);
+    }
+
+    #[test]
+    fn test_reply_quote() {
+        let parent = MessageContent::message(Some("alice".into()), "hello".to_string())
+            .with_msgid(Some("abc".into()));
+        let mut reply = MessageContent::message(Some("bob".into()), "hi".to_string())
+            .with_reply_to(parent.get_msgid().map(|v| v.to_string()));
+        assert_eq!(reply.get_reply_to(), Some("abc"));
+        assert_eq!(reply.wrapped_line_count(10), 2);
+
+        reply.set_quote(&parent);
+        assert_eq!(reply.get_quote(), Some("alice: hello"));
+        assert_eq!(reply.create_rows(10, None, None, 4).count(), 2);
+    }
+
     #[test]
     fn test_word_find() {
         let message = MessageContent::message(None, "https://test.com".to_string());
@@ -17,6 +17,18 @@ impl ProjectPath {
         Self::project_dir().map(|v| v.data_local_dir().join("logs").to_path_buf())
     }
 
+    /// Strict Transport Security policies
+    pub fn sts_path() -> Option<PathBuf> {
+        Self::project_dir().map(|v| v.data_local_dir().join("sts.toml"))
+    }
+
+    /// Received files, when no directory is set in the config
+    pub fn download_dir() -> Option<PathBuf> {
+        directories::UserDirs::new()
+            .and_then(|v| v.download_dir().map(|v| v.to_path_buf()))
+            .or_else(|| Self::project_dir().map(|v| v.data_local_dir().join("downloads")))
+    }
+
     pub fn log_name() -> &'static str {
         "app.log"
     }
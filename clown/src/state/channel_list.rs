@@ -0,0 +1,151 @@
+use crate::message_irc::message_parser::strip_irc_formatting;
+
+/// Channel of a LIST reply
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelListEntry {
+    pub name: String,
+    pub users: usize,
+    pub topic: String,
+    /// The name and the topic in lowercase, for the filter and the sort
+    lowercase_name: String,
+    lowercase_topic: String,
+}
+
+impl ChannelListEntry {
+    /// The formatting and the `[+nt]` modes some servers put before the topic are removed
+    pub fn new(name: String, users: usize, topic: &str) -> Self {
+        let topic = strip_irc_formatting(topic);
+        let topic = match topic.strip_prefix("[+") {
+            Some(rest) if let Some((_, rest)) = rest.split_once(']') => rest.trim_start(),
+            _ => &topic,
+        };
+        Self {
+            lowercase_name: name.to_lowercase(),
+            lowercase_topic: topic.to_lowercase(),
+            name,
+            users,
+            topic: topic.to_string(),
+        }
+    }
+
+    /// The filter is searched in the name and the topic, ignoring the case
+    pub fn matches(&self, filter: &str) -> bool {
+        self.matches_lowercase(&filter.to_lowercase())
+    }
+
+    fn matches_lowercase(&self, filter: &str) -> bool {
+        self.lowercase_name.contains(filter) || self.lowercase_topic.contains(filter)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    /// The most populated first
+    #[default]
+    Users,
+    Name,
+}
+
+impl SortBy {
+    pub fn next(self) -> Self {
+        match self {
+            SortBy::Users => SortBy::Name,
+            SortBy::Name => SortBy::Users,
+        }
+    }
+}
+
+/// Positions of the entries matching the filter, in the order to display them
+pub fn visible(entries: &[ChannelListEntry], filter: &str, sort_by: SortBy) -> Vec<usize> {
+    let filter = filter.to_lowercase();
+    let mut positions = (0..entries.len())
+        .filter(|v| {
+            entries
+                .get(*v)
+                .is_some_and(|v| v.matches_lowercase(&filter))
+        })
+        .collect::<Vec<_>>();
+    match sort_by {
+        SortBy::Users => positions.sort_by_key(|v| {
+            entries
+                .get(*v)
+                .map(|v| (std::cmp::Reverse(v.users), v.lowercase_name.as_str()))
+        }),
+        SortBy::Name => {
+            positions.sort_by_key(|v| entries.get(*v).map(|v| v.lowercase_name.as_str()))
+        }
+    }
+    positions
+}
+
+/// Replies of LIST, handed over at each pull so that a long list is shown while it loads
+#[derive(Debug, Default)]
+pub struct ChannelListCollector {
+    entries: Vec<ChannelListEntry>,
+    is_changed: bool,
+    is_finished: bool,
+}
+
+impl ChannelListCollector {
+    pub fn push(&mut self, entry: ChannelListEntry) {
+        self.entries.push(entry);
+        self.is_changed = true;
+    }
+
+    pub fn finish(&mut self) {
+        self.is_finished = true;
+        self.is_changed = true;
+    }
+
+    /// The entries received since the last call and whether the list is complete
+    pub fn take(&mut self) -> Option<(Vec<ChannelListEntry>, bool)> {
+        if !std::mem::take(&mut self.is_changed) {
+            return None;
+        }
+        Some((
+            std::mem::take(&mut self.entries),
+            std::mem::take(&mut self.is_finished),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, users: usize) -> ChannelListEntry {
+        ChannelListEntry::new(name.to_string(), users, "")
+    }
+
+    #[test]
+    fn test_topic() {
+        let entry = ChannelListEntry::new("#rust".to_string(), 10, "[+nt] \x02Rust\x02 language");
+        assert_eq!(entry.topic, "Rust language");
+        let entry = ChannelListEntry::new("#rust".to_string(), 10, "[not modes");
+        assert_eq!(entry.topic, "[not modes");
+        assert!(entry.matches("RUST"));
+        assert!(entry.matches("modes"));
+        assert!(!entry.matches("linux"));
+    }
+
+    #[test]
+    fn test_visible() {
+        let entries = [entry("#b", 5), entry("#C", 20), entry("#a", 5)];
+        assert_eq!(visible(&entries, "", SortBy::Users), vec![1, 2, 0]);
+        assert_eq!(visible(&entries, "", SortBy::Name), vec![2, 0, 1]);
+        assert_eq!(visible(&entries, "c", SortBy::Name), vec![1]);
+        assert!(visible(&entries, "#d", SortBy::Users).is_empty());
+    }
+
+    #[test]
+    fn test_collector() {
+        let mut collector = ChannelListCollector::default();
+        assert!(collector.take().is_none());
+        collector.push(entry("#a", 1));
+        collector.push(entry("#b", 2));
+        assert_eq!(collector.take().map(|v| (v.0.len(), v.1)), Some((2, false)));
+        assert!(collector.take().is_none());
+        collector.finish();
+        assert_eq!(collector.take().map(|v| (v.0.len(), v.1)), Some((0, true)));
+    }
+}
@@ -42,6 +42,9 @@ pub struct Connection {
     pub port: u16,
     #[serde(default = "default_true")]
     pub use_tls: bool,
+    /// Path of the WebSocket gateway, wss:// is used with TLS
+    #[serde(default)]
+    pub websocket: Option<String>,
 }
 
 impl RemoteConfig for Connection {
@@ -54,6 +57,7 @@ impl RemoteConfig for Connection {
             Some("address") => Ok(self.address.to_string()),
             Some("port") => Ok(self.port.to_string()),
             Some("use_tls") => Ok(self.use_tls.to_string()),
+            Some("websocket") => Ok(self.websocket.clone().unwrap_or_default()),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Connection]: Invalid path"),
         }
@@ -67,6 +71,7 @@ impl RemoteConfig for Connection {
             Some("address") => Ok(vec![ValueParameter::String]),
             Some("port") => Ok(vec![ValueParameter::Number]),
             Some("use_tls") => Ok(vec![ValueParameter::Boolean]),
+            Some("websocket") => Ok(vec![ValueParameter::String]),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Connection]: Invalid path"),
         }
@@ -90,13 +95,17 @@ impl RemoteConfig for Connection {
                 self.use_tls = value.parse::<bool>()?;
                 Ok(())
             }
+            Some("websocket") => {
+                self.websocket = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Connection]: Invalid path"),
         }
     }
 
     fn get_paths(prefix: &str) -> Vec<String> {
-        ["address", "port", "use_tls"]
+        ["address", "port", "use_tls", "websocket"]
             .iter()
             .map(|v| format!("{prefix}.{v}"))
             .collect::<Vec<String>>()
@@ -109,6 +118,8 @@ pub struct Login {
     pub real_name: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Network of a soju bouncer, the connection is bound to it
+    pub bouncer_network: Option<String>,
 }
 
 impl RemoteConfig for Login {
@@ -122,6 +133,7 @@ impl RemoteConfig for Login {
             Some("real_name") => Ok(self.real_name.clone().unwrap_or_default()),
             Some("username") => Ok(self.username.clone().unwrap_or_default()),
             Some("password") => Ok(self.password.clone().unwrap_or_default()),
+            Some("bouncer_network") => Ok(self.bouncer_network.clone().unwrap_or_default()),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Login]: Invalid path"),
         }
@@ -137,6 +149,7 @@ impl RemoteConfig for Login {
             Some("real_name") => Ok(vec![ValueParameter::String]),
             Some("username") => Ok(vec![ValueParameter::String]),
             Some("password") => Ok(vec![ValueParameter::String]),
+            Some("bouncer_network") => Ok(vec![ValueParameter::String]),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Login]: Invalid path"),
         }
@@ -164,16 +177,26 @@ impl RemoteConfig for Login {
                 self.password = Some(value);
                 Ok(())
             }
+            Some("bouncer_network") => {
+                self.bouncer_network = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("Invalid path"),
         }
     }
 
     fn get_paths(prefix: &str) -> Vec<String> {
-        ["nickname", "real_name", "username", "password"]
-            .iter()
-            .map(|v| format!("{prefix}.{v}"))
-            .collect::<Vec<String>>()
+        [
+            "nickname",
+            "real_name",
+            "username",
+            "password",
+            "bouncer_network",
+        ]
+        .iter()
+        .map(|v| format!("{prefix}.{v}"))
+        .collect::<Vec<String>>()
     }
 }
 
@@ -181,10 +204,28 @@ impl RemoteConfig for Login {
 pub struct Channels {
     pub list: Vec<String>,
     pub auto_join: bool,
+    /// Join again a channel after being kicked
+    #[serde(default)]
+    pub auto_rejoin: bool,
+    /// Keys of the +k channels
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub keys: ahash::AHashMap<String, String>,
+    /// Invitations joined without /accept, from these nicknames or to these channels
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub accept_invites: Vec<String>,
+}
+
+impl Channels {
+    pub fn get_key(&self, channel: &str) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(channel))
+            .map(|(_, key)| key.as_str())
+    }
 }
 
 impl RemoteConfig for Channels {
-    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    fn get_value<I>(&self, mut path: I, option: Option<&str>) -> Result<String>
     where
         I: Iterator,
         I::Item: AsRef<str>,
@@ -192,6 +233,19 @@ impl RemoteConfig for Channels {
         match path.next().as_ref().map(AsRef::as_ref) {
             Some("list") => Ok(self.list.join(",")),
             Some("auto_join") => Ok(self.auto_join.to_string()),
+            Some("auto_rejoin") => Ok(self.auto_rejoin.to_string()),
+            Some("accept_invites") => Ok(self.accept_invites.join(",")),
+            Some("keys") => {
+                if let Some(channel) = option.filter(|v| !v.is_empty()) {
+                    if let Some(key) = self.get_key(channel) {
+                        Ok(key.to_string())
+                    } else {
+                        bail!("The channel {channel} has no key")
+                    }
+                } else {
+                    bail!("Channel invalid: keys 'channel'")
+                }
+            }
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("Invalid path"),
         }
@@ -203,8 +257,9 @@ impl RemoteConfig for Channels {
         I::Item: AsRef<str>,
     {
         match path.next().as_ref().map(AsRef::as_ref) {
-            Some("list") => Ok(vec![ValueParameter::String]),
-            Some("auto_join") => Ok(vec![ValueParameter::List]),
+            Some("list") | Some("accept_invites") => Ok(vec![ValueParameter::String]),
+            Some("auto_join") | Some("auto_rejoin") => Ok(vec![ValueParameter::List]),
+            Some("keys") => Ok(vec![ValueParameter::String, ValueParameter::String]),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Login]: Invalid path"),
         }
@@ -227,24 +282,283 @@ impl RemoteConfig for Channels {
                 self.auto_join = value.parse::<bool>()?;
                 Ok(())
             }
+            Some("auto_rejoin") => {
+                self.auto_rejoin = value.parse::<bool>()?;
+                Ok(())
+            }
+            Some("accept_invites") => {
+                self.accept_invites = value
+                    .split(',')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<String>>();
+                Ok(())
+            }
+            Some("keys") => {
+                let mut split = value.split_ascii_whitespace();
+                let Some(channel) = split.next() else {
+                    bail!("Invalid value: {value}.\n should be: #channel key")
+                };
+                //Without a key, the channel is forgotten
+                self.keys
+                    .retain(|name, _| !name.eq_ignore_ascii_case(channel));
+                if let Some(key) = split.next() {
+                    self.keys.insert(channel.to_string(), key.to_string());
+                }
+                Ok(())
+            }
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("Invalid path"),
         }
     }
 
     fn get_paths(prefix: &str) -> Vec<String> {
-        ["list", "auto_join"]
+        ["list", "auto_join", "auto_rejoin", "keys", "accept_invites"]
             .iter()
             .map(|v| format!("{prefix}.{v}"))
             .collect::<Vec<String>>()
     }
 }
+/// Reconnection after losing the server, the delay doubles after each attempt
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Reconnect {
+    /// 0 to retry forever
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+    /// Seconds before the first retry
+    #[serde(default = "default_retry_delay")]
+    pub delay: u64,
+    /// Maximum seconds between two retries
+    #[serde(default = "default_max_retry_delay")]
+    pub max_delay: u64,
+}
+
+fn default_max_retries() -> usize {
+    5
+}
+
+fn default_retry_delay() -> u64 {
+    5
+}
+
+fn default_max_retry_delay() -> u64 {
+    300
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            delay: default_retry_delay(),
+            max_delay: default_max_retry_delay(),
+        }
+    }
+}
+
+impl RemoteConfig for Reconnect {
+    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("max_retries") => Ok(self.max_retries.to_string()),
+            Some("delay") => Ok(self.delay.to_string()),
+            Some("max_delay") => Ok(self.max_delay.to_string()),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Reconnect]: Invalid path"),
+        }
+    }
+
+    fn expected_parameters<I>(mut path: I) -> Result<Vec<ValueParameter>>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("max_retries") | Some("delay") | Some("max_delay") => {
+                Ok(vec![ValueParameter::Number])
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Reconnect]: Invalid path"),
+        }
+    }
+
+    fn set_value<I>(&mut self, mut path: I, value: String) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("max_retries") => {
+                self.max_retries = value.parse::<usize>()?;
+                Ok(())
+            }
+            Some("delay") => {
+                self.delay = value.parse::<u64>()?;
+                Ok(())
+            }
+            Some("max_delay") => {
+                self.max_delay = value.parse::<u64>()?;
+                Ok(())
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Reconnect]: Invalid path"),
+        }
+    }
+
+    fn get_paths(prefix: &str) -> Vec<String> {
+        ["max_retries", "delay", "max_delay"]
+            .iter()
+            .map(|v| format!("{prefix}.{v}"))
+            .collect::<Vec<String>>()
+    }
+}
+
+/// How the nickname is taken back when another connection uses it
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::EnumString,
+    strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Recover {
+    /// NickServ gives the nickname back directly
+    #[default]
+    Regain,
+    /// NickServ disconnects the other connection, the nickname is changed after
+    Ghost,
+    None,
+}
+
+/// NickServ identification, for the networks without SASL
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Services {
+    /// Password of the account, nothing is sent without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default = "default_nickserv")]
+    pub nickserv: String,
+    #[serde(default)]
+    pub recover: Recover,
+    /// The channels needing an account are joined again once NickServ has confirmed the identification
+    #[serde(default = "default_true")]
+    pub delay_join: bool,
+}
+
+fn default_nickserv() -> String {
+    "NickServ".to_string()
+}
+
+impl Default for Services {
+    fn default() -> Self {
+        Self {
+            password: None,
+            nickserv: default_nickserv(),
+            recover: Recover::default(),
+            delay_join: true,
+        }
+    }
+}
+
+impl RemoteConfig for Services {
+    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("password") => Ok(self.password.clone().unwrap_or_default()),
+            Some("nickserv") => Ok(self.nickserv.to_string()),
+            Some("recover") => Ok(self.recover.to_string()),
+            Some("delay_join") => Ok(self.delay_join.to_string()),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Services]: Invalid path"),
+        }
+    }
+
+    fn expected_parameters<I>(mut path: I) -> Result<Vec<ValueParameter>>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("password") | Some("nickserv") | Some("recover") => {
+                Ok(vec![ValueParameter::String])
+            }
+            Some("delay_join") => Ok(vec![ValueParameter::Boolean]),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Services]: Invalid path"),
+        }
+    }
+
+    fn set_value<I>(&mut self, mut path: I, value: String) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("password") => {
+                self.password = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
+            Some("nickserv") => {
+                if value.is_empty() {
+                    bail!("The nickname of NickServ cannot be empty");
+                }
+                self.nickserv = value;
+                Ok(())
+            }
+            Some("recover") => {
+                self.recover = value.parse::<Recover>()?;
+                Ok(())
+            }
+            Some("delay_join") => {
+                self.delay_join = value.parse::<bool>()?;
+                Ok(())
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Services]: Invalid path"),
+        }
+    }
+
+    fn get_paths(prefix: &str) -> Vec<String> {
+        ["password", "nickserv", "recover", "delay_join"]
+            .iter()
+            .map(|v| format!("{prefix}.{v}"))
+            .collect::<Vec<String>>()
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Server {
     pub name: String,
     pub connection: Connection,
     pub login: Login,
     pub channels: Channels,
+    /// Nicknames to be notified about when they are online or offline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub reconnect: Reconnect,
+    /// Other hosts of the network, tried in turn when the connection fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<Connection>,
+    /// The endpoints are tried in a random order instead
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub random_endpoints: bool,
+    /// Commands run after the registration, `/wait <seconds>` delays the next ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub perform: Vec<String>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub services: Services,
 }
 
 impl RemoteConfig for Server {
@@ -258,6 +572,11 @@ impl RemoteConfig for Server {
             Some("connection") => self.connection.get_value(path, option),
             Some("login") => self.login.get_value(path, option),
             Some("channels") => self.channels.get_value(path, option),
+            Some("notify") => Ok(self.notify.join(",")),
+            Some("reconnect") => self.reconnect.get_value(path, option),
+            Some("random_endpoints") => Ok(self.random_endpoints.to_string()),
+            Some("perform") => Ok(self.perform.join("; ")),
+            Some("services") => self.services.get_value(path, option),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("Invalid path"),
         }
@@ -273,6 +592,11 @@ impl RemoteConfig for Server {
             Some("connection") => Connection::expected_parameters(path),
             Some("login") => Login::expected_parameters(path),
             Some("channels") => Channels::expected_parameters(path),
+            Some("notify") => Ok(vec![ValueParameter::String]),
+            Some("reconnect") => Reconnect::expected_parameters(path),
+            Some("random_endpoints") => Ok(vec![ValueParameter::Boolean]),
+            Some("perform") => Ok(vec![ValueParameter::String]),
+            Some("services") => Services::expected_parameters(path),
             Some(p) => bail!("Invalid path {p}"),
             _ => bail!("[Login]: Invalid path"),
         }
@@ -300,17 +624,53 @@ impl RemoteConfig for Server {
                 self.channels.set_value(path, value)?;
                 Ok(())
             }
+            Some("reconnect") => {
+                self.reconnect.set_value(path, value)?;
+                Ok(())
+            }
+            Some("services") => {
+                self.services.set_value(path, value)?;
+                Ok(())
+            }
+            Some("random_endpoints") => {
+                self.random_endpoints = value.parse::<bool>()?;
+                Ok(())
+            }
+            //The commands are separated by ';'
+            Some("perform") => {
+                self.perform = value
+                    .split(';')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<String>>();
+                Ok(())
+            }
+            Some("notify") => {
+                self.notify = value
+                    .split(',')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+                    .collect::<Vec<String>>();
+                Ok(())
+            }
             _ => bail!("Invalid path"),
         }
     }
 
     fn get_paths(prefix: &str) -> Vec<String> {
         let p = format!("{prefix}.");
-        let mut fields = vec![format!("{p}name")];
+        let mut fields = vec![
+            format!("{p}name"),
+            format!("{p}notify"),
+            format!("{p}random_endpoints"),
+            format!("{p}perform"),
+        ];
 
         fields.extend(Connection::get_paths(&format!("{p}connection")));
         fields.extend(Login::get_paths(&format!("{p}login")));
         fields.extend(Channels::get_paths(&format!("{p}channels")));
+        fields.extend(Reconnect::get_paths(&format!("{p}reconnect")));
+        fields.extend(Services::get_paths(&format!("{p}services")));
 
         fields
     }
@@ -772,6 +1132,255 @@ fn default_true() -> bool {
     true
 }
 
+//
+// DCC
+//
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default, PartialEq)]
+pub struct Dcc {
+    /// Where the received files are written, the download directory of the user by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_dir: Option<PathBuf>,
+    /// Address announced in the offers, the one used to reach the server by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The other side listens (reverse DCC), when clown cannot be reached
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub passive: bool,
+}
+
+impl RemoteConfig for Dcc {
+    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("download_dir") => Ok(self
+                .download_dir
+                .as_ref()
+                .map(|v| v.display().to_string())
+                .unwrap_or_default()),
+            Some("address") => Ok(self.address.clone().unwrap_or_default()),
+            Some("passive") => Ok(self.passive.to_string()),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Dcc]: Invalid path"),
+        }
+    }
+
+    fn expected_parameters<I>(mut path: I) -> Result<Vec<ValueParameter>>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("download_dir") => Ok(vec![ValueParameter::String]),
+            Some("address") => Ok(vec![ValueParameter::String]),
+            Some("passive") => Ok(vec![ValueParameter::Boolean]),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Dcc]: Invalid path"),
+        }
+    }
+
+    fn set_value<I>(&mut self, mut path: I, value: String) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("download_dir") => {
+                self.download_dir = (!value.is_empty()).then(|| PathBuf::from(value));
+                Ok(())
+            }
+            Some("address") => {
+                if !value.is_empty() {
+                    value.parse::<std::net::IpAddr>()?;
+                }
+                self.address = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
+            Some("passive") => {
+                self.passive = value.parse::<bool>()?;
+                Ok(())
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Dcc]: Invalid path"),
+        }
+    }
+
+    fn get_paths(prefix: &str) -> Vec<String> {
+        ["download_dir", "address", "passive"]
+            .iter()
+            .map(|v| format!("{prefix}.{v}"))
+            .collect::<Vec<String>>()
+    }
+}
+
+//
+// CTCP
+//
+
+/// Automatic replies to the CTCP queries
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct Ctcp {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Replaces the build information in the VERSION reply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl RemoteConfig for Ctcp {
+    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("enabled") => Ok(self.enabled.to_string()),
+            Some("version") => Ok(self.version.clone().unwrap_or_default()),
+            Some("source") => Ok(self.source.clone().unwrap_or_default()),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Ctcp]: Invalid path"),
+        }
+    }
+
+    fn expected_parameters<I>(mut path: I) -> Result<Vec<ValueParameter>>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("enabled") => Ok(vec![ValueParameter::Boolean]),
+            Some("version") => Ok(vec![ValueParameter::String]),
+            Some("source") => Ok(vec![ValueParameter::String]),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Ctcp]: Invalid path"),
+        }
+    }
+
+    fn set_value<I>(&mut self, mut path: I, value: String) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("enabled") => {
+                self.enabled = value.parse::<bool>()?;
+                Ok(())
+            }
+            Some("version") => {
+                self.version = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
+            Some("source") => {
+                self.source = (!value.is_empty()).then_some(value);
+                Ok(())
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Ctcp]: Invalid path"),
+        }
+    }
+
+    fn get_paths(prefix: &str) -> Vec<String> {
+        ["enabled", "version", "source"]
+            .iter()
+            .map(|v| format!("{prefix}.{v}"))
+            .collect::<Vec<String>>()
+    }
+}
+
+/// Part of the user@host kept in the masks of /ban, /kickban and /quiet
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::EnumString,
+    strum::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum BanMask {
+    /// *!*@host
+    #[default]
+    Host,
+    /// *!*user@host
+    User,
+    /// *!*@*.domain
+    Domain,
+    /// nick!*@*
+    Nick,
+    /// nick!*user@host
+    Full,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default, PartialEq)]
+pub struct Moderation {
+    #[serde(default)]
+    pub ban_mask: BanMask,
+}
+
+impl RemoteConfig for Moderation {
+    fn get_value<I>(&self, mut path: I, _option: Option<&str>) -> Result<String>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("ban_mask") => Ok(self.ban_mask.to_string()),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Moderation]: Invalid path"),
+        }
+    }
+
+    fn expected_parameters<I>(mut path: I) -> Result<Vec<ValueParameter>>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("ban_mask") => Ok(vec![ValueParameter::String]),
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Moderation]: Invalid path"),
+        }
+    }
+
+    fn set_value<I>(&mut self, mut path: I, value: String) -> Result<()>
+    where
+        I: Iterator,
+        I::Item: AsRef<str>,
+    {
+        match path.next().as_ref().map(AsRef::as_ref) {
+            Some("ban_mask") => {
+                self.ban_mask = value.parse::<BanMask>()?;
+                Ok(())
+            }
+            Some(p) => bail!("Invalid path {p}"),
+            _ => bail!("[Moderation]: Invalid path"),
+        }
+    }
+
+    fn get_paths(prefix: &str) -> Vec<String> {
+        vec![format!("{prefix}.ban_mask")]
+    }
+}
+
+impl Default for Ctcp {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            version: None,
+            source: None,
+        }
+    }
+}
+
 //
 // KEYBINDINGS
 //
@@ -815,6 +1424,15 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "is_default")]
     pub topic: Topic,
 
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub ctcp: Ctcp,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub dcc: Dcc,
+
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub moderation: Moderation,
+
     #[serde(default, skip_serializing_if = "is_default")]
     pub keybindings: Keybindings,
 
@@ -837,18 +1455,29 @@ impl Default for Config {
                     address: "".into(),
                     port: 6697,
                     use_tls: true,
+                    websocket: None,
                 },
                 channels: Channels {
                     list: vec![],
                     auto_join: false,
+                    auto_rejoin: false,
+                    keys: ahash::AHashMap::new(),
+                    accept_invites: Vec::new(),
                 },
                 login: Login {
                     nickname: "nickname".into(),
                     real_name: None,
                     username: None,
                     password: None,
+                    bouncer_network: None,
                 },
                 name: "IRC-Server".into(),
+                notify: vec![],
+                reconnect: Reconnect::default(),
+                endpoints: Vec::new(),
+                random_endpoints: false,
+                perform: Vec::new(),
+                services: Services::default(),
             }],
             nickname_colors: NicknameColors::default(),
             completion: Completion::default(),
@@ -856,6 +1485,9 @@ impl Default for Config {
             discuss: Discuss::default(),
             users: Users::default(),
             topic: Topic::default(),
+            ctcp: Ctcp::default(),
+            dcc: Dcc::default(),
+            moderation: Moderation::default(),
             meta: Meta { version: 0 },
         }
     }
@@ -890,6 +1522,9 @@ impl RemoteConfig for Config {
             Some("discuss") => self.discuss.get_value(path, option),
             Some("users") => self.users.get_value(path, option),
             Some("topic") => self.topic.get_value(path, option),
+            Some("ctcp") => self.ctcp.get_value(path, option),
+            Some("dcc") => self.dcc.get_value(path, option),
+            Some("moderation") => self.moderation.get_value(path, option),
             Some("meta") => match path.next().as_ref().map(AsRef::as_ref) {
                 Some("version") => Ok(self.meta.version.to_string()),
                 _ => bail!("Invalid path"),
@@ -915,6 +1550,9 @@ impl RemoteConfig for Config {
             Some("discuss") => Discuss::expected_parameters(path),
             Some("users") => Users::expected_parameters(path),
             Some("topic") => Topic::expected_parameters(path),
+            Some("ctcp") => Ctcp::expected_parameters(path),
+            Some("dcc") => Dcc::expected_parameters(path),
+            Some("moderation") => Moderation::expected_parameters(path),
             Some("meta") => match path.next().as_ref().map(AsRef::as_ref) {
                 Some("version") => Ok(vec![ValueParameter::String]),
                 _ => bail!("Invalid path"),
@@ -931,12 +1569,14 @@ impl RemoteConfig for Config {
     {
         match path.next().as_ref().map(AsRef::as_ref) {
             Some("server") => {
-                let mut split = value.split_ascii_whitespace();
-                let server_id = split
-                    .next()
-                    .and_then(|v| v.parse::<usize>().ok())
-                    .ok_or_else(|| anyhow::anyhow!("Invalid server index"))?;
-                let rest = split.collect::<String>();
+                //The value keeps its spaces, e.g. the commands of the perform list
+                let value = value.trim_start();
+                let (server_id, rest) =
+                    value.split_once(char::is_whitespace).unwrap_or((value, ""));
+                let server_id = server_id
+                    .parse::<usize>()
+                    .map_err(|_| anyhow::anyhow!("Invalid server index"))?;
+                let rest = rest.trim().to_string();
 
                 self.servers
                     .get_mut(server_id)
@@ -948,6 +1588,9 @@ impl RemoteConfig for Config {
             Some("discuss") => self.discuss.set_value(path, value),
             Some("users") => self.users.set_value(path, value),
             Some("topic") => self.topic.set_value(path, value),
+            Some("ctcp") => self.ctcp.set_value(path, value),
+            Some("dcc") => self.dcc.set_value(path, value),
+            Some("moderation") => self.moderation.set_value(path, value),
             Some("meta") => {
                 bail!("Invalid path, impossible to set")
             }
@@ -965,6 +1608,9 @@ impl RemoteConfig for Config {
             "discuss",
             "users",
             "topic",
+            "ctcp",
+            "dcc",
+            "moderation",
         ]
         .iter()
         .map(|v| v.to_string())
@@ -977,6 +1623,9 @@ impl RemoteConfig for Config {
         fields.extend(Discuss::get_paths("discuss"));
         fields.extend(Users::get_paths("users"));
         fields.extend(Topic::get_paths("topic"));
+        fields.extend(Ctcp::get_paths("ctcp"));
+        fields.extend(Dcc::get_paths("dcc"));
+        fields.extend(Moderation::get_paths("moderation"));
 
         fields
     }
@@ -1038,6 +1687,37 @@ impl Config {
             .flat_map(|v| v.channels.list.iter().map(|v| v.deref()))
     }
 
+    pub fn get_channel_key(&self, in_id: ServerID, channel: &str) -> Option<&str> {
+        self.servers
+            .get(in_id.as_usize())
+            .and_then(|v| v.channels.get_key(channel))
+    }
+
+    pub fn get_accept_invites(&self, in_id: ServerID) -> &[String] {
+        self.servers
+            .get(in_id.as_usize())
+            .map(|v| v.channels.accept_invites.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_notify_list(&self, in_id: ServerID) -> &[String] {
+        self.servers
+            .get(in_id.as_usize())
+            .map(|v| v.notify.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_perform(&self, in_id: ServerID) -> &[String] {
+        self.servers
+            .get(in_id.as_usize())
+            .map(|v| v.perform.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get_services(&self, in_id: ServerID) -> Option<&Services> {
+        self.servers.get(in_id.as_usize()).map(|v| &v.services)
+    }
+
     pub fn get_address(&self, in_id: ServerID) -> Option<&str> {
         self.servers
             .get(in_id.as_usize())
@@ -1071,6 +1751,12 @@ impl Config {
             .is_some_and(|v| v.channels.auto_join)
     }
 
+    pub fn is_autorejoin_id(&self, in_id: ServerID) -> bool {
+        self.servers
+            .get(in_id.as_usize())
+            .is_some_and(|v| v.channels.auto_rejoin)
+    }
+
     pub fn is_autojoin(&self) -> impl Iterator<Item = ServerID> {
         self.servers
             .iter()
@@ -1078,14 +1764,28 @@ impl Config {
             .filter_map(|(i, v)| v.channels.auto_join.then_some(ServerID::new(i)))
     }
 
-    pub fn get_connection_config(&self, in_id: ServerID) -> Option<ConnectionConfig> {
+    /// The main connection first, then the other endpoints
+    pub fn get_connection_configs(&self, in_id: ServerID) -> Vec<ConnectionConfig> {
         self.servers
             .get(in_id.as_usize())
-            .map(|v| ConnectionConfig {
-                address: v.connection.address.to_string(),
-                port: v.connection.port,
-                use_tls: v.connection.use_tls,
+            .map(|v| {
+                std::iter::once(&v.connection)
+                    .chain(v.endpoints.iter())
+                    .map(|connection| ConnectionConfig {
+                        address: connection.address.to_string(),
+                        port: connection.port,
+                        use_tls: connection.use_tls,
+                        websocket: connection.websocket.clone(),
+                    })
+                    .collect()
             })
+            .unwrap_or_default()
+    }
+
+    pub fn is_random_endpoints(&self, in_id: ServerID) -> bool {
+        self.servers
+            .get(in_id.as_usize())
+            .is_some_and(|v| v.random_endpoints)
     }
 
     pub fn get_login_config(&self, in_id: ServerID) -> Option<LoginConfig> {
@@ -1094,8 +1794,65 @@ impl Config {
             password: v.login.password.clone(),
             real_name: v.login.real_name.clone(),
             username: v.login.username.clone(),
+            bouncer_network: v.login.bouncer_network.clone(),
         })
     }
+
+    pub fn get_reconnect(&self, in_id: ServerID) -> Reconnect {
+        self.servers
+            .get(in_id.as_usize())
+            .map(|v| v.reconnect)
+            .unwrap_or_default()
+    }
+
+    pub fn get_bouncer_network(&self, in_id: ServerID) -> Option<&str> {
+        self.servers
+            .get(in_id.as_usize())
+            .and_then(|v| v.login.bouncer_network.as_deref())
+    }
+
+    /// The networks of a bouncer share the address, their logs are kept apart
+    pub fn get_log_address(&self, in_id: ServerID) -> Option<String> {
+        self.servers
+            .get(in_id.as_usize())
+            .map(|v| match &v.login.bouncer_network {
+                Some(network) => format!("{}-{network}", v.connection.address),
+                None => v.connection.address.to_string(),
+            })
+    }
+
+    /// Adds a server bound to a network of the bouncer, None if it already exists
+    pub fn add_bouncer_network(
+        &mut self,
+        in_id: ServerID,
+        network: &str,
+        name: Option<&str>,
+    ) -> Option<ServerID> {
+        let bouncer = self.servers.get(in_id.as_usize())?;
+        if self.servers.iter().any(|v| {
+            v.connection == bouncer.connection
+                && v.login.bouncer_network.as_deref() == Some(network)
+        }) {
+            return None;
+        }
+
+        let name = name.unwrap_or(network);
+        let name = if self.find_id_from_name(name).is_some() {
+            format!("{}/{name}", bouncer.name)
+        } else {
+            name.to_string()
+        };
+        let mut server = bouncer.clone();
+        server.name = name;
+        server.login.bouncer_network = Some(network.to_string());
+        //The bouncer joins the channels of the network by itself
+        server.channels.list.clear();
+        server.channels.auto_join = true;
+
+        let id = ServerID::new(self.servers.len());
+        self.servers.push(server);
+        Some(id)
+    }
 }
 
 #[cfg(test)]
@@ -1110,17 +1867,28 @@ mod tests {
                     address: "irc.example.com".into(),
                     port: 6667,
                     use_tls: true,
+                    websocket: None,
                 },
                 login: Login {
                     nickname: "tester".into(),
                     real_name: Some("Real".into()),
                     username: Some("user".into()),
                     password: None,
+                    bouncer_network: None,
                 },
                 channels: Channels {
                     list: vec!["#rust".into(), "#linux".into()],
                     auto_join: true,
+                    auto_rejoin: false,
+                    keys: ahash::AHashMap::new(),
+                    accept_invites: Vec::new(),
                 },
+                notify: vec!["alice".into(), "bob".into()],
+                reconnect: Reconnect::default(),
+                endpoints: Vec::new(),
+                random_endpoints: false,
+                perform: Vec::new(),
+                services: Services::default(),
             }],
             ..Default::default()
         }
@@ -1198,6 +1966,140 @@ mod tests {
         assert_eq!(channels, vec!["#rust", "#linux"]);
     }
 
+    #[test]
+    fn test_notify_list() {
+        let mut config = sample_config();
+
+        assert_eq!(config.get_notify_list(ServerID::new(0)), ["alice", "bob"]);
+        assert!(config.get_notify_list(ServerID::new(1)).is_empty());
+
+        config
+            .set_value_from_root("server.notify", "0 carl, dave".into())
+            .unwrap();
+        assert_eq!(
+            config
+                .get_value_from_root("server.notify", Some("0"))
+                .unwrap(),
+            "carl,dave"
+        );
+    }
+
+    #[test]
+    fn test_perform() {
+        let mut config = sample_config();
+        assert!(config.get_perform(ServerID::new(0)).is_empty());
+        assert!(!toml::to_string(&config).unwrap().contains("perform"));
+
+        config
+            .set_value_from_root(
+                "server.perform",
+                "0 /msg NickServ IDENTIFY pass; /wait 2 ;/mode $nick +x".into(),
+            )
+            .unwrap();
+        assert_eq!(
+            config.get_perform(ServerID::new(0)),
+            ["/msg NickServ IDENTIFY pass", "/wait 2", "/mode $nick +x"]
+        );
+        assert_eq!(
+            config
+                .get_value_from_root("server.perform", Some("0"))
+                .unwrap(),
+            "/msg NickServ IDENTIFY pass; /wait 2; /mode $nick +x"
+        );
+    }
+
+    #[test]
+    fn test_ban_mask() {
+        let mut config = sample_config();
+        assert!(!toml::to_string(&config).unwrap().contains("moderation"));
+        config
+            .set_value_from_root("moderation.ban_mask", "Domain".into())
+            .unwrap();
+        assert_eq!(config.moderation.ban_mask, BanMask::Domain);
+        assert_eq!(
+            config
+                .get_value_from_root("moderation.ban_mask", None)
+                .unwrap(),
+            "domain"
+        );
+        assert!(
+            config
+                .set_value_from_root("moderation.ban_mask", "ident".into())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_services() {
+        let mut config = sample_config();
+        assert!(!toml::to_string(&config).unwrap().contains("services"));
+
+        config
+            .set_value_from_root("server.services.password", "0 my secret".into())
+            .unwrap();
+        config
+            .set_value_from_root("server.services.recover", "0 Ghost".into())
+            .unwrap();
+        let services = config.get_services(ServerID::new(0)).unwrap();
+        assert_eq!(services.password.as_deref(), Some("my secret"));
+        assert_eq!(services.recover, Recover::Ghost);
+        assert_eq!(services.nickserv, "NickServ");
+        assert!(services.delay_join);
+        assert!(
+            config
+                .set_value_from_root("server.services.recover", "0 kill".into())
+                .is_err()
+        );
+        assert_eq!(
+            config
+                .get_value_from_root("server.services.recover", Some("0"))
+                .unwrap(),
+            "ghost"
+        );
+    }
+
+    #[test]
+    fn test_channel_keys() {
+        let mut config = sample_config();
+        assert!(!toml::to_string(&config).unwrap().contains("keys"));
+
+        config
+            .set_value_from_root("server.channels.keys", "0 #Secret hunter2".into())
+            .unwrap();
+        let id = ServerID::new(0);
+        assert_eq!(config.get_channel_key(id, "#secret"), Some("hunter2"));
+        assert_eq!(
+            config
+                .get_value_from_root("server.channels.keys", Some("0 #secret"))
+                .unwrap(),
+            "hunter2"
+        );
+        assert!(toml::to_string(&config).unwrap().contains("hunter2"));
+
+        config
+            .set_value_from_root("server.channels.keys", "0 #secret".into())
+            .unwrap();
+        assert!(config.get_channel_key(id, "#secret").is_none());
+        assert!(config.get_channel_key(id, "#rust").is_none());
+    }
+
+    #[test]
+    fn test_accept_invites() {
+        let mut config = sample_config();
+        let id = ServerID::new(0);
+        assert!(config.get_accept_invites(id).is_empty());
+        config
+            .set_value_from_root("server.channels.accept_invites", "0 #rust, alice".into())
+            .unwrap();
+        assert_eq!(config.get_accept_invites(id), ["#rust", "alice"]);
+        assert_eq!(
+            config
+                .get_value_from_root("server.channels.accept_invites", Some("0"))
+                .unwrap(),
+            "#rust,alice"
+        );
+    }
+
     #[test]
     fn test_get_address() {
         let config = sample_config();
@@ -1223,10 +2125,34 @@ mod tests {
     fn test_get_connection_config() {
         let config = sample_config();
 
-        let conn = config.get_connection_config(ServerID::new(0)).unwrap();
+        let conns = config.get_connection_configs(ServerID::new(0));
+
+        assert_eq!(conns.len(), 1);
+        assert_eq!(conns[0].address, "irc.example.com");
+        assert_eq!(conns[0].port, 6667);
+        assert!(config.get_connection_configs(ServerID::new(3)).is_empty());
+    }
 
-        assert_eq!(conn.address, "irc.example.com");
-        assert_eq!(conn.port, 6667);
+    #[test]
+    fn test_endpoints() {
+        let mut config = sample_config();
+        let server = config.servers.get_mut(0).unwrap();
+        server.endpoints.push(Connection {
+            address: "irc2.example.com".into(),
+            port: 6697,
+            use_tls: true,
+            websocket: None,
+        });
+        let serialized = toml::to_string(&config).unwrap();
+        assert!(serialized.contains("irc2.example.com"));
+        assert!(!serialized.contains("random_endpoints"));
+
+        let config: Config = toml::from_str(&serialized).unwrap();
+        let conns = config.get_connection_configs(ServerID::new(0));
+        assert_eq!(conns.len(), 2);
+        assert_eq!(conns[1].address, "irc2.example.com");
+        assert!(conns[1].use_tls);
+        assert!(!config.is_random_endpoints(ServerID::new(0)));
     }
 
     #[test]
@@ -1240,6 +2166,36 @@ mod tests {
         assert_eq!(login.real_name, Some("Real".into()));
     }
 
+    #[test]
+    fn test_add_bouncer_network() {
+        let mut config = sample_config();
+        let bouncer = ServerID::new(0);
+
+        let id = config
+            .add_bouncer_network(bouncer, "42", Some("Libera"))
+            .unwrap();
+        assert_eq!(config.get_name(id), "Libera");
+        assert_eq!(config.get_bouncer_network(id), Some("42"));
+        assert_eq!(config.get_channels(id).count(), 0);
+        assert!(config.is_autojoin_id(id));
+        assert_eq!(
+            config.get_log_address(id).as_deref(),
+            Some("irc.example.com-42")
+        );
+        assert_eq!(
+            config.get_log_address(bouncer).as_deref(),
+            Some("irc.example.com")
+        );
+
+        //Already known
+        assert!(config.add_bouncer_network(bouncer, "42", None).is_none());
+        //The name is taken
+        let id = config
+            .add_bouncer_network(bouncer, "43", Some("libera"))
+            .unwrap();
+        assert_eq!(config.get_name(id), "test/libera");
+    }
+
     #[test]
     fn test_invalid_path() {
         let config = sample_config();
@@ -0,0 +1,177 @@
+use super::config;
+use clown_core::ctcp::Ctcp;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// No more than 4 replies every 10 seconds, the other queries are ignored
+const MAX_REPLIES: usize = 4;
+const REPLY_WINDOW: Duration = Duration::from_secs(10);
+
+const DEFAULT_SOURCE: &str = "https://github.com/F4r3n/clown";
+
+/// Queries answered automatically
+pub const SUPPORTED: &[&str] = &["ACTION", "CLIENTINFO", "PING", "SOURCE", "TIME", "VERSION"];
+
+/// Answers the CTCP queries of a server, limited to avoid being flooded off
+#[derive(Debug, Default)]
+pub struct CtcpResponder {
+    replies: VecDeque<Instant>,
+}
+
+impl CtcpResponder {
+    /// The build information is used for VERSION unless the config replaces it
+    pub fn reply(
+        &mut self,
+        query: &Ctcp,
+        settings: &config::Ctcp,
+        build_version: &str,
+        now: Instant,
+    ) -> Option<Ctcp> {
+        if !settings.enabled {
+            return None;
+        }
+        let params = match query.command.as_str() {
+            "VERSION" => settings
+                .version
+                .clone()
+                .unwrap_or_else(|| build_version.to_string()),
+            "PING" => query.params.clone().unwrap_or_default(),
+            "TIME" => chrono::Local::now().to_rfc2822(),
+            "CLIENTINFO" => SUPPORTED.join(" "),
+            "SOURCE" => settings
+                .source
+                .clone()
+                .unwrap_or_else(|| DEFAULT_SOURCE.to_string()),
+            _ => return None,
+        };
+        if !self.can_reply(now) {
+            return None;
+        }
+        Some(Ctcp::new(&query.command, Some(params)))
+    }
+
+    fn can_reply(&mut self, now: Instant) -> bool {
+        while self
+            .replies
+            .front()
+            .is_some_and(|v| now.saturating_duration_since(*v) >= REPLY_WINDOW)
+        {
+            self.replies.pop_front();
+        }
+        if self.replies.len() >= MAX_REPLIES {
+            return false;
+        }
+        self.replies.push_back(now);
+        true
+    }
+}
+
+/// Milliseconds since the epoch, sent back by the PING reply to measure the lag
+pub fn ping_params(now: SystemTime) -> String {
+    now.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .to_string()
+}
+
+/// Text displayed for a reply received by NOTICE
+pub fn describe_reply(source: &str, reply: &Ctcp, now: SystemTime) -> String {
+    let params = reply.params.as_deref().unwrap_or_default();
+    if reply.command == "PING"
+        && let Ok(sent) = params.parse::<u64>()
+        && let Ok(elapsed) = now.duration_since(UNIX_EPOCH + Duration::from_millis(sent))
+    {
+        format!(
+            "CTCP PING reply from {source}: {:.3}s",
+            elapsed.as_secs_f32()
+        )
+    } else {
+        format!("CTCP {} reply from {source}: {params}", reply.command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION: &str = "clown 0.4.0 (f8bf4c0b, linux-x86_64)";
+
+    #[test]
+    fn test_reply() {
+        let mut responder = CtcpResponder::default();
+        let settings = config::Ctcp::default();
+        let now = Instant::now();
+
+        let reply = responder
+            .reply(
+                &Ctcp::new("PING", Some("123".into())),
+                &settings,
+                VERSION,
+                now,
+            )
+            .unwrap();
+        assert_eq!(reply, Ctcp::new("PING", Some("123".into())));
+
+        let reply = responder
+            .reply(&Ctcp::new("VERSION", None), &settings, VERSION, now)
+            .unwrap();
+        assert_eq!(reply.params.as_deref(), Some(VERSION));
+
+        let settings = config::Ctcp {
+            version: Some("custom".into()),
+            ..config::Ctcp::default()
+        };
+        let reply = responder
+            .reply(&Ctcp::new("VERSION", None), &settings, VERSION, now)
+            .unwrap();
+        assert_eq!(reply.params.as_deref(), Some("custom"));
+
+        assert!(
+            responder
+                .reply(&Ctcp::new("FINGER", None), &settings, VERSION, now)
+                .is_none()
+        );
+        let settings = config::Ctcp {
+            enabled: false,
+            ..config::Ctcp::default()
+        };
+        assert!(
+            responder
+                .reply(&Ctcp::new("PING", None), &settings, VERSION, now)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut responder = CtcpResponder::default();
+        let settings = config::Ctcp::default();
+        let query = Ctcp::new("CLIENTINFO", None);
+        let now = Instant::now();
+
+        for _ in 0..MAX_REPLIES {
+            assert!(responder.reply(&query, &settings, VERSION, now).is_some());
+        }
+        assert!(responder.reply(&query, &settings, VERSION, now).is_none());
+        assert!(
+            responder
+                .reply(&query, &settings, VERSION, now + REPLY_WINDOW)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_describe_reply() {
+        let sent = UNIX_EPOCH + Duration::from_secs(100);
+        let reply = Ctcp::new("PING", Some(ping_params(sent)));
+        assert_eq!(
+            describe_reply("alice", &reply, sent + Duration::from_millis(250)),
+            "CTCP PING reply from alice: 0.250s"
+        );
+        let reply = Ctcp::new("VERSION", Some("irssi".into()));
+        assert_eq!(
+            describe_reply("alice", &reply, sent),
+            "CTCP VERSION reply from alice: irssi"
+        );
+    }
+}
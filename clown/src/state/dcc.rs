@@ -0,0 +1,1234 @@
+use super::server_id::ServerID;
+use clown_core::ctcp::Ctcp;
+use clown_core::dcc::{self, DccChat, DccRequest, DccResume, DccSend};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncSeekExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// The other side has 5 minutes to connect
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(300);
+/// A finished transfer stays in the list for 30 seconds
+const FINISHED_DISPLAY: Duration = Duration::from_secs(30);
+/// An offer not accepted within 10 minutes is dropped
+const OFFER_TIMEOUT: Duration = Duration::from_secs(600);
+/// Offers received beyond this count are ignored until some are accepted or closed
+const MAX_OFFERS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Send,
+    Receive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferState {
+    /// Incoming offer, waiting for /dcc get
+    Offered,
+    /// Waiting for the other side to connect or to accept the resume
+    Waiting,
+    Transferring,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl TransferState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed(_) | Self::Cancelled)
+    }
+}
+
+impl std::fmt::Display for TransferState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Offered => write!(f, "offered"),
+            Self::Waiting => write!(f, "waiting"),
+            Self::Transferring => write!(f, "transferring"),
+            Self::Done => write!(f, "done"),
+            Self::Failed(reason) => write!(f, "failed: {reason}"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+pub struct Transfer {
+    pub id: usize,
+    pub server_id: ServerID,
+    pub nick: String,
+    pub direction: TransferDirection,
+    pub state: TransferState,
+    pub path: PathBuf,
+    pub size: Option<u64>,
+    pub transferred: u64,
+    /// The offer as sent or received
+    offer: DccSend,
+    /// Where a resumed transfer starts, read by the task when the peer connects
+    position: Arc<AtomicU64>,
+    created: Instant,
+    finished: Option<Instant>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Transfer {
+    pub fn filename(&self) -> &str {
+        &self.offer.filename
+    }
+
+    /// Percentage of the file already transferred
+    pub fn progress(&self) -> Option<u64> {
+        self.size
+            .filter(|v| *v > 0)
+            .map(|size| self.transferred.saturating_mul(100) / size)
+    }
+
+    fn finish(&mut self, state: TransferState) {
+        self.state = state;
+        self.finished = Some(Instant::now());
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    fn is_expired_offer(&self) -> bool {
+        self.state == TransferState::Offered && self.created.elapsed() >= OFFER_TIMEOUT
+    }
+
+    fn is_resume_of(&self, nick: &str, resume: &DccResume) -> bool {
+        self.nick.eq_ignore_ascii_case(nick)
+            && self.state == TransferState::Waiting
+            && self.offer.port == resume.port
+            && (resume.port != 0 || self.offer.token == resume.token)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatState {
+    /// Incoming offer, waiting for /dcc get
+    Offered,
+    /// Waiting for the other side to connect
+    Waiting,
+    Connected,
+    Closed,
+}
+
+impl ChatState {
+    pub fn is_finished(&self) -> bool {
+        *self == Self::Closed
+    }
+}
+
+impl std::fmt::Display for ChatState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Offered => write!(f, "offered"),
+            Self::Waiting => write!(f, "waiting"),
+            Self::Connected => write!(f, "connected"),
+            Self::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+/// Direct chat with a nick, shown in its own buffer
+pub struct ChatSession {
+    pub id: usize,
+    pub server_id: ServerID,
+    pub nick: String,
+    pub state: ChatState,
+    offer: DccChat,
+    created: Instant,
+    /// Lines to send to the nick
+    sender: Option<mpsc::UnboundedSender<String>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl ChatSession {
+    fn finish(&mut self) {
+        self.state = ChatState::Closed;
+        self.sender = None;
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+
+    fn is_expired_offer(&self) -> bool {
+        self.state == ChatState::Offered && self.created.elapsed() >= OFFER_TIMEOUT
+    }
+
+    fn is_active_with(&self, server_id: ServerID, nick: &str) -> bool {
+        self.server_id == server_id
+            && self.nick.eq_ignore_ascii_case(nick)
+            && !self.state.is_finished()
+    }
+}
+
+/// The buffer of a chat is the nick prefixed by '=', apart from the query with the same nick
+pub fn chat_buffer(nick: &str) -> String {
+    format!("={nick}")
+}
+
+/// The nick of a chat buffer
+pub fn chat_nick(buffer: &str) -> Option<&str> {
+    buffer.strip_prefix('=').filter(|v| !v.is_empty())
+}
+
+enum TaskEvent {
+    Started,
+    Progress(u64),
+    Line(String),
+    Finished(Result<u64, String>),
+}
+
+/// What happened to the transfers and chats since the last poll
+#[derive(Debug, PartialEq)]
+pub enum DccEvent {
+    /// Shown in the server buffer, or in the buffer of a chat
+    Info(ServerID, Option<String>, String),
+    /// Line received from the nick of a chat
+    ChatLine(ServerID, String, String),
+}
+
+/// Result of a DCC request received from a nick
+#[derive(Debug, PartialEq)]
+pub enum DccOutcome {
+    /// A new offer waits to be accepted
+    Offered(usize),
+    /// To send back to the nick
+    Reply(Ctcp),
+    Started(usize),
+    Ignored,
+}
+
+enum Peer {
+    Listen(TcpListener),
+    Connect(SocketAddr),
+}
+
+impl Peer {
+    async fn open(self) -> std::io::Result<TcpStream> {
+        let stream = tokio::time::timeout(CONNECT_TIMEOUT, async {
+            match self {
+                Self::Listen(listener) => listener.accept().await.map(|v| v.0),
+                Self::Connect(address) => TcpStream::connect(address).await,
+            }
+        })
+        .await;
+        stream.map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))?
+    }
+}
+
+/// Listens on every interface, the port is announced in the offer
+fn listen(address: IpAddr) -> std::io::Result<(TcpListener, u16)> {
+    let any = match address {
+        IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+    };
+    let listener = std::net::TcpListener::bind((any, 0))?;
+    listener.set_nonblocking(true)?;
+    let port = listener.local_addr()?.port();
+    Ok((TcpListener::from_std(listener)?, port))
+}
+
+/// An existing file is not overwritten, a number is added to the name
+fn unique_path(directory: &Path, filename: &str) -> PathBuf {
+    let mut path = directory.join(filename);
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{filename}.{counter}"));
+        counter += 1;
+    }
+    path
+}
+
+async fn send_task(
+    peer: Peer,
+    path: PathBuf,
+    position: Arc<AtomicU64>,
+    total: u64,
+    events: impl Fn(TaskEvent),
+) -> Result<u64, String> {
+    let stream = peer.open().await.map_err(|e| e.to_string())?;
+    events(TaskEvent::Started);
+    let position = position.load(Ordering::Relaxed);
+    let mut file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    file.seek(std::io::SeekFrom::Start(position))
+        .await
+        .map_err(|e| e.to_string())?;
+    dcc::send_file(stream, &mut file, position, total, &mut |v| {
+        events(TaskEvent::Progress(v))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn receive_task(
+    peer: Peer,
+    path: PathBuf,
+    position: u64,
+    size: Option<u64>,
+    events: impl Fn(TaskEvent),
+) -> Result<u64, String> {
+    let stream = peer.open().await.map_err(|e| e.to_string())?;
+    events(TaskEvent::Started);
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(position == 0)
+        .open(&path)
+        .await
+        .map_err(|e| e.to_string())?;
+    file.set_len(position).await.map_err(|e| e.to_string())?;
+    file.seek(std::io::SeekFrom::Start(position))
+        .await
+        .map_err(|e| e.to_string())?;
+    dcc::receive_file(stream, &mut file, position, size, &mut |v| {
+        events(TaskEvent::Progress(v))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn chat_task(
+    peer: Peer,
+    mut outgoing: mpsc::UnboundedReceiver<String>,
+    events: impl Fn(TaskEvent),
+) -> Result<(), String> {
+    let stream = peer.open().await.map_err(|e| e.to_string())?;
+    events(TaskEvent::Started);
+    dcc::chat(stream, &mut outgoing, &mut |line| {
+        events(TaskEvent::Line(line))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// File transfers and chats, they are kept when the connection to the server is lost
+pub struct DccManager {
+    transfers: Vec<Transfer>,
+    chats: Vec<ChatSession>,
+    next_id: usize,
+    event_sender: mpsc::UnboundedSender<(usize, TaskEvent)>,
+    event_receiver: mpsc::UnboundedReceiver<(usize, TaskEvent)>,
+    changed: bool,
+}
+
+impl Default for DccManager {
+    fn default() -> Self {
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        Self {
+            transfers: Vec::new(),
+            chats: Vec::new(),
+            next_id: 1,
+            event_sender,
+            event_receiver,
+            changed: false,
+        }
+    }
+}
+
+impl DccManager {
+    pub fn get(&self, id: usize) -> Option<&Transfer> {
+        self.transfers.iter().find(|v| v.id == id)
+    }
+
+    fn get_mut(&mut self, id: usize) -> Option<&mut Transfer> {
+        self.transfers.iter_mut().find(|v| v.id == id)
+    }
+
+    pub fn transfers(&self) -> impl Iterator<Item = &Transfer> {
+        self.transfers.iter()
+    }
+
+    pub fn get_chat(&self, id: usize) -> Option<&ChatSession> {
+        self.chats.iter().find(|v| v.id == id)
+    }
+
+    fn get_chat_mut(&mut self, id: usize) -> Option<&mut ChatSession> {
+        self.chats.iter_mut().find(|v| v.id == id)
+    }
+
+    pub fn chats(&self) -> impl Iterator<Item = &ChatSession> {
+        self.chats.iter()
+    }
+
+    /// Transfers shown in the list: the ones running and the ones recently finished
+    pub fn visible_transfers(&self) -> impl Iterator<Item = &Transfer> {
+        self.transfers.iter().filter(|v| {
+            v.finished
+                .is_none_or(|finished| finished.elapsed() < FINISHED_DISPLAY)
+        })
+    }
+
+    /// The list has to be drawn again
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// The transfers and the chats share their ids
+    fn new_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn add(&mut self, mut transfer: Transfer) -> usize {
+        let id = self.new_id();
+        transfer.id = id;
+        self.transfers.push(transfer);
+        self.changed = true;
+        id
+    }
+
+    fn add_chat(
+        &mut self,
+        server_id: ServerID,
+        nick: &str,
+        state: ChatState,
+        offer: DccChat,
+    ) -> usize {
+        let id = self.new_id();
+        self.chats.push(ChatSession {
+            id,
+            server_id,
+            nick: nick.to_string(),
+            state,
+            offer,
+            created: Instant::now(),
+            sender: None,
+            task: None,
+        });
+        id
+    }
+
+    fn spawn_chat(&mut self, id: usize, peer: Peer) {
+        let sender = self.event_sender.clone();
+        let (line_sender, lines) = mpsc::unbounded_channel();
+        if let Some(chat) = self.get_chat_mut(id) {
+            chat.state = ChatState::Waiting;
+            chat.sender = Some(line_sender);
+            chat.task = Some(tokio::spawn(async move {
+                let events = |event| {
+                    let _ = sender.send((id, event));
+                };
+                let result = chat_task(peer, lines, events).await;
+                let _ = sender.send((id, TaskEvent::Finished(result.map(|()| 0))));
+            }));
+        }
+    }
+
+    fn spawn_send(&mut self, id: usize, peer: Peer) {
+        let sender = self.event_sender.clone();
+        if let Some(transfer) = self.get_mut(id) {
+            let path = transfer.path.clone();
+            let position = transfer.position.clone();
+            let total = transfer.size.unwrap_or_default();
+            transfer.task = Some(tokio::spawn(async move {
+                let events = |event| {
+                    let _ = sender.send((id, event));
+                };
+                let result = send_task(peer, path, position, total, events).await;
+                let _ = sender.send((id, TaskEvent::Finished(result)));
+            }));
+        }
+    }
+
+    fn spawn_receive(&mut self, id: usize, peer: Peer) {
+        let sender = self.event_sender.clone();
+        if let Some(transfer) = self.get_mut(id) {
+            let path = transfer.path.clone();
+            let position = transfer.position.load(Ordering::Relaxed);
+            let size = transfer.size;
+            transfer.state = TransferState::Waiting;
+            transfer.task = Some(tokio::spawn(async move {
+                let events = |event| {
+                    let _ = sender.send((id, event));
+                };
+                let result = receive_task(peer, path, position, size, events).await;
+                let _ = sender.send((id, TaskEvent::Finished(result)));
+            }));
+        }
+    }
+
+    /// Offers a file to a nick, the CTCP query is returned to be sent.
+    /// A passive offer lets the nick listen, when it cannot be reached.
+    pub fn offer(
+        &mut self,
+        server_id: ServerID,
+        nick: &str,
+        path: &Path,
+        address: IpAddr,
+        passive: bool,
+    ) -> anyhow::Result<Ctcp> {
+        let size = std::fs::metadata(path)?.len();
+        let filename = path
+            .file_name()
+            .map(|v| v.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Invalid file {}", path.display()))?;
+        let (listener, port) = if passive {
+            (None, 0)
+        } else {
+            let (listener, port) = listen(address)?;
+            (Some(listener), port)
+        };
+        let id = self.add(Transfer {
+            id: 0,
+            server_id,
+            nick: nick.to_string(),
+            direction: TransferDirection::Send,
+            state: TransferState::Waiting,
+            path: path.to_path_buf(),
+            size: Some(size),
+            transferred: 0,
+            offer: DccSend {
+                filename,
+                address,
+                port,
+                size: Some(size),
+                token: None,
+            },
+            position: Arc::default(),
+            created: Instant::now(),
+            finished: None,
+            task: None,
+        });
+        if let Some(transfer) = self.get_mut(id)
+            && passive
+        {
+            transfer.offer.token = Some(id.to_string());
+        }
+        if let Some(listener) = listener {
+            self.spawn_send(id, Peer::Listen(listener));
+        }
+        self.get(id)
+            .map(|v| DccRequest::Send(v.offer.clone()).to_ctcp())
+            .ok_or_else(|| anyhow::anyhow!("Invalid transfer {id}"))
+    }
+
+    /// Offers a chat to a nick, the CTCP query is returned to be sent
+    pub fn offer_chat(
+        &mut self,
+        server_id: ServerID,
+        nick: &str,
+        address: IpAddr,
+        passive: bool,
+    ) -> anyhow::Result<Ctcp> {
+        if self.chats.iter().any(|v| v.is_active_with(server_id, nick)) {
+            anyhow::bail!("A DCC chat with {nick} is already open");
+        }
+        let (listener, port) = if passive {
+            (None, 0)
+        } else {
+            let (listener, port) = listen(address)?;
+            (Some(listener), port)
+        };
+        let offer = DccChat {
+            address,
+            port,
+            token: None,
+        };
+        let id = self.add_chat(server_id, nick, ChatState::Waiting, offer);
+        if let Some(chat) = self.get_chat_mut(id)
+            && passive
+        {
+            chat.offer.token = Some(id.to_string());
+        }
+        if let Some(listener) = listener {
+            self.spawn_chat(id, Peer::Listen(listener));
+        }
+        self.get_chat(id)
+            .map(|v| DccRequest::Chat(v.offer.clone()).to_ctcp())
+            .ok_or_else(|| anyhow::anyhow!("Invalid chat {id}"))
+    }
+
+    /// Sends a line to the nick of a connected chat
+    pub fn send_chat(
+        &mut self,
+        server_id: ServerID,
+        nick: &str,
+        line: String,
+    ) -> anyhow::Result<()> {
+        let sender = self
+            .chats
+            .iter()
+            .rev()
+            .filter(|v| v.is_active_with(server_id, nick) && v.state == ChatState::Connected)
+            .find_map(|v| v.sender.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("No DCC chat connected with {nick}"))?;
+        sender
+            .send(line)
+            .map_err(|_| anyhow::anyhow!("The DCC chat with {nick} is closed"))
+    }
+
+    /// Closes the chats with a nick, when its buffer is closed
+    pub fn close_chat(&mut self, server_id: ServerID, nick: &str) -> bool {
+        let mut closed = false;
+        for chat in self
+            .chats
+            .iter_mut()
+            .filter(|v| v.is_active_with(server_id, nick))
+        {
+            chat.finish();
+            closed = true;
+        }
+        closed
+    }
+
+    /// Offers received and not yet accepted or refused
+    fn pending_offers(&self) -> usize {
+        self.transfers
+            .iter()
+            .filter(|v| v.state == TransferState::Offered)
+            .count()
+            + self
+                .chats
+                .iter()
+                .filter(|v| v.state == ChatState::Offered)
+                .count()
+    }
+
+    /// Requests received from a nick: new offers, answers to our offers and resumes
+    pub fn handle_request(
+        &mut self,
+        server_id: ServerID,
+        nick: &str,
+        request: DccRequest,
+        address: IpAddr,
+    ) -> anyhow::Result<DccOutcome> {
+        match request {
+            DccRequest::Send(send) => {
+                //Answer to a passive offer, the nick is listening
+                if send.port != 0
+                    && send.token.is_some()
+                    && let Some(id) = self
+                        .transfers
+                        .iter()
+                        .find(|v| {
+                            v.nick.eq_ignore_ascii_case(nick)
+                                && v.direction == TransferDirection::Send
+                                && v.state == TransferState::Waiting
+                                && v.offer.port == 0
+                                && v.offer.token == send.token
+                        })
+                        .map(|v| v.id)
+                {
+                    self.spawn_send(id, Peer::Connect(SocketAddr::new(send.address, send.port)));
+                    return Ok(DccOutcome::Started(id));
+                }
+                if self.pending_offers() >= MAX_OFFERS {
+                    return Ok(DccOutcome::Ignored);
+                }
+                let size = send.size.filter(|v| *v > 0);
+                let id = self.add(Transfer {
+                    id: 0,
+                    server_id,
+                    nick: nick.to_string(),
+                    direction: TransferDirection::Receive,
+                    state: TransferState::Offered,
+                    path: PathBuf::from(dcc::safe_filename(&send.filename)),
+                    size,
+                    transferred: 0,
+                    offer: send,
+                    position: Arc::default(),
+                    created: Instant::now(),
+                    finished: None,
+                    task: None,
+                });
+                Ok(DccOutcome::Offered(id))
+            }
+            DccRequest::Chat(chat) => {
+                //Answer to a passive offer
+                if chat.port != 0
+                    && chat.token.is_some()
+                    && let Some(id) = self
+                        .chats
+                        .iter()
+                        .find(|v| {
+                            v.is_active_with(server_id, nick)
+                                && v.state == ChatState::Waiting
+                                && v.offer.port == 0
+                                && v.offer.token == chat.token
+                        })
+                        .map(|v| v.id)
+                {
+                    self.spawn_chat(id, Peer::Connect(SocketAddr::new(chat.address, chat.port)));
+                    return Ok(DccOutcome::Started(id));
+                }
+                if self.pending_offers() >= MAX_OFFERS {
+                    return Ok(DccOutcome::Ignored);
+                }
+                Ok(DccOutcome::Offered(self.add_chat(
+                    server_id,
+                    nick,
+                    ChatState::Offered,
+                    chat,
+                )))
+            }
+            DccRequest::Resume(resume) => {
+                let Some(transfer) = self.transfers.iter_mut().find(|v| {
+                    v.direction == TransferDirection::Send && v.is_resume_of(nick, &resume)
+                }) else {
+                    return Ok(DccOutcome::Ignored);
+                };
+                let position = resume.position.min(transfer.size.unwrap_or_default());
+                transfer.position.store(position, Ordering::Relaxed);
+                Ok(DccOutcome::Reply(
+                    DccRequest::Accept(DccResume { position, ..resume }).to_ctcp(),
+                ))
+            }
+            DccRequest::Accept(resume) => {
+                let Some(transfer) = self.transfers.iter_mut().find(|v| {
+                    v.direction == TransferDirection::Receive && v.is_resume_of(nick, &resume)
+                }) else {
+                    return Ok(DccOutcome::Ignored);
+                };
+                transfer.position.store(resume.position, Ordering::Relaxed);
+                let id = transfer.id;
+                self.start_receive(id, address).map(|reply| match reply {
+                    Some(reply) => DccOutcome::Reply(reply),
+                    None => DccOutcome::Started(id),
+                })
+            }
+        }
+    }
+
+    /// Connects to the sender, or listens for a passive offer and returns the answer
+    fn start_receive(&mut self, id: usize, address: IpAddr) -> anyhow::Result<Option<Ctcp>> {
+        let transfer = self
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Invalid transfer {id}"))?;
+        let offer = transfer.offer.clone();
+        if offer.port != 0 {
+            self.spawn_receive(
+                id,
+                Peer::Connect(SocketAddr::new(offer.address, offer.port)),
+            );
+            return Ok(None);
+        }
+        let (listener, port) = listen(address)?;
+        self.spawn_receive(id, Peer::Listen(listener));
+        Ok(Some(
+            DccRequest::Send(DccSend {
+                address,
+                port,
+                ..offer
+            })
+            .to_ctcp(),
+        ))
+    }
+
+    /// Connects to the nick, or listens for a passive offer and returns the answer
+    fn accept_chat(&mut self, id: usize, address: IpAddr) -> anyhow::Result<Option<Ctcp>> {
+        let offer = self
+            .get_chat(id)
+            .filter(|v| v.state == ChatState::Offered)
+            .map(|v| v.offer.clone())
+            .ok_or_else(|| anyhow::anyhow!("No offer {id} waiting"))?;
+        if offer.port != 0 {
+            self.spawn_chat(
+                id,
+                Peer::Connect(SocketAddr::new(offer.address, offer.port)),
+            );
+            return Ok(None);
+        }
+        let (listener, port) = listen(address)?;
+        self.spawn_chat(id, Peer::Listen(listener));
+        Ok(Some(
+            DccRequest::Chat(DccChat {
+                address,
+                port,
+                ..offer
+            })
+            .to_ctcp(),
+        ))
+    }
+
+    /// An earlier receive of the same offer stopped before its end
+    fn is_partial(&self, offer: &Transfer, path: &Path) -> bool {
+        self.transfers.iter().any(|v| {
+            v.id != offer.id
+                && v.direction == TransferDirection::Receive
+                && matches!(v.state, TransferState::Failed(_) | TransferState::Cancelled)
+                && v.path == path
+                && v.nick.eq_ignore_ascii_case(&offer.nick)
+                && v.offer.filename == offer.offer.filename
+                && v.size == offer.size
+        })
+    }
+
+    /// Accepts an offer, a partial file is resumed when asked
+    /// or when it was left by an earlier receive of the same offer.
+    /// Returns the CTCP query to send back, if any.
+    pub fn accept(
+        &mut self,
+        id: usize,
+        download_dir: &Path,
+        address: IpAddr,
+        resume: bool,
+    ) -> anyhow::Result<Option<Ctcp>> {
+        if self.get_chat(id).is_some() {
+            return self.accept_chat(id, address);
+        }
+        self.changed = true;
+        let transfer = self
+            .get(id)
+            .filter(|v| v.state == TransferState::Offered)
+            .ok_or_else(|| anyhow::anyhow!("No offer {id} waiting"))?;
+        std::fs::create_dir_all(download_dir)?;
+        let filename = dcc::safe_filename(&transfer.offer.filename);
+        let path = download_dir.join(&filename);
+        let existing = std::fs::metadata(&path)
+            .map(|v| v.len())
+            .unwrap_or_default();
+        let resume = resume || self.is_partial(transfer, &path);
+        let transfer = self
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Invalid transfer {id}"))?;
+        if resume
+            && existing > 0
+            && let Some(size) = transfer.size
+            && existing < size
+        {
+            transfer.path = path;
+            transfer.state = TransferState::Waiting;
+            transfer.transferred = existing;
+            transfer.position.store(existing, Ordering::Relaxed);
+            return Ok(Some(
+                DccRequest::Resume(DccResume {
+                    filename: transfer.offer.filename.clone(),
+                    port: transfer.offer.port,
+                    position: existing,
+                    token: transfer.offer.token.clone(),
+                })
+                .to_ctcp(),
+            ));
+        }
+        transfer.path = unique_path(download_dir, &filename);
+        self.start_receive(id, address)
+    }
+
+    /// Rejects an offer, stops a transfer or closes a chat
+    pub fn close(&mut self, id: usize) -> bool {
+        if let Some(chat) = self.get_chat_mut(id) {
+            if chat.state.is_finished() {
+                return false;
+            }
+            chat.finish();
+            return true;
+        }
+        let Some(transfer) = self.get_mut(id).filter(|v| !v.state.is_finished()) else {
+            return false;
+        };
+        transfer.finish(TransferState::Cancelled);
+        self.changed = true;
+        true
+    }
+
+    fn poll_chat(chat: &mut ChatSession, event: TaskEvent) -> Option<DccEvent> {
+        let buffer = chat_buffer(&chat.nick);
+        match event {
+            TaskEvent::Started => {
+                chat.state = ChatState::Connected;
+                Some(DccEvent::Info(
+                    chat.server_id,
+                    Some(buffer),
+                    format!("DCC chat with {} connected", chat.nick),
+                ))
+            }
+            TaskEvent::Line(line) => {
+                Some(DccEvent::ChatLine(chat.server_id, chat.nick.clone(), line))
+            }
+            TaskEvent::Progress(_) => None,
+            TaskEvent::Finished(result) => {
+                //A chat never connected has no buffer
+                let buffer = (chat.state == ChatState::Connected).then_some(buffer);
+                let message = match result {
+                    Ok(_) => format!("DCC chat with {} closed", chat.nick),
+                    Err(reason) => format!("DCC chat with {} closed, {reason}", chat.nick),
+                };
+                chat.finish();
+                Some(DccEvent::Info(chat.server_id, buffer, message))
+            }
+        }
+    }
+
+    /// Offers not accepted in time are dropped
+    fn expire_offers(&mut self, events: &mut Vec<DccEvent>) {
+        for transfer in self.transfers.iter_mut().filter(|v| v.is_expired_offer()) {
+            transfer.finish(TransferState::Failed("offer expired".to_string()));
+            events.push(DccEvent::Info(
+                transfer.server_id,
+                None,
+                format!(
+                    "DCC {}: offer of {} from {} expired",
+                    transfer.id,
+                    transfer.filename(),
+                    transfer.nick
+                ),
+            ));
+            self.changed = true;
+        }
+        for chat in self.chats.iter_mut().filter(|v| v.is_expired_offer()) {
+            chat.finish();
+            events.push(DccEvent::Info(
+                chat.server_id,
+                None,
+                format!("DCC {}: chat offer from {} expired", chat.id, chat.nick),
+            ));
+        }
+    }
+
+    /// Follows the running transfers and chats
+    pub fn poll(&mut self) -> Vec<DccEvent> {
+        let mut events = Vec::new();
+        self.expire_offers(&mut events);
+        while let Ok((id, event)) = self.event_receiver.try_recv() {
+            if let Some(chat) = self.get_chat_mut(id) {
+                if !chat.state.is_finished()
+                    && let Some(event) = Self::poll_chat(chat, event)
+                {
+                    events.push(event);
+                }
+                continue;
+            }
+            self.changed = true;
+            let Some(transfer) = self.get_mut(id).filter(|v| !v.state.is_finished()) else {
+                continue;
+            };
+            match event {
+                TaskEvent::Started => transfer.state = TransferState::Transferring,
+                TaskEvent::Progress(transferred) => transfer.transferred = transferred,
+                TaskEvent::Line(_) => {}
+                TaskEvent::Finished(result) => {
+                    let verb = match transfer.direction {
+                        TransferDirection::Send => "sent to",
+                        TransferDirection::Receive => "received from",
+                    };
+                    let message = match result {
+                        Ok(transferred) => {
+                            transfer.transferred = transferred;
+                            transfer.finish(TransferState::Done);
+                            format!(
+                                "DCC {}: {} {verb} {}",
+                                transfer.id,
+                                transfer.path.display(),
+                                transfer.nick
+                            )
+                        }
+                        Err(reason) => {
+                            let message = format!(
+                                "DCC {}: {} not {verb} {}, {reason}",
+                                transfer.id,
+                                transfer.filename(),
+                                transfer.nick
+                            );
+                            transfer.finish(TransferState::Failed(reason));
+                            message
+                        }
+                    };
+                    events.push(DccEvent::Info(transfer.server_id, None, message));
+                }
+            }
+        }
+        events
+    }
+}
+
+/// Address announced in the offers when none is set in the config:
+/// the one of the interface used to reach the server
+pub fn local_address(server_address: &str, port: u16) -> Option<IpAddr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect((server_address, port)).ok()?;
+    socket.local_addr().ok().map(|v| v.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    /// Two clients exchange their CTCP queries until the transfers are finished
+    async fn run(
+        sender: &mut DccManager,
+        receiver: &mut DccManager,
+        id: usize,
+        receiver_id: usize,
+    ) {
+        for _ in 0..500 {
+            sender.poll();
+            receiver.poll();
+            if sender.get(id).is_some_and(|v| v.state.is_finished())
+                && receiver
+                    .get(receiver_id)
+                    .is_some_and(|v| v.state.is_finished())
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Transfer not finished");
+    }
+
+    fn request(ctcp: &Ctcp) -> DccRequest {
+        DccRequest::parse(ctcp).unwrap()
+    }
+
+    fn offered_id(outcome: DccOutcome) -> usize {
+        match outcome {
+            DccOutcome::Offered(id) => id,
+            _ => panic!("Not an offer: {outcome:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_active_send() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("notes.txt");
+        std::fs::write(&source, b"hello from clown".repeat(1000)).unwrap();
+        let download = dir.path().join("downloads");
+
+        let mut alice = DccManager::default();
+        let mut bob = DccManager::default();
+        let offer = alice
+            .offer(ServerID::new(0), "bob", &source, LOCALHOST, false)
+            .unwrap();
+        let received = offered_id(
+            bob.handle_request(ServerID::new(0), "alice", request(&offer), LOCALHOST)
+                .unwrap(),
+        );
+        assert_eq!(bob.get(received).unwrap().state, TransferState::Offered);
+        assert_eq!(
+            bob.accept(received, &download, LOCALHOST, false).unwrap(),
+            None
+        );
+
+        run(&mut alice, &mut bob, 1, received).await;
+        assert_eq!(alice.get(1).unwrap().state, TransferState::Done);
+        assert_eq!(bob.get(received).unwrap().state, TransferState::Done);
+        assert_eq!(bob.get(received).unwrap().progress(), Some(100));
+        assert_eq!(
+            std::fs::read(download.join("notes.txt")).unwrap(),
+            std::fs::read(&source).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passive_send_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("log.txt");
+        let content = b"0123456789".repeat(500);
+        std::fs::write(&source, &content).unwrap();
+        let download = dir.path().join("downloads");
+        std::fs::create_dir_all(&download).unwrap();
+        std::fs::write(download.join("log.txt"), content.get(..1234).unwrap()).unwrap();
+
+        let mut alice = DccManager::default();
+        let mut bob = DccManager::default();
+        let offer = alice
+            .offer(ServerID::new(0), "bob", &source, LOCALHOST, true)
+            .unwrap();
+        let received = offered_id(
+            bob.handle_request(ServerID::new(0), "alice", request(&offer), LOCALHOST)
+                .unwrap(),
+        );
+
+        //The partial file is resumed
+        let resume = bob
+            .accept(received, &download, LOCALHOST, true)
+            .unwrap()
+            .unwrap();
+        let DccOutcome::Reply(accept) = alice
+            .handle_request(ServerID::new(0), "bob", request(&resume), LOCALHOST)
+            .unwrap()
+        else {
+            panic!("Resume not accepted");
+        };
+        //Bob listens and gives its port
+        let DccOutcome::Reply(answer) = bob
+            .handle_request(ServerID::new(0), "alice", request(&accept), LOCALHOST)
+            .unwrap()
+        else {
+            panic!("No answer to the passive offer");
+        };
+        assert_eq!(
+            alice
+                .handle_request(ServerID::new(0), "bob", request(&answer), LOCALHOST)
+                .unwrap(),
+            DccOutcome::Started(1)
+        );
+
+        run(&mut alice, &mut bob, 1, received).await;
+        assert_eq!(bob.get(received).unwrap().state, TransferState::Done);
+        assert_eq!(std::fs::read(download.join("log.txt")).unwrap(), content);
+    }
+
+    /// Polls until an event is found
+    async fn wait_event(manager: &mut DccManager, found: impl Fn(&DccEvent) -> bool) {
+        for _ in 0..500 {
+            if manager.poll().iter().any(&found) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Event not received");
+    }
+
+    #[tokio::test]
+    async fn test_passive_chat() {
+        let server_id = ServerID::new(0);
+        let mut alice = DccManager::default();
+        let mut bob = DccManager::default();
+        let offer = alice.offer_chat(server_id, "bob", LOCALHOST, true).unwrap();
+        assert!(alice.offer_chat(server_id, "bob", LOCALHOST, true).is_err());
+        let chat_id = offered_id(
+            bob.handle_request(server_id, "alice", request(&offer), LOCALHOST)
+                .unwrap(),
+        );
+        assert_eq!(bob.get_chat(chat_id).unwrap().state, ChatState::Offered);
+
+        //Bob listens and gives its port
+        let answer = bob
+            .accept(chat_id, Path::new("unused"), LOCALHOST, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            alice
+                .handle_request(server_id, "bob", request(&answer), LOCALHOST)
+                .unwrap(),
+            DccOutcome::Started(1)
+        );
+        let connected =
+            |v: &DccEvent| matches!(v, DccEvent::Info(_, Some(buffer), _) if buffer == "=alice");
+        wait_event(&mut bob, connected).await;
+        wait_event(&mut alice, |v| matches!(v, DccEvent::Info(_, Some(_), _))).await;
+
+        alice.send_chat(server_id, "bob", "hello".into()).unwrap();
+        assert!(alice.send_chat(server_id, "carol", "hello".into()).is_err());
+        wait_event(&mut bob, |v| {
+            *v == DccEvent::ChatLine(server_id, "alice".into(), "hello".into())
+        })
+        .await;
+
+        //The chat ends when its buffer is closed
+        assert!(alice.close_chat(server_id, "bob"));
+        wait_event(
+            &mut bob,
+            |v| matches!(v, DccEvent::Info(_, _, message) if message.ends_with("closed")),
+        )
+        .await;
+        assert_eq!(bob.get_chat(chat_id).unwrap().state, ChatState::Closed);
+    }
+
+    #[test]
+    fn test_chat_buffer() {
+        assert_eq!(chat_buffer("alice"), "=alice");
+        assert_eq!(chat_nick("=alice"), Some("alice"));
+        assert_eq!(chat_nick("alice"), None);
+        assert_eq!(chat_nick("="), None);
+    }
+
+    #[tokio::test]
+    async fn test_close_offer() {
+        let mut bob = DccManager::default();
+        let offer = DccRequest::Send(DccSend {
+            filename: "../secret".into(),
+            address: LOCALHOST,
+            port: 1,
+            size: Some(10),
+            token: None,
+        });
+        let id = offered_id(
+            bob.handle_request(ServerID::new(0), "alice", offer, LOCALHOST)
+                .unwrap(),
+        );
+        assert_eq!(bob.get(id).unwrap().path, PathBuf::from("secret"));
+        assert!(bob.close(id));
+        assert!(!bob.close(id));
+        assert_eq!(bob.get(id).unwrap().state, TransferState::Cancelled);
+    }
+
+    fn passive_offer(filename: &str) -> DccRequest {
+        DccRequest::Send(DccSend {
+            filename: filename.into(),
+            address: LOCALHOST,
+            port: 0,
+            size: Some(10),
+            token: Some("1".into()),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_offers_limited_and_expired() {
+        let mut bob = DccManager::default();
+        for _ in 0..MAX_OFFERS {
+            offered_id(
+                bob.handle_request(ServerID::new(0), "alice", passive_offer("a"), LOCALHOST)
+                    .unwrap(),
+            );
+        }
+        assert_eq!(
+            bob.handle_request(ServerID::new(0), "alice", passive_offer("a"), LOCALHOST)
+                .unwrap(),
+            DccOutcome::Ignored
+        );
+
+        let expired = Instant::now().checked_sub(OFFER_TIMEOUT).unwrap();
+        bob.transfers[0].created = expired;
+        let events = bob.poll();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(bob.transfers[0].state, TransferState::Failed(_)));
+        offered_id(
+            bob.handle_request(ServerID::new(0), "alice", passive_offer("a"), LOCALHOST)
+                .unwrap(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("log.txt"), b"01234").unwrap();
+        let mut bob = DccManager::default();
+        let first = offered_id(
+            bob.handle_request(
+                ServerID::new(0),
+                "alice",
+                passive_offer("log.txt"),
+                LOCALHOST,
+            )
+            .unwrap(),
+        );
+        //Another file with the same name is not resumed
+        let answer = bob.accept(first, dir.path(), LOCALHOST, false).unwrap();
+        assert!(matches!(
+            answer.as_ref().and_then(DccRequest::parse),
+            Some(DccRequest::Send(_))
+        ));
+        assert_ne!(bob.get(first).unwrap().path, dir.path().join("log.txt"));
+
+        //The file left by a cancelled receive of the same offer is resumed
+        bob.get_mut(first).unwrap().path = dir.path().join("log.txt");
+        bob.close(first);
+        let second = offered_id(
+            bob.handle_request(
+                ServerID::new(0),
+                "alice",
+                passive_offer("log.txt"),
+                LOCALHOST,
+            )
+            .unwrap(),
+        );
+        let answer = bob.accept(second, dir.path(), LOCALHOST, false).unwrap();
+        assert!(matches!(
+            answer.as_ref().and_then(DccRequest::parse),
+            Some(DccRequest::Resume(DccResume { position: 5, .. }))
+        ));
+    }
+}
@@ -0,0 +1,79 @@
+/// Endpoints of a server, the next one is tried when a connection fails
+#[derive(Debug, Default)]
+pub struct EndpointRotation {
+    /// Endpoint which has completed the registration, None until one has
+    working: Option<usize>,
+    /// Endpoint of the last attempt
+    current: usize,
+}
+
+impl EndpointRotation {
+    /// The attempt is 0 after a successful connection, the endpoint which worked is kept.
+    /// Before any has worked, the first attempt is spread at random when asked.
+    pub fn select(&mut self, attempt: usize, count: usize, random: Option<u64>) -> usize {
+        if count == 0 {
+            return 0;
+        }
+        let working = self.working.unwrap_or_default();
+        self.current = if attempt == 0 {
+            match (self.working, random) {
+                (None, Some(random)) => usize::try_from(random % count as u64).unwrap_or_default(),
+                _ => working % count,
+            }
+        } else if let Some(random) = random
+            && count > 1
+        {
+            //Never the endpoint which has just failed
+            let offset = usize::try_from(random % (count as u64 - 1)).unwrap_or_default();
+            (self.current + 1 + offset) % count
+        } else {
+            (working + attempt) % count
+        };
+        self.current
+    }
+
+    /// The endpoint of the last attempt is used first the next time
+    pub fn set_working(&mut self) {
+        self.working = Some(self.current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_order() {
+        let mut rotation = EndpointRotation::default();
+        assert_eq!(rotation.select(0, 3, None), 0);
+        assert_eq!(rotation.select(1, 3, None), 1);
+        assert_eq!(rotation.select(2, 3, None), 2);
+        assert_eq!(rotation.select(3, 3, None), 0);
+        assert_eq!(rotation.select(4, 3, None), 1);
+
+        rotation.set_working();
+        assert_eq!(rotation.select(0, 3, None), 1);
+        assert_eq!(rotation.select(1, 3, None), 2);
+        assert_eq!(rotation.select(0, 1, None), 0);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rotation = EndpointRotation::default();
+        //The first connection is spread over the endpoints
+        let mut previous = rotation.select(0, 3, Some(7));
+        assert_eq!(previous, 1);
+        assert_eq!(rotation.select(0, 3, Some(5)), 2);
+        for random in 0..20 {
+            let current = rotation.select(1, 3, Some(random));
+            assert_ne!(current, previous);
+            previous = current;
+        }
+        assert_eq!(rotation.select(1, 1, Some(3)), 0);
+
+        //The endpoint which worked is kept
+        rotation.select(0, 3, Some(2));
+        rotation.set_working();
+        assert_eq!(rotation.select(0, 3, Some(7)), 2);
+    }
+}
@@ -0,0 +1,222 @@
+use ahash::AHashMap;
+use clown_core::command::Command;
+use clown_core::ctcp::Ctcp;
+use clown_core::message::ServerMessage;
+use clown_core::response::Response;
+
+/// Number of messages requested when joining a channel
+pub const HISTORY_LIMIT: u16 = 100;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HistoryKind {
+    Message,
+    Action,
+    Notice,
+}
+
+/// A message replayed by the server (draft/chathistory)
+#[derive(Debug, PartialEq, Clone)]
+pub struct HistoryMessage {
+    pub time: std::time::SystemTime,
+    pub msgid: Option<String>,
+    pub reply_to: Option<String>,
+    pub kind: HistoryKind,
+    pub source: String,
+    pub target: String,
+    pub content: String,
+}
+
+impl HistoryMessage {
+    pub fn from_server_message(message: &ServerMessage) -> Option<Self> {
+        let source = message.source()?.to_string();
+        let (kind, target, content) = match message.reply() {
+            Response::Cmd(Command::PrivMsg(target, content)) => {
+                match Ctcp::parse(&content) {
+                    Some(ctcp) if ctcp.is_action() => {
+                        (HistoryKind::Action, target, ctcp.params.unwrap_or_default())
+                    }
+                    //The other CTCP queries are not part of the conversation
+                    Some(_) => return None,
+                    None => (HistoryKind::Message, target, content),
+                }
+            }
+            Response::Cmd(Command::Notice(target, content)) => {
+                (HistoryKind::Notice, target, content)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            time: message
+                .tag("time")
+                .and_then(|v| parse_server_time(&v))
+                .unwrap_or_else(std::time::SystemTime::now),
+            msgid: message.tag("msgid").map(|v| v.to_string()),
+            reply_to: message.tag("+draft/reply").map(|v| v.to_string()),
+            kind,
+            source,
+            target,
+            content,
+        })
+    }
+}
+
+/// server-time format: 2011-10-19T16:40:51.620Z
+pub fn parse_server_time(time: &str) -> Option<std::time::SystemTime> {
+    chrono::DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(std::time::SystemTime::from)
+}
+
+pub fn format_server_time(time: std::time::SystemTime) -> String {
+    let time: chrono::DateTime<chrono::Utc> = time.into();
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// ZNC playback module: seconds since UNIX_EPOCH
+pub fn format_playback_time(time: std::time::SystemTime) -> String {
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|v| v.as_secs_f64())
+        .unwrap_or_default();
+    format!("{seconds:.3}")
+}
+
+/// Messages replayed with draft/chathistory or by the ZNC playback module
+fn is_history_kind(kind: &str) -> bool {
+    kind.eq_ignore_ascii_case("chathistory") || kind.eq_ignore_ascii_case("znc.in/playback")
+}
+
+#[derive(Debug)]
+struct Batch {
+    kind: String,
+    parameters: Vec<String>,
+    messages: Vec<HistoryMessage>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FinishedBatch {
+    pub kind: String,
+    pub parameters: Vec<String>,
+    pub messages: Vec<HistoryMessage>,
+}
+
+impl FinishedBatch {
+    pub fn is_history(&self) -> bool {
+        is_history_kind(&self.kind)
+    }
+}
+
+/// Keeps the messages of the opened batches until the server closes them
+#[derive(Debug, Default)]
+pub struct BatchCollector {
+    batches: AHashMap<String, Batch>,
+}
+
+impl BatchCollector {
+    pub fn start(&mut self, reference: &str, kind: &str, parameters: &[String]) {
+        self.batches.insert(
+            reference.to_string(),
+            Batch {
+                kind: kind.to_string(),
+                parameters: parameters.to_vec(),
+                messages: Vec::new(),
+            },
+        );
+    }
+
+    pub fn is_history(&self, reference: &str) -> bool {
+        self.batches
+            .get(reference)
+            .is_some_and(|v| is_history_kind(&v.kind))
+    }
+
+    pub fn push(&mut self, reference: &str, message: HistoryMessage) {
+        if let Some(batch) = self.batches.get_mut(reference) {
+            batch.messages.push(message);
+        }
+    }
+
+    pub fn end(&mut self, reference: &str) -> Option<FinishedBatch> {
+        self.batches.remove(reference).map(|batch| FinishedBatch {
+            kind: batch.kind,
+            parameters: batch.parameters,
+            messages: batch.messages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clown_parser::message::create_message;
+
+    fn server_message(line: &[u8]) -> ServerMessage {
+        ServerMessage::new(create_message(line).unwrap())
+    }
+
+    #[test]
+    fn test_server_time() {
+        let time = parse_server_time("2024-05-20T14:30:05.120Z").unwrap();
+        assert_eq!(format_server_time(time), "2024-05-20T14:30:05.120Z");
+        assert!(parse_server_time("not a time").is_none());
+        assert_eq!(format_playback_time(time), "1716215405.120");
+    }
+
+    #[test]
+    fn test_history_from_privmsg() {
+        let message = server_message(
+            b"@batch=1;msgid=abc;time=2024-05-20T14:30:05.000Z :alice!a@host PRIVMSG #rust :hello",
+        );
+        let history = HistoryMessage::from_server_message(&message).unwrap();
+        assert_eq!(history.kind, HistoryKind::Message);
+        assert_eq!(history.msgid.as_deref(), Some("abc"));
+        assert_eq!(history.source, "alice");
+        assert_eq!(history.target, "#rust");
+        assert_eq!(history.content, "hello");
+        assert_eq!(
+            history.time,
+            parse_server_time("2024-05-20T14:30:05.000Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_history_from_action() {
+        let message = server_message(b":alice!a@host PRIVMSG #rust :\x01ACTION waves\x01");
+        let history = HistoryMessage::from_server_message(&message).unwrap();
+        assert_eq!(history.kind, HistoryKind::Action);
+        assert_eq!(history.content, "waves");
+        assert_eq!(history.msgid, None);
+    }
+
+    #[test]
+    fn test_history_ignores_other_commands() {
+        let message = server_message(b":alice!a@host JOIN #rust");
+        assert!(HistoryMessage::from_server_message(&message).is_none());
+    }
+
+    #[test]
+    fn test_batch_collector() {
+        let mut collector = BatchCollector::default();
+        collector.start("ref", "chathistory", &["#rust".to_string()]);
+        collector.start("other", "netsplit", &[]);
+        collector.start("znc", "znc.in/playback", &["#rust".to_string()]);
+        assert!(collector.is_history("ref"));
+        assert!(collector.is_history("znc"));
+        assert!(!collector.is_history("other"));
+        assert!(!collector.is_history("unknown"));
+
+        let message = server_message(b":alice!a@host PRIVMSG #rust :hello");
+        collector.push(
+            "ref",
+            HistoryMessage::from_server_message(&message).unwrap(),
+        );
+
+        let batch = collector.end("ref").unwrap();
+        assert!(batch.is_history());
+        assert_eq!(batch.kind, "chathistory");
+        assert_eq!(batch.parameters, vec!["#rust".to_string()]);
+        assert_eq!(batch.messages.len(), 1);
+        assert!(collector.end("ref").is_none());
+    }
+}
@@ -0,0 +1,84 @@
+/// The oldest invitations are forgotten
+const MAX_PENDING_INVITES: usize = 20;
+
+/// Invitation to a channel, waiting for /accept
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invite {
+    pub source: String,
+    pub channel: String,
+}
+
+/// An entry of the allowlist is a channel or the nickname of the one inviting
+pub fn is_allowed(allowlist: &[String], source: &str, channel: &str) -> bool {
+    allowlist
+        .iter()
+        .any(|v| v.eq_ignore_ascii_case(channel) || v.eq_ignore_ascii_case(source))
+}
+
+#[derive(Debug, Default)]
+pub struct PendingInvites {
+    invites: Vec<Invite>,
+}
+
+impl PendingInvites {
+    /// A new invitation to the same channel replaces the previous one
+    pub fn push(&mut self, source: String, channel: String) {
+        self.invites
+            .retain(|v| !v.channel.eq_ignore_ascii_case(&channel));
+        self.invites.push(Invite { source, channel });
+        if self.invites.len() > MAX_PENDING_INVITES {
+            self.invites.remove(0);
+        }
+    }
+
+    /// The invitation to the channel, or the last one received
+    pub fn take(&mut self, channel: Option<&str>) -> Option<Invite> {
+        let position = match channel {
+            Some(channel) => self
+                .invites
+                .iter()
+                .rposition(|v| v.channel.eq_ignore_ascii_case(channel))?,
+            None => self.invites.len().checked_sub(1)?,
+        };
+        Some(self.invites.remove(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_allowed() {
+        let allowlist = vec!["#Rust".to_string(), "alice".to_string()];
+        assert!(is_allowed(&allowlist, "bob", "#rust"));
+        assert!(is_allowed(&allowlist, "Alice", "#linux"));
+        assert!(!is_allowed(&allowlist, "bob", "#linux"));
+        assert!(!is_allowed(&[], "alice", "#rust"));
+    }
+
+    #[test]
+    fn test_pending_invites() {
+        let mut invites = PendingInvites::default();
+        assert!(invites.take(None).is_none());
+
+        invites.push("alice".to_string(), "#rust".to_string());
+        invites.push("bob".to_string(), "#linux".to_string());
+        invites.push("carol".to_string(), "#Rust".to_string());
+        assert_eq!(
+            invites.take(Some("#rust")).map(|v| v.source).as_deref(),
+            Some("carol")
+        );
+        assert!(invites.take(Some("#rust")).is_none());
+        assert_eq!(
+            invites.take(None).map(|v| v.channel).as_deref(),
+            Some("#linux")
+        );
+
+        for i in 0..=MAX_PENDING_INVITES {
+            invites.push("alice".to_string(), format!("#{i}"));
+        }
+        assert!(invites.take(Some("#0")).is_none());
+        assert!(invites.take(Some("#1")).is_some());
+    }
+}
@@ -2,11 +2,26 @@ use ahash::AHashMap;
 
 use crate::message_event::MessageEvent;
 use crate::state::server_id::ServerID;
+
+/// Information about a user sent by the server (away-notify, account-notify, chghost, setname...)
+#[derive(Debug, PartialEq, Clone)]
+pub enum UserInfo {
+    Away(Option<String>),    /*away message, None if back*/
+    Account(Option<String>), /*None if logged out*/
+    Host(String),            /*user@host*/
+    RealName(String),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct User {
     name: String,
     connected_sections: bit_vec::BitVec,
     is_main: bool,
+    away: Option<String>,
+    account: Option<String>,
+    user_host: Option<String>,
+    realname: Option<String>,
+    membership: AHashMap<usize, Vec<char>>, /*channel id -> prefix modes (o, v...)*/
 }
 const NB_SECTIONS: usize = 32;
 
@@ -16,6 +31,11 @@ impl User {
             name,
             is_main,
             connected_sections: bit_vec::BitVec::from_elem(NB_SECTIONS, false),
+            away: None,
+            account: None,
+            user_host: None,
+            realname: None,
+            membership: AHashMap::new(),
         }
     }
 
@@ -24,6 +44,53 @@ impl User {
         &self.name
     }
 
+    pub fn is_away(&self) -> bool {
+        self.away.is_some()
+    }
+
+    pub fn get_away(&self) -> Option<&str> {
+        self.away.as_deref()
+    }
+
+    pub fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    pub fn get_user_host(&self) -> Option<&str> {
+        self.user_host.as_deref()
+    }
+
+    pub fn get_realname(&self) -> Option<&str> {
+        self.realname.as_deref()
+    }
+
+    pub fn set_info(&mut self, info: &UserInfo) {
+        match info {
+            UserInfo::Away(message) => self.away = message.clone(),
+            UserInfo::Account(account) => self.account = account.clone(),
+            UserInfo::Host(user_host) => self.user_host = Some(user_host.clone()),
+            UserInfo::RealName(realname) => self.realname = Some(realname.clone()),
+        }
+    }
+
+    /// Text displayed in the tooltip, one information per line
+    pub fn describe(&self) -> String {
+        let mut lines = vec![self.name.clone()];
+        if let Some(user_host) = &self.user_host {
+            lines.push(user_host.clone());
+        }
+        if let Some(realname) = &self.realname {
+            lines.push(realname.clone());
+        }
+        if let Some(account) = &self.account {
+            lines.push(format!("Account: {account}"));
+        }
+        if let Some(away) = &self.away {
+            lines.push(format!("Away: {away}"));
+        }
+        lines.join("\n")
+    }
+
     pub fn join_section(&mut self, id: usize) {
         if let Some(mut channel) = self.connected_sections.get_mut(id) {
             *channel = true;
@@ -38,6 +105,22 @@ impl User {
         if let Some(mut channel) = self.connected_sections.get_mut(id) {
             *channel = false;
         }
+        self.membership.remove(&id);
+    }
+
+    pub fn get_membership(&self, id: usize) -> &[char] {
+        self.membership
+            .get(&id)
+            .map(|v| v.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn set_member_mode(&mut self, id: usize, mode: char, is_set: bool) {
+        let modes = self.membership.entry(id).or_default();
+        modes.retain(|m| *m != mode);
+        if is_set {
+            modes.push(mode);
+        }
     }
 
     pub fn has_joined_any_section(&self) -> bool {
@@ -52,6 +135,7 @@ struct Channel {
 
     has_unread_message: bool,
     has_received_message: bool,
+    modes: Vec<(char, Option<String>)>, /*modes of the channel without the lists and the ranks*/
 }
 
 #[derive(Debug)]
@@ -68,6 +152,18 @@ impl IrcModel {
         }
     }
 
+    /// A query opened before a reconnection
+    pub fn open_query(&mut self, in_id: ServerID, nickname: &str) {
+        if let Some(Some(server)) = self.get_mut_server_from_id(in_id) {
+            server.add_channel(nickname);
+        }
+    }
+
+    /// A server added while running, it is initialized when connecting
+    pub fn add_server(&mut self) {
+        self.servers.push(None);
+    }
+
     pub fn init_server(&mut self, in_id: ServerID, server_name: String, nickname: String) {
         if let Some(server) = self.get_mut_server_from_id(in_id) {
             *server = Some(IrcServerModel::new_model(in_id, server_name, nickname))
@@ -119,6 +215,23 @@ impl IrcModel {
         }
     }
 
+    /// Forget the presence of the nicknames removed from the notify list
+    pub fn retain_notify(&mut self, server_id: ServerID, nicknames: &[String]) {
+        if let Some(Some(server)) = self.get_mut_server_from_id(server_id) {
+            server
+                .notify
+                .retain(|(nick, _)| nicknames.iter().any(|v| v.eq_ignore_ascii_case(nick)));
+        }
+    }
+
+    /// CHANTYPES of the server, the ones of RFC 1459 if it is unknown
+    pub fn is_channel(&self, server_id: ServerID, name: &str) -> bool {
+        match self.get_server(server_id) {
+            Some(server) => server.is_channel(name),
+            None => clown_core::isupport::ISupport::default().is_channel(name),
+        }
+    }
+
     pub fn is_main_user(&self, server_id: ServerID, nick: &str) -> bool {
         if let Some(Some(server)) = self.get_server_from_id(server_id) {
             server.is_main_user(nick)
@@ -164,13 +277,16 @@ impl IrcModel {
                     server.join(channel, user);
                 }
             }
-            MessageEvent::Part(server_id, channel, user) => {
+            MessageEvent::Part(server_id, channel, user)
+            | MessageEvent::Kick(server_id, channel, user, _, _) => {
                 if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.typing.remove(user);
                     server.part(channel, user);
                 }
             }
             MessageEvent::Quit(server_id, user, _) => {
                 if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.typing.remove(user);
                     server.quit(user);
                 }
             }
@@ -186,6 +302,40 @@ impl IrcModel {
                     }
                 }
             }
+            MessageEvent::ISupport(server_id, tokens) => {
+                if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.isupport.update(tokens);
+                }
+            }
+            MessageEvent::Mode(server_id, _, target, changes) => {
+                if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    if server.is_channel(target) {
+                        server.set_channel_modes(target, changes);
+                    } else if server.is_main_user(target) {
+                        server.set_user_modes(changes);
+                    }
+                }
+            }
+            MessageEvent::Notify(server_id, nick, is_online) => {
+                if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.set_notify(nick, *is_online);
+                }
+            }
+            MessageEvent::Typing(server_id, source, target, state) => {
+                if let Some(Some(server)) = self.get_mut_server_from_id(*server_id)
+                    && !server.is_main_user(source)
+                {
+                    let channel = server.get_target(source, target).to_string();
+                    server
+                        .typing
+                        .set(&channel, source, *state, std::time::Instant::now());
+                }
+            }
+            MessageEvent::UserInfo(server_id, nick, info) => {
+                if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.set_user_info(nick, info);
+                }
+            }
             MessageEvent::SelectChannel(server_id, channel) => {
                 if let Some(server_id) = server_id
                     && let Some(Some(server)) = self.get_mut_server_from_id(*server_id)
@@ -194,9 +344,10 @@ impl IrcModel {
                 }
                 self.current_id = *server_id;
             }
-            MessageEvent::PrivMsg(server_id, source, target, _)
-            | MessageEvent::ActionMsg(server_id, source, target, _) => {
+            MessageEvent::PrivMsg(server_id, source, target, _, _, _)
+            | MessageEvent::ActionMsg(server_id, source, target, _, _) => {
                 if let Some(Some(server)) = self.get_mut_server_from_id(*server_id) {
+                    server.typing.remove(source);
                     server.received_message(source, target);
                 }
             }
@@ -236,16 +387,22 @@ pub struct IrcServerModel {
     current_channel: Option<String>,
 
     current_nick: String,
+    user_modes: Vec<char>,
     server_id: ServerID,
     name: String,
+    isupport: clown_core::isupport::ISupport,
+    notify: Vec<(String, bool)>, /*nickname, is online */
+    typing: super::typing::TypingTracker,
 }
 
 impl IrcServerModel {
     pub fn new_model(server_id: ServerID, server_name: String, nick_name: String) -> Self {
+        let isupport = clown_core::isupport::ISupport::default();
+        let nick_name = Self::sanitize_name(&isupport, &nick_name);
         let mut users = AHashMap::new();
         users.insert(
-            Self::sanitize_name(&nick_name).to_lowercase(),
-            User::new(Self::sanitize_name(&nick_name).to_string(), true),
+            nick_name.to_lowercase(),
+            User::new(nick_name.to_string(), true),
         );
         Self {
             users,
@@ -253,7 +410,11 @@ impl IrcServerModel {
             server_id,
             list_channels: Vec::new(),
             current_channel: None,
-            current_nick: Self::sanitize_name(&nick_name).to_string(),
+            current_nick: nick_name.to_string(),
+            isupport,
+            user_modes: Vec::new(),
+            notify: Vec::new(),
+            typing: super::typing::TypingTracker::default(),
         }
     }
 
@@ -273,6 +434,15 @@ impl IrcServerModel {
         self.server_id
     }
 
+    pub fn get_isupport(&self) -> &clown_core::isupport::ISupport {
+        &self.isupport
+    }
+
+    /// The name starts with one of the CHANTYPES of the server
+    pub fn is_channel(&self, name: &str) -> bool {
+        self.isupport.is_channel(name)
+    }
+
     pub fn get_target<'b>(&self, source: &'b str, target: &'b str) -> &'b str {
         if target.eq_ignore_ascii_case(self.get_current_nick()) {
             source
@@ -281,8 +451,20 @@ impl IrcServerModel {
         }
     }
 
-    fn sanitize_name(user: &str) -> &str {
-        user.strip_prefix('@').unwrap_or(user)
+    /// Remove the membership prefixes of PREFIX and the user@host (userhost-in-names) from a name
+    fn sanitize_name<'a>(isupport: &clown_core::isupport::ISupport, user: &'a str) -> &'a str {
+        let user = isupport.strip_prefix(user);
+        user.split_once('!').map(|(nick, _)| nick).unwrap_or(user)
+    }
+
+    /// A channel keeps its name, only the STATUSMSG symbols (@#chan) are removed
+    fn sanitize_target<'a>(isupport: &clown_core::isupport::ISupport, target: &'a str) -> &'a str {
+        let channel = isupport.strip_statusmsg(target);
+        if isupport.is_channel(channel) {
+            channel
+        } else {
+            Self::sanitize_name(isupport, target)
+        }
     }
 
     fn get_channel_id(&self, channel: &str) -> Option<usize> {
@@ -316,6 +498,7 @@ impl IrcServerModel {
             name: channel.to_string(),
             has_received_message: false,
             has_unread_message: false,
+            modes: Vec::new(),
         };
         let id = new_channel.id;
         self.list_channels.push(new_channel);
@@ -328,24 +511,39 @@ impl IrcServerModel {
         }
     }
 
-    fn join(&mut self, channel: &str, nick: &str) {
-        let nick = Self::sanitize_name(nick);
+    fn join(&mut self, channel: &str, entry: &str) {
+        let (modes, entry) = self.isupport.split_prefix(entry);
+        let nick = Self::sanitize_name(&self.isupport, entry);
         let id = self.add_channel(channel);
 
-        let entry = self
+        let user = self
             .users
             .entry(nick.to_lowercase())
             .or_insert_with(|| User::new(nick.to_string(), false));
-        entry.join_section(id);
-        if entry.is_main {
+        user.join_section(id);
+        user.membership.insert(id, modes);
+        if let Some((_, user_host)) = entry.split_once('!') {
+            user.user_host = Some(user_host.to_string());
+        }
+        if user.is_main {
             self.current_channel = Some(channel.to_string());
         }
     }
 
     fn part(&mut self, channel: &str, nick: &str) {
-        let nick = Self::sanitize_name(nick).to_lowercase();
+        let nick = Self::sanitize_name(&self.isupport, nick).to_lowercase();
         let id = self.add_channel(channel);
 
+        if self.is_main_user(&nick) {
+            //We are not in the channel anymore, nobody can be seen there
+            for user in self.users.values_mut() {
+                user.quit_section(id);
+            }
+            self.users
+                .retain(|_, user| user.is_main || user.has_joined_any_section());
+            return;
+        }
+
         let mut should_delete = false;
         if let Some(user) = self.users.get_mut(&nick) {
             user.quit_section(id);
@@ -358,14 +556,14 @@ impl IrcServerModel {
     }
 
     fn quit(&mut self, nick: &str) {
-        let nick = Self::sanitize_name(nick).to_lowercase();
+        let nick = Self::sanitize_name(&self.isupport, nick).to_lowercase();
 
         self.users.remove(&nick);
     }
 
     fn nick(&mut self, old: &str, new: &str) {
-        let old = Self::sanitize_name(old);
-        let new = Self::sanitize_name(new);
+        let old = Self::sanitize_name(&self.isupport, old);
+        let new = Self::sanitize_name(&self.isupport, new);
         let old_lower = old.to_lowercase();
 
         if let Some(user) = self.users.remove(&old_lower) {
@@ -386,8 +584,136 @@ impl IrcServerModel {
         }
     }
 
+    fn set_channel_modes(&mut self, channel: &str, changes: &[clown_core::mode::ModeChange]) {
+        for change in changes {
+            if self.isupport.is_prefix_mode(change.mode) {
+                if let Some(nick) = change.parameter.as_deref() {
+                    self.set_member_mode(channel, nick, change.mode, change.is_set);
+                }
+            } else if !self.isupport.is_list_mode(change.mode)
+                && let Some(c) = self.get_channel_mut(channel)
+            {
+                c.modes.retain(|(mode, _)| *mode != change.mode);
+                if change.is_set {
+                    c.modes.push((change.mode, change.parameter.clone()));
+                }
+            }
+        }
+    }
+
+    /// Key of a +k channel, known once the modes have been received
+    pub fn get_channel_key(&self, channel: &str) -> Option<&str> {
+        self.get_channel(channel)?
+            .modes
+            .iter()
+            .find(|(mode, _)| *mode == 'k')
+            .and_then(|(_, key)| key.as_deref())
+    }
+
+    /// +nt-like representation of the channel modes
+    pub fn get_channel_modes(&self, channel: &str) -> Option<String> {
+        let c = self.get_channel(channel)?;
+        let changes = c
+            .modes
+            .iter()
+            .map(|(mode, parameter)| clown_core::mode::ModeChange {
+                is_set: true,
+                mode: *mode,
+                parameter: parameter.clone(),
+            })
+            .collect::<Vec<_>>();
+        (!changes.is_empty()).then(|| clown_core::mode::format_modes(&changes))
+    }
+
+    fn set_user_modes(&mut self, changes: &[clown_core::mode::ModeChange]) {
+        for change in changes {
+            self.user_modes.retain(|mode| *mode != change.mode);
+            if change.is_set {
+                self.user_modes.push(change.mode);
+            }
+        }
+    }
+
+    pub fn get_user_modes(&self) -> &[char] {
+        &self.user_modes
+    }
+
+    fn set_member_mode(&mut self, channel: &str, nick: &str, mode: char, is_set: bool) {
+        if let Some(id) = self.get_channel_id(channel)
+            && let Some(user) = self
+                .users
+                .get_mut(&Self::sanitize_name(&self.isupport, nick).to_lowercase())
+        {
+            user.set_member_mode(id, mode, is_set);
+        }
+    }
+
+    /// Symbol of the highest rank of the user in the channel (@, +...)
+    pub fn get_user_prefix(&self, nick: &str, channel: &str) -> Option<char> {
+        let id = self.get_channel_id(channel)?;
+        let user = self.get_user(nick)?;
+        user.get_membership(id)
+            .iter()
+            .min_by_key(|mode| self.isupport.get_rank(**mode))
+            .and_then(|mode| self.isupport.get_symbol(*mode))
+    }
+
+    /// 0 is the highest rank, users without any prefix have the lowest rank
+    pub fn get_user_rank(&self, nick: &str, channel: &str) -> usize {
+        self.get_channel_id(channel)
+            .zip(self.get_user(nick))
+            .and_then(|(id, user)| {
+                user.get_membership(id)
+                    .iter()
+                    .map(|mode| self.isupport.get_rank(*mode))
+                    .min()
+            })
+            .unwrap_or(self.isupport.nb_ranks())
+    }
+
+    fn set_user_info(&mut self, nick: &str, info: &UserInfo) {
+        if let Some(user) = self
+            .users
+            .get_mut(&Self::sanitize_name(&self.isupport, nick).to_lowercase())
+        {
+            user.set_info(info);
+        }
+    }
+
+    fn set_notify(&mut self, nick: &str, is_online: bool) {
+        if let Some((_, online)) = self
+            .notify
+            .iter_mut()
+            .find(|(name, _)| name.eq_ignore_ascii_case(nick))
+        {
+            *online = is_online;
+        } else {
+            self.notify.push((nick.to_string(), is_online));
+        }
+    }
+
+    /// None if the presence of the user is not known yet
+    pub fn get_notify_status(&self, nick: &str) -> Option<bool> {
+        self.notify
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(nick))
+            .map(|(_, online)| *online)
+    }
+
+    pub fn get_notify_list(&self) -> &[(String, bool)] {
+        &self.notify
+    }
+
+    /// Users typing in the channel or the private conversation
+    pub fn get_typing_users(&self, channel: &str) -> Vec<&str> {
+        self.typing.get_typing(channel, std::time::Instant::now())
+    }
+
     pub fn is_main_user(&self, user: &str) -> bool {
-        if let Some(user) = self.users.get(&Self::sanitize_name(user).to_lowercase()) {
+        if let Some(user) = self
+            .users
+            .get(&Self::sanitize_name(&self.isupport, user).to_lowercase())
+        {
             user.is_main
         } else {
             false
@@ -395,13 +721,14 @@ impl IrcServerModel {
     }
 
     pub fn get_user(&self, user: &str) -> Option<&User> {
-        self.users.get(&Self::sanitize_name(user).to_lowercase())
+        self.users
+            .get(&Self::sanitize_name(&self.isupport, user).to_lowercase())
     }
 
     pub fn has_user_joined_channel(&self, user: &str, channel: &str) -> bool {
         if let Some(id) = self.get_channel_id(channel) {
             self.users
-                .get(&Self::sanitize_name(user).to_lowercase())
+                .get(&Self::sanitize_name(&self.isupport, user).to_lowercase())
                 .map(|v| v.has_joined_section(id))
                 .is_some_and(|v| v)
         } else {
@@ -410,7 +737,7 @@ impl IrcServerModel {
     }
 
     pub fn get_all_joined_channel(&self, user: &str) -> impl Iterator<Item = &str> + '_ {
-        let user = Self::sanitize_name(user).to_lowercase();
+        let user = Self::sanitize_name(&self.isupport, user).to_lowercase();
         let maybe_user = self.users.get(&user);
         maybe_user.into_iter().flat_map(|u| {
             self.list_channels
@@ -420,10 +747,26 @@ impl IrcServerModel {
         })
     }
 
+    /// Channels joined by the main user
+    pub fn get_joined_channels(&self) -> Vec<String> {
+        self.get_all_joined_channel(&self.current_nick)
+            .map(|v| v.to_string())
+            .collect()
+    }
+
+    /// Private conversations, they are not joined
+    pub fn get_queries(&self) -> Vec<String> {
+        self.list_channels
+            .iter()
+            .filter(|c| !self.is_channel(&c.name) && !c.name.eq_ignore_ascii_case(&self.name))
+            .map(|c| c.name.to_string())
+            .collect()
+    }
+
     // a(source) sends to b(target)
     fn received_message(&mut self, source: &str, target: &str) {
-        let target = Self::sanitize_name(target);
-        let source = Self::sanitize_name(source);
+        let target = Self::sanitize_target(&self.isupport, target);
+        let source = Self::sanitize_name(&self.isupport, source);
 
         let target = self.get_target(source, target);
         let id = self.add_channel(target);
@@ -447,7 +790,7 @@ impl IrcServerModel {
     }
 
     fn select_channel(&mut self, channel: &str) {
-        self.current_channel = Some(Self::sanitize_name(channel).to_string());
+        self.current_channel = Some(Self::sanitize_target(&self.isupport, channel).to_string());
         if let Some(c) = self.get_channel_mut(&channel.to_lowercase()) {
             c.has_unread_message = false;
         }
@@ -462,6 +805,7 @@ impl IrcServerModel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::typing::TypingState;
 
     fn model() -> IrcModel {
         // create one usable server slot
@@ -573,6 +917,8 @@ mod tests {
             "jack".into(),
             "me".into(),
             "hello".into(),
+            None,
+            None,
         ));
 
         assert!(server(&m).get_channel_id("jack").is_some());
@@ -618,6 +964,8 @@ mod tests {
             "alice".into(),
             "#RUST".into(),
             "hello".into(),
+            None,
+            None,
         ));
 
         assert!(server(&m).has_unread_message("#rust"));
@@ -653,6 +1001,35 @@ mod tests {
         assert!(channels.iter().any(|c| c.eq_ignore_ascii_case("#linux")));
     }
 
+    #[test]
+    fn test_open_buffers() {
+        let mut m = model();
+        setup_server(&mut m);
+        let id = ServerID::new(0);
+
+        m.handle_action(&MessageEvent::JoinServer(id));
+        m.handle_action(&MessageEvent::Join(id, "#rust".into(), "Me".into()));
+        m.handle_action(&MessageEvent::Join(id, "#linux".into(), "Alice".into()));
+        m.handle_action(&MessageEvent::PrivMsg(
+            id,
+            "Bob".into(),
+            "Me".into(),
+            "hello".into(),
+            None,
+            None,
+        ));
+
+        assert_eq!(server(&m).get_joined_channels(), vec!["#rust".to_string()]);
+        assert_eq!(server(&m).get_queries(), vec!["Bob".to_string()]);
+
+        m.init_server(id, "TEST".into(), "Me".into());
+        m.open_query(id, "Bob");
+        assert_eq!(
+            m.get_server(id).unwrap().get_queries(),
+            vec!["Bob".to_string()]
+        );
+    }
+
     #[test]
     fn test_private_message_unread() {
         let mut m = model();
@@ -673,11 +1050,316 @@ mod tests {
             "ALICE".into(),
             "#rust".into(),
             "hello".into(),
+            None,
+            None,
         ));
 
         assert!(server(&m).has_unread_message("#RUST"));
     }
 
+    #[test]
+    fn test_userhost_in_names() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::UpdateUsers(
+            ServerID::new(0),
+            "#rust".into(),
+            vec!["@Alice!al@host.com".into(), "bob".into()],
+        ));
+
+        let s = server(&m);
+        assert!(s.has_user_joined_channel("alice", "#rust"));
+        assert_eq!(
+            s.get_user("alice").unwrap().get_user_host(),
+            Some("al@host.com")
+        );
+        assert_eq!(s.get_user("bob").unwrap().get_user_host(), None);
+    }
+
+    #[test]
+    fn test_user_info() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::Join(
+            ServerID::new(0),
+            "#rust".into(),
+            "Alice".into(),
+        ));
+        for info in [
+            UserInfo::Away(Some("lunch".into())),
+            UserInfo::Account(Some("alice_acc".into())),
+            UserInfo::Host("al@host.com".into()),
+            UserInfo::RealName("Alice Liddell".into()),
+        ] {
+            m.handle_action(&MessageEvent::UserInfo(
+                ServerID::new(0),
+                "ALICE".into(),
+                info,
+            ));
+        }
+
+        let user = server(&m).get_user("alice").unwrap();
+        assert!(user.is_away());
+        assert_eq!(user.get_away(), Some("lunch"));
+        assert_eq!(user.get_account(), Some("alice_acc"));
+        assert_eq!(user.get_user_host(), Some("al@host.com"));
+        assert_eq!(user.get_realname(), Some("Alice Liddell"));
+        assert_eq!(
+            user.describe(),
+            "Alice\nal@host.com\nAlice Liddell\nAccount: alice_acc\nAway: lunch"
+        );
+
+        m.handle_action(&MessageEvent::UserInfo(
+            ServerID::new(0),
+            "alice".into(),
+            UserInfo::Away(None),
+        ));
+        m.handle_action(&MessageEvent::UserInfo(
+            ServerID::new(0),
+            "alice".into(),
+            UserInfo::Account(None),
+        ));
+        let user = server(&m).get_user("alice").unwrap();
+        assert!(!user.is_away());
+        assert_eq!(user.get_account(), None);
+    }
+
+    #[test]
+    fn test_channel_target_keeps_prefix_symbols() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::ISupport(
+            ServerID::new(0),
+            vec!["PREFIX=(qaohv)~&@%+".into(), "STATUSMSG=~&@%+".into()],
+        ));
+        for target in ["&local", "&&twice", "@#ops"] {
+            m.handle_action(&MessageEvent::PrivMsg(
+                ServerID::new(0),
+                "alice".into(),
+                target.into(),
+                "hello".into(),
+                None,
+                None,
+            ));
+        }
+        assert!(server(&m).has_unread_message("&local"));
+        assert!(server(&m).has_unread_message("&&twice"));
+        assert!(server(&m).has_unread_message("#ops"));
+        assert!(!server(&m).has_unread_message("local"));
+
+        m.handle_action(&MessageEvent::SelectChannel(
+            Some(ServerID::new(0)),
+            "&local".into(),
+        ));
+        assert_eq!(server(&m).get_current_channel(), Some("&local"));
+    }
+
+    #[test]
+    fn test_membership_ranks() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::ISupport(
+            ServerID::new(0),
+            vec!["PREFIX=(qaohv)~&@%+".into()],
+        ));
+        m.handle_action(&MessageEvent::UpdateUsers(
+            ServerID::new(0),
+            "#rust".into(),
+            vec![
+                "~@owner".into(),
+                "&admin".into(),
+                "%half".into(),
+                "+voice".into(),
+                "user".into(),
+            ],
+        ));
+
+        let s = server(&m);
+        for (nick, prefix, rank) in [
+            ("owner", Some('~'), 0),
+            ("admin", Some('&'), 1),
+            ("half", Some('%'), 3),
+            ("voice", Some('+'), 4),
+            ("user", None, 5),
+        ] {
+            assert!(s.get_user(nick).is_some(), "{nick}");
+            assert_eq!(s.get_user_prefix(nick, "#rust"), prefix, "{nick}");
+            assert_eq!(s.get_user_rank(nick, "#RUST"), rank, "{nick}");
+        }
+
+        m.handle_action(&MessageEvent::Mode(
+            ServerID::new(0),
+            Some("owner".into()),
+            "#rust".into(),
+            clown_core::mode::parse_channel_modes(
+                "-q+o-h+v owner user half half",
+                server(&m).get_isupport(),
+            ),
+        ));
+
+        let s = server(&m);
+        assert_eq!(s.get_user_prefix("owner", "#rust"), Some('@'));
+        assert_eq!(s.get_user_prefix("user", "#rust"), Some('@'));
+        assert_eq!(s.get_user_prefix("half", "#rust"), Some('+'));
+
+        m.handle_action(&MessageEvent::Part(
+            ServerID::new(0),
+            "#rust".into(),
+            "user".into(),
+        ));
+        m.handle_action(&MessageEvent::Join(
+            ServerID::new(0),
+            "#rust".into(),
+            "user".into(),
+        ));
+        assert_eq!(server(&m).get_user_prefix("user", "#rust"), None);
+    }
+
+    #[test]
+    fn test_channel_and_user_modes() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::Join(
+            ServerID::new(0),
+            "#rust".into(),
+            "Me".into(),
+        ));
+        let isupport = server(&m).get_isupport().clone();
+        m.handle_action(&MessageEvent::Mode(
+            ServerID::new(0),
+            None,
+            "#rust".into(),
+            clown_core::mode::parse_channel_modes("+ntlk 10 secret", &isupport),
+        ));
+        assert_eq!(
+            server(&m).get_channel_modes("#RUST").as_deref(),
+            Some("+ntlk 10 secret")
+        );
+
+        m.handle_action(&MessageEvent::Mode(
+            ServerID::new(0),
+            Some("op".into()),
+            "#rust".into(),
+            clown_core::mode::parse_channel_modes("-l+b-k+o *!*@spam secret me", &isupport),
+        ));
+        let s = server(&m);
+        assert_eq!(s.get_channel_modes("#rust").as_deref(), Some("+nt"));
+        assert_eq!(s.get_user_prefix("me", "#rust"), Some('@'));
+
+        m.handle_action(&MessageEvent::Mode(
+            ServerID::new(0),
+            Some("Me".into()),
+            "Me".into(),
+            clown_core::mode::parse_user_modes("+iw-w"),
+        ));
+        assert_eq!(server(&m).get_user_modes(), &['i']);
+    }
+
+    #[test]
+    fn test_notify() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        assert_eq!(server(&m).get_notify_status("alice"), None);
+        m.handle_action(&MessageEvent::Notify(
+            ServerID::new(0),
+            "Alice".into(),
+            true,
+        ));
+        assert_eq!(server(&m).get_notify_status("alice"), Some(true));
+        m.handle_action(&MessageEvent::Notify(
+            ServerID::new(0),
+            "alice".into(),
+            false,
+        ));
+        assert_eq!(server(&m).get_notify_status("ALICE"), Some(false));
+        assert_eq!(server(&m).get_notify_list(), [("Alice".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_kick() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        for user in ["Me", "Alice", "Bob"] {
+            m.handle_action(&MessageEvent::Join(
+                ServerID::new(0),
+                "#rust".into(),
+                user.into(),
+            ));
+        }
+        m.handle_action(&MessageEvent::Join(
+            ServerID::new(0),
+            "#linux".into(),
+            "Bob".into(),
+        ));
+
+        m.handle_action(&MessageEvent::Kick(
+            ServerID::new(0),
+            "#rust".into(),
+            "alice".into(),
+            "bob".into(),
+            Some("spam".into()),
+        ));
+        assert!(!server(&m).has_user_joined_channel("alice", "#rust"));
+        assert!(server(&m).get_user("alice").is_none());
+
+        m.handle_action(&MessageEvent::Kick(
+            ServerID::new(0),
+            "#rust".into(),
+            "me".into(),
+            "bob".into(),
+            None,
+        ));
+        let s = server(&m);
+        assert!(!s.has_user_joined_channel("me", "#rust"));
+        assert!(!s.has_user_joined_channel("bob", "#rust"));
+        assert!(s.has_user_joined_channel("bob", "#linux"));
+        assert!(s.get_user("me").is_some());
+    }
+
+    #[test]
+    fn test_typing() {
+        let mut m = model();
+        setup_server(&mut m);
+
+        m.handle_action(&MessageEvent::Typing(
+            ServerID::new(0),
+            "alice".into(),
+            "#rust".into(),
+            TypingState::Active,
+        ));
+        m.handle_action(&MessageEvent::Typing(
+            ServerID::new(0),
+            "bob".into(),
+            "Me".into(),
+            TypingState::Active,
+        ));
+        m.handle_action(&MessageEvent::Typing(
+            ServerID::new(0),
+            "me".into(),
+            "#rust".into(),
+            TypingState::Active,
+        ));
+        assert_eq!(server(&m).get_typing_users("#rust"), vec!["alice"]);
+        assert_eq!(server(&m).get_typing_users("bob"), vec!["bob"]);
+
+        m.handle_action(&MessageEvent::PrivMsg(
+            ServerID::new(0),
+            "alice".into(),
+            "#rust".into(),
+            "hello".into(),
+            None,
+            None,
+        ));
+        assert!(server(&m).get_typing_users("#rust").is_empty());
+    }
+
     #[test]
     fn test_get_target() {
         let mut m = model();
@@ -0,0 +1,65 @@
+use clown_core::command::Command;
+
+/// Channels sent in a single JOIN, to stay below the length of a line
+const CHANNELS_PER_JOIN: usize = 10;
+
+/// Arguments of /join, e.g. `#a,#b key1`, the keys are matched by position and `x` skips one
+pub fn parse(args: &str) -> Vec<(String, Option<String>)> {
+    let mut split = args.split_ascii_whitespace();
+    let channels = split.next().unwrap_or_default();
+    let mut keys = split.next().unwrap_or_default().split(',');
+    channels
+        .split(',')
+        .filter(|v| !v.is_empty())
+        .map(|channel| {
+            let key = keys.next().filter(|v| !v.is_empty() && *v != "x");
+            (channel.to_string(), key.map(str::to_string))
+        })
+        .collect()
+}
+
+/// The JOIN commands for the channels and their keys
+pub fn commands(channels: Vec<(String, Option<String>)>) -> Vec<Command> {
+    channels
+        .chunks(CHANNELS_PER_JOIN)
+        .map(|v| Command::JoinKeys(v.to_vec()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(name: &str, key: Option<&str>) -> (String, Option<String>) {
+        (name.to_string(), key.map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("#rust"), vec![channel("#rust", None)]);
+        assert_eq!(
+            parse("#a,#b,#c key1"),
+            vec![
+                channel("#a", Some("key1")),
+                channel("#b", None),
+                channel("#c", None)
+            ]
+        );
+        assert_eq!(
+            parse("#a,#b x,key2"),
+            vec![channel("#a", None), channel("#b", Some("key2"))]
+        );
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_commands() {
+        let channels = (0..25)
+            .map(|v| channel(&format!("#{v}"), None))
+            .collect::<Vec<_>>();
+        let commands = commands(channels);
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(&commands[2], Command::JoinKeys(v) if v.len() == 5));
+        assert!(super::commands(Vec::new()).is_empty());
+    }
+}
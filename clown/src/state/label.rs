@@ -0,0 +1,114 @@
+use ahash::{AHashMap, AHashSet};
+use clown_core::command::Command;
+use clown_core::response::Response;
+
+/// Remembers the buffer that has sent a labeled command (labeled-response)
+#[derive(Debug, Default)]
+pub struct LabelTracker {
+    counter: u64,
+    labels: AHashMap<String, String>,
+    batches: AHashMap<String, String>,
+    /// Commands whose echo (echo-message) is not shown
+    hidden: AHashSet<String>,
+}
+
+impl LabelTracker {
+    pub fn create(&mut self, buffer: &str) -> String {
+        self.counter = self.counter.wrapping_add(1);
+        let label = format!("clown{}", self.counter);
+        self.labels.insert(label.clone(), buffer.to_string());
+        label
+    }
+
+    /// Label of a command holding a password, its echo is dropped
+    pub fn create_hidden(&mut self) -> String {
+        self.counter = self.counter.wrapping_add(1);
+        let label = format!("clown{}", self.counter);
+        self.hidden.insert(label.clone());
+        label
+    }
+
+    /// The message is the echo of a hidden command
+    pub fn is_hidden(&mut self, label: Option<&str>) -> bool {
+        label.is_some_and(|v| self.hidden.remove(v))
+    }
+
+    /// Returns the buffer to display the reply into
+    pub fn route(
+        &mut self,
+        label: Option<&str>,
+        batch: Option<&str>,
+        reply: &Response,
+    ) -> Option<String> {
+        if let Some(label) = label {
+            let buffer = self.labels.remove(label)?;
+            //The replies can be grouped in a batch
+            if let Response::Cmd(Command::Batch(reference, _)) = reply
+                && let Some(reference) = reference.strip_prefix('+')
+            {
+                self.batches.insert(reference.to_string(), buffer.clone());
+            }
+            Some(buffer)
+        } else if let Response::Cmd(Command::Batch(reference, _)) = reply
+            && let Some(reference) = reference.strip_prefix('-')
+        {
+            self.batches.remove(reference)
+        } else {
+            batch.and_then(|batch| self.batches.get(batch).cloned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clown_core::response::ResponseNumber;
+
+    fn error() -> Response {
+        Response::Rpl(ResponseNumber::Err(401, "No such nick".to_string()))
+    }
+
+    #[test]
+    fn test_route_single_reply() {
+        let mut tracker = LabelTracker::default();
+        let label = tracker.create("#rust");
+        assert_eq!(
+            tracker.route(Some(&label), None, &error()),
+            Some("#rust".to_string())
+        );
+        //A label is used only once
+        assert_eq!(tracker.route(Some(&label), None, &error()), None);
+        assert_eq!(tracker.route(Some("unknown"), None, &error()), None);
+    }
+
+    #[test]
+    fn test_route_batch() {
+        let mut tracker = LabelTracker::default();
+        let label = tracker.create("#rust");
+        let start = Response::Cmd(Command::Batch(
+            "+ref".to_string(),
+            vec!["labeled-response".to_string()],
+        ));
+        assert_eq!(
+            tracker.route(Some(&label), None, &start),
+            Some("#rust".to_string())
+        );
+        assert_eq!(
+            tracker.route(None, Some("ref"), &error()),
+            Some("#rust".to_string())
+        );
+        let end = Response::Cmd(Command::Batch("-ref".to_string(), vec![]));
+        assert_eq!(tracker.route(None, None, &end), Some("#rust".to_string()));
+        assert_eq!(tracker.route(None, Some("ref"), &error()), None);
+    }
+
+    #[test]
+    fn test_hidden() {
+        let mut tracker = LabelTracker::default();
+        let label = tracker.create_hidden();
+        assert_eq!(tracker.route(Some(&label), None, &error()), None);
+        assert!(!tracker.is_hidden(None));
+        assert!(tracker.is_hidden(Some(&label)));
+        assert!(!tracker.is_hidden(Some(&label)));
+    }
+}
@@ -1,6 +1,22 @@
+pub mod channel_list;
 pub mod config;
 pub mod context;
+pub mod ctcp;
+pub mod dcc;
+pub mod endpoint;
+pub mod history;
+pub mod invite;
 pub mod irc_model;
+pub mod join;
+pub mod label;
+pub mod mode_list;
 pub mod model;
+pub mod moderation;
+pub mod notify;
+pub mod perform;
 pub mod server_id;
+pub mod services;
 pub mod session;
+pub mod sts;
+pub mod typing;
+pub mod whois;
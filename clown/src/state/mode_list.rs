@@ -0,0 +1,104 @@
+use clown_core::mode::ModeChange;
+use clown_core::response::ListEntry;
+
+/// Bans, ban exceptions and invite exceptions
+pub const LIST_MODES: [char; 3] = ['b', 'e', 'I'];
+
+/// Entries of MODE #chan b/e/I, kept until the end of their list
+#[derive(Debug, Default)]
+pub struct ModeListCollector {
+    lists: Vec<(String, char, Vec<ListEntry>)>,
+}
+
+impl ModeListCollector {
+    pub fn push(&mut self, channel: &str, mode: char, entry: ListEntry) {
+        match self
+            .lists
+            .iter_mut()
+            .find(|(c, m, _)| *m == mode && c.eq_ignore_ascii_case(channel))
+        {
+            Some((_, _, entries)) => entries.push(entry),
+            None => self.lists.push((channel.to_string(), mode, vec![entry])),
+        }
+    }
+
+    /// The entries received, an empty list has none
+    pub fn finish(&mut self, channel: &str, mode: char) -> Vec<ListEntry> {
+        match self
+            .lists
+            .iter()
+            .position(|(c, m, _)| *m == mode && c.eq_ignore_ascii_case(channel))
+        {
+            Some(position) => self.lists.remove(position).2,
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Add or remove the mask of a change seen after the list was received
+pub fn apply(entries: &mut Vec<ListEntry>, change: &ModeChange, setter: Option<&str>, set_at: u64) {
+    let Some(mask) = change.parameter.as_deref() else {
+        return;
+    };
+    entries.retain(|v| !v.mask.eq_ignore_ascii_case(mask));
+    if change.is_set {
+        entries.push(ListEntry {
+            mask: mask.to_string(),
+            setter: setter.map(str::to_string),
+            set_at: Some(set_at),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mask: &str) -> ListEntry {
+        ListEntry {
+            mask: mask.to_string(),
+            setter: None,
+            set_at: None,
+        }
+    }
+
+    #[test]
+    fn test_collector() {
+        let mut collector = ModeListCollector::default();
+        collector.push("#rust", 'b', entry("*!*@a"));
+        collector.push("#linux", 'b', entry("*!*@b"));
+        collector.push("#Rust", 'b', entry("*!*@c"));
+        collector.push("#rust", 'e', entry("*!*@d"));
+
+        assert_eq!(
+            collector.finish("#rust", 'b'),
+            vec![entry("*!*@a"), entry("*!*@c")]
+        );
+        assert!(collector.finish("#rust", 'b').is_empty());
+        assert_eq!(collector.finish("#rust", 'e'), vec![entry("*!*@d")]);
+        assert!(collector.finish("#rust", 'I').is_empty());
+    }
+
+    #[test]
+    fn test_apply() {
+        let change = |is_set, mask: &str| ModeChange {
+            is_set,
+            mode: 'b',
+            parameter: Some(mask.to_string()),
+        };
+        let mut entries = vec![entry("*!*@a")];
+        apply(
+            &mut entries,
+            &change(true, "*!*@b"),
+            Some("alice"),
+            1700000000,
+        );
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].setter.as_deref(), Some("alice"));
+        assert_eq!(entries[1].set_at, Some(1700000000));
+
+        apply(&mut entries, &change(false, "*!*@A"), None, 0);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].mask, "*!*@b");
+    }
+}
@@ -1,4 +1,4 @@
-use super::config::{Config, Discuss};
+use super::config::{BanMask, Config, Ctcp, Dcc, Discuss, Reconnect, Services};
 use super::server_id::ServerID;
 use crate::irc_view::color_user::ColorGenerator;
 use clown_core::{client::LoginConfig, conn::ConnectionConfig};
@@ -18,6 +18,22 @@ pub struct IRCConnection {
     pub error_receiver: mpsc::Receiver<String>,
     pub _error_sender: mpsc::Sender<String>,
     pub task: JoinHandle<()>,
+    /// Kept up to date by the core client
+    pub capabilities: clown_core::capability::SharedCapabilities,
+    pub batches: super::history::BatchCollector,
+    pub labels: super::label::LabelTracker,
+    pub notify: super::notify::NotifyPoller,
+    pub typing: super::typing::TypingThrottle,
+    pub ctcp: super::ctcp::CtcpResponder,
+    pub services: super::services::ServicesState,
+    pub invites: super::invite::PendingInvites,
+    pub mode_lists: super::mode_list::ModeListCollector,
+    pub channel_list: super::channel_list::ChannelListCollector,
+    pub whois: super::whois::WhoisCollector,
+    /// Address announced in the DCC offers, found in the background once connected
+    pub dcc_address: std::sync::Arc<std::sync::OnceLock<std::net::IpAddr>>,
+    /// Config used for the connection, after the STS policies
+    pub connection_config: clown_core::conn::ConnectionConfig,
 }
 
 #[derive(Default)]
@@ -148,18 +164,75 @@ impl Model {
         self.get_config().is_autojoin_id(in_id)
     }
 
+    pub fn is_autorejoin_by_id(&self, in_id: ServerID) -> bool {
+        self.get_config().is_autorejoin_id(in_id)
+    }
+
+    pub fn get_notify_list(&self, in_id: ServerID) -> &[String] {
+        self.get_config().get_notify_list(in_id)
+    }
+
+    pub fn get_perform(&self, in_id: ServerID) -> &[String] {
+        self.get_config().get_perform(in_id)
+    }
+
+    pub fn get_services(&self, in_id: ServerID) -> Option<&Services> {
+        self.get_config().get_services(in_id)
+    }
+
+    pub fn get_accept_invites(&self, in_id: ServerID) -> &[String] {
+        self.get_config().get_accept_invites(in_id)
+    }
+
+    pub fn get_channel_key(&self, in_id: ServerID, channel: &str) -> Option<&str> {
+        self.get_config().get_channel_key(in_id, channel)
+    }
+
     pub fn is_autojoin(&self) -> impl Iterator<Item = ServerID> {
         self.get_config().is_autojoin()
     }
 
-    pub fn get_connection_config(&self, in_id: ServerID) -> Option<ConnectionConfig> {
-        self.get_config().get_connection_config(in_id)
+    pub fn get_connection_configs(&self, in_id: ServerID) -> Vec<ConnectionConfig> {
+        self.get_config().get_connection_configs(in_id)
+    }
+
+    pub fn is_random_endpoints(&self, in_id: ServerID) -> bool {
+        self.get_config().is_random_endpoints(in_id)
     }
 
     pub fn get_login_config(&self, in_id: ServerID) -> Option<LoginConfig> {
         self.get_config().get_login_config(in_id)
     }
 
+    pub fn get_reconnect(&self, in_id: ServerID) -> Reconnect {
+        self.get_config().get_reconnect(in_id)
+    }
+
+    pub fn get_bouncer_network(&self, in_id: ServerID) -> Option<&str> {
+        self.get_config().get_bouncer_network(in_id)
+    }
+
+    pub fn get_log_address(&self, in_id: ServerID) -> Option<String> {
+        self.get_config().get_log_address(in_id)
+    }
+
+    /// Adds and saves a server for a network of the bouncer, None if it is already known
+    pub fn add_bouncer_network(
+        &mut self,
+        in_id: ServerID,
+        network: &str,
+        name: Option<&str>,
+    ) -> anyhow::Result<Option<ServerID>> {
+        let id = self
+            .stored_config
+            .config
+            .add_bouncer_network(in_id, network, name);
+        if id.is_some() {
+            self.save()?;
+        }
+        Ok(id)
+    }
+
     pub fn get_channels(&mut self, in_id: ServerID) -> impl Iterator<Item = &str> {
         self.stored_config.config.get_channels(in_id)
     }
@@ -172,6 +245,28 @@ impl Model {
         self.color_generator.generate_color(input)
     }
 
+    pub fn get_ban_mask(&self) -> BanMask {
+        self.stored_config.config.moderation.ban_mask
+    }
+
+    pub fn get_dcc_config(&self) -> &Dcc {
+        &self.stored_config.config.dcc
+    }
+
+    pub fn get_download_dir(&self) -> std::path::PathBuf {
+        self.stored_config
+            .config
+            .dcc
+            .download_dir
+            .clone()
+            .or_else(crate::project_path::ProjectPath::download_dir)
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+    }
+
+    pub fn get_ctcp_config(&self) -> &Ctcp {
+        &self.stored_config.config.ctcp
+    }
+
     pub fn is_topic_ui_enabled(&self) -> bool {
         self.stored_config.config.topic.enabled
     }
@@ -0,0 +1,127 @@
+use super::config::BanMask;
+use clown_core::isupport::ISupport;
+use clown_core::mode::ModeChange;
+
+/// A nickname never contains these characters, the target is already a mask or an extban
+pub fn is_mask(target: &str) -> bool {
+    target.contains(['!', '@', '*', '?', ':'])
+}
+
+/// `*!*@*.example.com` for `host.example.com`, `*!*@1.2.3.*` for an IPv4 address
+fn domain(host: &str) -> String {
+    if host.parse::<std::net::Ipv4Addr>().is_ok()
+        && let Some((network, _)) = host.rsplit_once('.')
+    {
+        format!("{network}.*")
+    } else if host.contains(['/', ':']) {
+        //Cloaks and IPv6 addresses are kept whole
+        host.to_string()
+    } else {
+        match host.split_once('.') {
+            Some((_, rest)) if rest.contains('.') => format!("*.{rest}"),
+            _ => host.to_string(),
+        }
+    }
+}
+
+/// Mask of a user from its user@host, the nickname alone when the host is unknown
+pub fn ban_mask(style: BanMask, nick: &str, user_host: Option<&str>) -> String {
+    let Some((user, host)) = user_host.and_then(|v| v.split_once('@')) else {
+        return format!("{nick}!*@*");
+    };
+    //The ident is checked by the server when it does not start with ~
+    let user = user.trim_start_matches('~');
+    match style {
+        BanMask::Host => format!("*!*@{host}"),
+        BanMask::User => format!("*!*{user}@{host}"),
+        BanMask::Domain => format!("*!*@{}", domain(host)),
+        BanMask::Nick => format!("{nick}!*@*"),
+        BanMask::Full => format!("{nick}!*{user}@{host}"),
+    }
+}
+
+/// Mode and prefix of the masks of a quiet: +q, or an extended ban
+/// +b ~q: (UnrealIRCd) or +b m: (InspIRCd mute)
+pub fn quiet_mode(isupport: &ISupport) -> anyhow::Result<(char, String)> {
+    if isupport.is_list_mode('q') {
+        Ok(('q', String::new()))
+    } else if let Some(prefix) = isupport
+        .get_extban('q')
+        .or_else(|| isupport.get_extban('m'))
+    {
+        Ok(('b', prefix))
+    } else {
+        anyhow::bail!("The server does not support quiets")
+    }
+}
+
+/// The same mode set or removed on each parameter: +ooo alice bob carol
+pub fn changes(is_set: bool, mode: char, parameters: Vec<String>) -> Vec<ModeChange> {
+    parameters
+        .into_iter()
+        .map(|parameter| ModeChange {
+            is_set,
+            mode,
+            parameter: Some(parameter),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ban_mask() {
+        let user_host = Some("~alice@host.example.com");
+        assert_eq!(
+            ban_mask(BanMask::Host, "alice", user_host),
+            "*!*@host.example.com"
+        );
+        assert_eq!(
+            ban_mask(BanMask::User, "alice", user_host),
+            "*!*alice@host.example.com"
+        );
+        assert_eq!(
+            ban_mask(BanMask::Domain, "alice", user_host),
+            "*!*@*.example.com"
+        );
+        assert_eq!(ban_mask(BanMask::Nick, "alice", user_host), "alice!*@*");
+        assert_eq!(
+            ban_mask(BanMask::Full, "alice", user_host),
+            "alice!*alice@host.example.com"
+        );
+        assert_eq!(
+            ban_mask(BanMask::Domain, "bob", Some("bob@192.168.1.20")),
+            "*!*@192.168.1.*"
+        );
+        assert_eq!(
+            ban_mask(BanMask::Domain, "bob", Some("bob@user/bob")),
+            "*!*@user/bob"
+        );
+        assert_eq!(ban_mask(BanMask::Host, "bob", None), "bob!*@*");
+    }
+
+    #[test]
+    fn test_is_mask() {
+        assert!(is_mask("*!*@host"));
+        assert!(is_mask("$a:alice"));
+        assert!(!is_mask("alice"));
+        assert!(!is_mask("[bob]"));
+    }
+
+    #[test]
+    fn test_quiet_mode() {
+        let mut isupport = ISupport::default();
+        assert!(quiet_mode(&isupport).is_err());
+        isupport.update(&["EXTBAN=~,qjncrRa"]);
+        assert_eq!(quiet_mode(&isupport).unwrap(), ('b', "~q:".to_string()));
+        isupport.update(&["CHANMODES=eIbq,k,flj,CFLMPQScgimnprstuz"]);
+        assert_eq!(quiet_mode(&isupport).unwrap(), ('q', String::new()));
+
+        //InspIRCd mutes with an extended ban without prefix
+        let mut isupport = ISupport::default();
+        isupport.update(&["EXTBAN=,ABCDMNOQRSTUacjmnprswz"]);
+        assert_eq!(quiet_mode(&isupport).unwrap(), ('b', "m:".to_string()));
+    }
+}
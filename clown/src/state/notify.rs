@@ -0,0 +1,170 @@
+use clown_core::command::Command;
+
+/// The ISUPPORT is sent after the welcome, it tells if MONITOR is supported
+const NOTIFY_START_DELAY: u64 = 5;
+/// Without MONITOR, the presence is asked periodically with ISON
+const ISON_POLL_TIMER: u64 = 60;
+/// Keep the MONITOR lines under the size limit of a message
+const NB_TARGETS_PER_MONITOR: usize = 20;
+
+/// Follows the presence of the notify list with MONITOR, or ISON if the server does not support it
+#[derive(Debug, Default)]
+pub struct NotifyPoller {
+    next_poll: Option<std::time::Instant>,
+    is_monitoring: bool,
+    /// Targets of MONITOR on the server
+    monitored: Vec<String>,
+}
+
+fn monitor_commands(sub_command: &str, nicknames: &[String]) -> Vec<Command> {
+    nicknames
+        .chunks(NB_TARGETS_PER_MONITOR)
+        .map(|targets| Command::Monitor(sub_command.to_string(), targets.to_vec()))
+        .collect()
+}
+
+fn contains(nicknames: &[String], nick: &str) -> bool {
+    nicknames.iter().any(|v| v.eq_ignore_ascii_case(nick))
+}
+
+impl NotifyPoller {
+    /// Called once the registration is done
+    pub fn start(&mut self, now: std::time::Instant) {
+        self.next_poll = Some(now + std::time::Duration::from_secs(NOTIFY_START_DELAY));
+    }
+
+    /// Commands to send to the server, the MONITOR list is sent only once
+    pub fn poll(
+        &mut self,
+        now: std::time::Instant,
+        nicknames: &[String],
+        monitor_limit: Option<usize>,
+    ) -> Vec<Command> {
+        if self.is_monitoring || self.next_poll.is_none_or(|v| v > now) {
+            return Vec::new();
+        }
+
+        if let Some(limit) = monitor_limit {
+            self.is_monitoring = true;
+            self.next_poll = None;
+            let nicknames = nicknames.get(..limit).unwrap_or(nicknames);
+            self.monitored = nicknames.to_vec();
+            monitor_commands("+", nicknames)
+        } else {
+            self.next_poll = Some(now + std::time::Duration::from_secs(ISON_POLL_TIMER));
+            if nicknames.is_empty() {
+                Vec::new()
+            } else {
+                vec![Command::Ison(nicknames.to_vec())]
+            }
+        }
+    }
+
+    /// The notify list has been edited, only its differences are sent to MONITOR
+    pub fn update(&mut self, nicknames: &[String], monitor_limit: Option<usize>) -> Vec<Command> {
+        let Some(limit) = monitor_limit.filter(|_| self.is_monitoring) else {
+            return Vec::new();
+        };
+        let nicknames = nicknames.get(..limit).unwrap_or(nicknames);
+        let removed = self
+            .monitored
+            .iter()
+            .filter(|v| !contains(nicknames, v))
+            .cloned()
+            .collect::<Vec<_>>();
+        let added = nicknames
+            .iter()
+            .filter(|v| !contains(&self.monitored, v))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.monitored = nicknames.to_vec();
+        let mut commands = monitor_commands("-", &removed);
+        commands.extend(monitor_commands("+", &added));
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn nicknames(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("nick{i}")).collect()
+    }
+
+    #[test]
+    fn test_not_started() {
+        let mut poller = NotifyPoller::default();
+        assert!(poller.poll(Instant::now(), &nicknames(2), None).is_empty());
+    }
+
+    #[test]
+    fn test_ison_polling() {
+        let mut poller = NotifyPoller::default();
+        let now = Instant::now();
+        poller.start(now);
+        assert!(poller.poll(now, &nicknames(2), None).is_empty());
+
+        let now = now + Duration::from_secs(NOTIFY_START_DELAY);
+        let commands = poller.poll(now, &nicknames(2), None);
+        assert!(matches!(commands.as_slice(), [Command::Ison(nicks)] if nicks.len() == 2));
+        assert!(poller.poll(now, &nicknames(2), None).is_empty());
+
+        let now = now + Duration::from_secs(ISON_POLL_TIMER);
+        assert_eq!(poller.poll(now, &nicknames(2), None).len(), 1);
+    }
+
+    #[test]
+    fn test_monitor_once() {
+        let mut poller = NotifyPoller::default();
+        let start = Instant::now();
+        poller.start(start);
+        let now = start + Duration::from_secs(NOTIFY_START_DELAY);
+
+        let commands = poller.poll(now, &nicknames(45), Some(100));
+        assert_eq!(commands.len(), 3);
+        assert!(matches!(commands.first(),
+            Some(Command::Monitor(sub_command, targets)) if sub_command == "+" && targets.len() == 20));
+
+        let now = now + Duration::from_secs(ISON_POLL_TIMER);
+        assert!(poller.poll(now, &nicknames(45), Some(100)).is_empty());
+    }
+
+    #[test]
+    fn test_update_monitor() {
+        let mut poller = NotifyPoller::default();
+        let nicks = |v: &[&str]| v.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        //Nothing is monitored before the registration
+        assert!(poller.update(&nicks(&["alice"]), Some(100)).is_empty());
+
+        let start = Instant::now();
+        poller.start(start);
+        let now = start + Duration::from_secs(NOTIFY_START_DELAY);
+        poller.poll(now, &nicks(&["alice", "bob"]), Some(100));
+
+        let commands = poller.update(&nicks(&["Bob", "carl"]), Some(100));
+        assert!(matches!(commands.as_slice(), [
+            Command::Monitor(removed, r),
+            Command::Monitor(added, a),
+        ] if removed == "-" && *r == ["alice"] && added == "+" && *a == ["carl"]));
+        assert!(
+            poller
+                .update(&nicks(&["bob", "carl"]), Some(100))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_monitor_limit() {
+        let mut poller = NotifyPoller::default();
+        let start = Instant::now();
+        poller.start(start);
+        let now = start + Duration::from_secs(NOTIFY_START_DELAY);
+
+        let commands = poller.poll(now, &nicknames(5), Some(3));
+        assert!(
+            matches!(commands.as_slice(), [Command::Monitor(_, targets)] if targets.len() == 3)
+        );
+    }
+}
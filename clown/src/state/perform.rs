@@ -0,0 +1,109 @@
+use clown_core::command::Command;
+use std::time::Duration;
+
+/// Pseudo command delaying the entries after it, e.g. `/wait 2`
+const WAIT_COMMAND: &str = "wait";
+
+/// Replaces $nick and $server in an entry of the perform list
+pub fn expand(entry: &str, nick: &str, server: &str) -> String {
+    entry.replace("$nick", nick).replace("$server", server)
+}
+
+/// The entries to run after the welcome, with the delay since the registration
+pub fn schedule(entries: &[String], nick: &str, server: &str) -> Vec<(Duration, String)> {
+    let mut delay = Duration::ZERO;
+    let mut scheduled = Vec::new();
+    for entry in entries.iter().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        let line = entry.strip_prefix('/').unwrap_or(entry);
+        let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+        if name.eq_ignore_ascii_case(WAIT_COMMAND) {
+            match rest
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .map(Duration::try_from_secs_f64)
+            {
+                Some(Ok(wait)) => delay = delay.saturating_add(wait),
+                _ => tracing::error!("Invalid perform delay {rest}"),
+            }
+        } else {
+            scheduled.push((delay, expand(entry, nick, server)));
+        }
+    }
+    scheduled
+}
+
+/// An entry is a client command (/msg, /notice, /join, /mode, /nick, /quote)
+/// or a raw IRC line, e.g. `OPER name password`
+pub fn to_command(entry: &str) -> Option<Command> {
+    let line = entry.trim();
+    let line = line.strip_prefix('/').unwrap_or(line);
+    let (name, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim_start();
+    let split = |rest: &str| -> Option<(String, String)> {
+        let (target, content) = rest.split_once(' ')?;
+        Some((target.to_string(), content.trim_start().to_string()))
+    };
+    match name.to_lowercase().as_str() {
+        "" => None,
+        "msg" => split(rest).map(|(target, content)| Command::PrivMsg(target, content)),
+        "notice" => split(rest).map(|(target, content)| Command::Notice(target, content)),
+        "join" if !rest.is_empty() => Some(Command::Join(rest.to_string())),
+        "nick" if !rest.is_empty() => Some(Command::Nick(rest.to_string())),
+        "mode" => split(rest).map(|(target, modes)| Command::Mode(target, modes)),
+        "quote" | "raw" if !rest.is_empty() => Some(Command::Unknown(rest.to_string())),
+        _ if rest.is_empty() => Some(Command::Unknown(name.to_uppercase())),
+        _ => Some(Command::Unknown(format!("{} {rest}", name.to_uppercase()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let entries = vec![
+            "/msg NickServ IDENTIFY $nick secret".to_string(),
+            "/wait 2".to_string(),
+            "".to_string(),
+            "/mode $nick +x".to_string(),
+            "/wait 0.5".to_string(),
+            "/wait abc".to_string(),
+            "/join #$server".to_string(),
+        ];
+        assert_eq!(
+            schedule(&entries, "clown", "libera"),
+            vec![
+                (
+                    Duration::ZERO,
+                    "/msg NickServ IDENTIFY clown secret".to_string()
+                ),
+                (Duration::from_secs(2), "/mode clown +x".to_string()),
+                (Duration::from_millis(2500), "/join #libera".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_command() {
+        assert!(matches!(
+            to_command("/msg NickServ IDENTIFY pass"),
+            Some(Command::PrivMsg(target, content)) if target == "NickServ" && content == "IDENTIFY pass"
+        ));
+        assert!(matches!(
+            to_command("/mode clown +x"),
+            Some(Command::Mode(target, modes)) if target == "clown" && modes == "+x"
+        ));
+        assert!(matches!(
+            to_command("/oper admin pass"),
+            Some(Command::Unknown(line)) if line == "OPER admin pass"
+        ));
+        assert!(matches!(
+            to_command("/quote PRIVMSG #a :b c"),
+            Some(Command::Unknown(line)) if line == "PRIVMSG #a :b c"
+        ));
+        assert!(to_command("/msg NickServ").is_none());
+        assert!(to_command("/").is_none());
+    }
+}
@@ -0,0 +1,324 @@
+use super::config::{Recover, Services};
+use clown_core::command::Command;
+use std::time::{Duration, Instant};
+
+/// The channels are joined anyway if NickServ does not answer
+const IDENTIFY_TIMEOUT: u64 = 10;
+/// Length of RFC 1459, while the server has not advertised NICKLEN
+const DEFAULT_NICKLEN: usize = 9;
+/// Digits replacing the end of a nickname which cannot grow
+const MAX_NICK_SUFFIX: u32 = 9;
+/// Time for the ghost to be disconnected before taking its nickname
+pub const GHOST_NICK_DELAY: Duration = Duration::from_secs(2);
+
+/// Their notices are shown in the server buffer
+const SERVICES: [&str; 8] = [
+    "NickServ", "ChanServ", "MemoServ", "OperServ", "HostServ", "BotServ", "SaslServ", "Global",
+];
+
+const IDENTIFIED_REPLIES: [&str; 4] = [
+    "you are now identified",
+    "you are successfully identified",
+    "password accepted",
+    "you are now logged in",
+];
+
+/// Messages to NickServ ending with a password
+const PASSWORD_COMMANDS: [&str; 3] = ["IDENTIFY", "REGAIN", "GHOST"];
+
+const FAILED_REPLIES: [&str; 4] = [
+    "invalid password",
+    "password incorrect",
+    "incorrect password",
+    "is not registered",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum IdentifyReply {
+    Identified,
+    Failed,
+}
+
+pub fn is_service(source: &str, nickserv: &str) -> bool {
+    source.eq_ignore_ascii_case(nickserv) || SERVICES.iter().any(|v| v.eq_ignore_ascii_case(source))
+}
+
+/// Result of the identification, read from a NOTICE of NickServ
+pub fn parse_reply(message: &str) -> Option<IdentifyReply> {
+    let message = message.to_lowercase();
+    if IDENTIFIED_REPLIES.iter().any(|v| message.contains(v)) {
+        Some(IdentifyReply::Identified)
+    } else if FAILED_REPLIES.iter().any(|v| message.contains(v)) {
+        Some(IdentifyReply::Failed)
+    } else {
+        None
+    }
+}
+
+/// Our message to NickServ with its password hidden, None if it has no password
+pub fn mask_password(target: &str, content: &str, nickserv: &str) -> Option<String> {
+    if !target.eq_ignore_ascii_case(nickserv) {
+        return None;
+    }
+    let (command, rest) = content.trim().split_once(' ')?;
+    if !PASSWORD_COMMANDS
+        .iter()
+        .any(|v| v.eq_ignore_ascii_case(command))
+    {
+        return None;
+    }
+    Some(match rest.trim().rsplit_once(' ') {
+        Some((nick, _)) => format!("{command} {nick} ********"),
+        None => format!("{command} ********"),
+    })
+}
+
+/// Commands sent to NickServ after the registration, the primary nickname is taken back if needed
+pub fn login_commands(services: &Services, primary: &str, current: &str) -> Vec<Command> {
+    let Some(password) = services.password.as_deref() else {
+        return Vec::new();
+    };
+    let message = |content: String| Command::PrivMsg(services.nickserv.clone(), content);
+    if primary.eq_ignore_ascii_case(current) {
+        return vec![message(format!("IDENTIFY {password}"))];
+    }
+    let mut commands = vec![message(format!("IDENTIFY {primary} {password}"))];
+    match services.recover {
+        Recover::Regain => commands.push(message(format!("REGAIN {primary} {password}"))),
+        Recover::Ghost => commands.push(message(format!("GHOST {primary} {password}"))),
+        Recover::None => {}
+    }
+    commands
+}
+
+/// Nickname of the registration and identification to NickServ of a connection
+#[derive(Debug, Default)]
+pub struct ServicesState {
+    nickname: String,
+    /// Digit ending the nickname once it has reached NICKLEN
+    suffix: u32,
+    is_registered: bool,
+    /// Channels refused before the identification
+    delayed_channels: Vec<String>,
+    deadline: Option<Instant>,
+}
+
+impl ServicesState {
+    pub fn new(nickname: String) -> Self {
+        Self {
+            nickname,
+            ..Default::default()
+        }
+    }
+
+    /// The nickname is used, another one is tried until the registration is done.
+    /// It grows with '_' up to NICKLEN, then its last character is replaced by a digit.
+    pub fn nick_in_use(&mut self, nicklen: Option<usize>) -> Option<String> {
+        if self.is_registered {
+            return None;
+        }
+        let length = self.nickname.chars().count();
+        let nicklen = nicklen.unwrap_or(DEFAULT_NICKLEN.max(length)).max(2);
+        if length < nicklen {
+            self.nickname.push('_');
+        } else {
+            //A longer one would be truncated back to the nickname in use
+            self.suffix += 1;
+            if self.suffix > MAX_NICK_SUFFIX {
+                return None;
+            }
+            let base = self.nickname.chars().take(nicklen - 1).collect::<String>();
+            self.nickname = format!("{base}{}", self.suffix);
+        }
+        Some(self.nickname.clone())
+    }
+
+    /// Nickname accepted by the server
+    pub fn registered(&mut self) -> &str {
+        self.is_registered = true;
+        &self.nickname
+    }
+
+    /// The channels refused until the identification are joined again once identified
+    pub fn wait_identification(&mut self, now: Instant) {
+        self.deadline = Some(now + Duration::from_secs(IDENTIFY_TIMEOUT));
+    }
+
+    /// A channel needs an account, false if the identification is not awaited
+    pub fn delay_join(&mut self, channel: &str) -> bool {
+        if self.deadline.is_none() {
+            return false;
+        }
+        if !self
+            .delayed_channels
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case(channel))
+        {
+            self.delayed_channels.push(channel.to_string());
+        }
+        true
+    }
+
+    /// Identified, or failed, the delayed channels can be joined
+    pub fn identified(&mut self) -> Vec<String> {
+        self.deadline = None;
+        std::mem::take(&mut self.delayed_channels)
+    }
+
+    /// The delayed channels, once NickServ has not answered in time
+    pub fn poll(&mut self, now: Instant) -> Vec<String> {
+        if self.deadline.is_some_and(|v| v <= now) {
+            self.identified()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn services(recover: Recover) -> Services {
+        Services {
+            password: Some("secret".to_string()),
+            recover,
+            ..Services::default()
+        }
+    }
+
+    fn lines(commands: &[Command]) -> Vec<String> {
+        commands
+            .iter()
+            .filter_map(|v| match v {
+                Command::PrivMsg(target, content) => Some(format!("{target} {content}")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_login_commands() {
+        assert!(login_commands(&Services::default(), "clown", "clown").is_empty());
+        assert_eq!(
+            lines(&login_commands(
+                &services(Recover::Regain),
+                "clown",
+                "Clown"
+            )),
+            ["NickServ IDENTIFY secret"]
+        );
+        assert_eq!(
+            lines(&login_commands(
+                &services(Recover::Regain),
+                "clown",
+                "clown_"
+            )),
+            [
+                "NickServ IDENTIFY clown secret",
+                "NickServ REGAIN clown secret"
+            ]
+        );
+        assert_eq!(
+            lines(&login_commands(
+                &services(Recover::Ghost),
+                "clown",
+                "clown_"
+            )),
+            [
+                "NickServ IDENTIFY clown secret",
+                "NickServ GHOST clown secret"
+            ]
+        );
+        assert_eq!(
+            lines(&login_commands(&services(Recover::None), "clown", "clown_")),
+            ["NickServ IDENTIFY clown secret"]
+        );
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(
+            parse_reply("You are now identified for \x02clown\x02."),
+            Some(IdentifyReply::Identified)
+        );
+        assert_eq!(
+            parse_reply("Password accepted - you are now recognized."),
+            Some(IdentifyReply::Identified)
+        );
+        assert_eq!(
+            parse_reply("Invalid password for \x02clown\x02."),
+            Some(IdentifyReply::Failed)
+        );
+        assert_eq!(
+            parse_reply("This nickname is registered. Please choose a different nickname"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mask_password() {
+        assert_eq!(
+            mask_password("nickserv", "identify secret", "NickServ").as_deref(),
+            Some("identify ********")
+        );
+        assert_eq!(
+            mask_password("NickServ", "GHOST clown secret", "NickServ").as_deref(),
+            Some("GHOST clown ********")
+        );
+        assert_eq!(mask_password("NickServ", "INFO clown", "NickServ"), None);
+        assert_eq!(mask_password("NickServ", "IDENTIFY", "NickServ"), None);
+        assert_eq!(mask_password("alice", "IDENTIFY secret", "NickServ"), None);
+    }
+
+    #[test]
+    fn test_is_service() {
+        assert!(is_service("chanserv", "NickServ"));
+        assert!(is_service("NickServ", "NickServ"));
+        assert!(is_service("NS", "NS"));
+        assert!(!is_service("alice", "NickServ"));
+    }
+
+    #[test]
+    fn test_nick_in_use() {
+        let mut state = ServicesState::new("clown".to_string());
+        assert_eq!(state.nick_in_use(None).as_deref(), Some("clown_"));
+        assert_eq!(state.nick_in_use(None).as_deref(), Some("clown__"));
+        assert_eq!(state.registered(), "clown__");
+        //A /nick refused after the registration
+        assert!(state.nick_in_use(None).is_none());
+
+        let mut state = ServicesState::new("clown".to_string());
+        assert_eq!(state.nick_in_use(Some(6)).as_deref(), Some("clown_"));
+        assert_eq!(state.nick_in_use(Some(6)).as_deref(), Some("clown1"));
+        assert_eq!(state.nick_in_use(Some(6)).as_deref(), Some("clown2"));
+        for _ in 3..=MAX_NICK_SUFFIX {
+            assert!(state.nick_in_use(Some(6)).is_some());
+        }
+        assert!(state.nick_in_use(Some(6)).is_none());
+
+        //Without NICKLEN, a long nickname does not grow
+        let mut state = ServicesState::new("clown_the_great".to_string());
+        assert_eq!(state.nick_in_use(None).as_deref(), Some("clown_the_grea1"));
+    }
+
+    #[test]
+    fn test_delay_join() {
+        let mut state = ServicesState::new("clown".to_string());
+        let now = Instant::now();
+        assert!(!state.delay_join("#rust"));
+        state.wait_identification(now);
+        assert!(state.delay_join("#rust"));
+        assert!(state.delay_join("#Rust"));
+        assert!(state.poll(now).is_empty());
+        assert_eq!(state.identified(), ["#rust"]);
+        assert!(state.identified().is_empty());
+        assert!(!state.delay_join("#rust"));
+
+        state.wait_identification(now);
+        assert!(state.delay_join("#rust"));
+        let now = now + Duration::from_secs(IDENTIFY_TIMEOUT);
+        assert_eq!(state.poll(now), ["#rust"]);
+        assert!(state.poll(now).is_empty());
+    }
+}
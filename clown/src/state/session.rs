@@ -1,3 +1,5 @@
+use super::config::{Ctcp as CtcpConfig, Reconnect};
+use super::endpoint::EndpointRotation;
 use super::irc_model::IrcModel;
 use super::irc_model::IrcServerModel;
 use super::model::IRCConnection;
@@ -6,6 +8,7 @@ use anyhow::anyhow;
 use clown_core::client::LoginConfig;
 use clown_core::command::Command;
 use clown_core::conn::ConnectionConfig;
+use clown_core::ctcp::Ctcp;
 use clown_core::message::ServerMessage;
 use tokio::sync::mpsc;
 
@@ -28,46 +31,110 @@ impl SessionStatus<'_> {
     }
 }
 
+/// Part of the delay removed at random, the servers are not all retried at the same time
+const RETRY_JITTER: f32 = 0.2;
+
+/// Attempt waiting to be made
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryCountdown {
+    pub remaining: std::time::Duration,
+    pub attempt: usize,
+    /// None when retrying forever
+    pub max_retries: Option<usize>,
+}
+
 struct RetryState {
-    pub max_retry: usize,
-    pub counter: usize,
-    pub time_before_next_retry: std::time::Duration,
+    policy: Reconnect,
+    counter: usize,
+    next_retry: Option<std::time::Instant>,
 }
 
 impl RetryState {
-    pub fn new() -> Self {
+    pub fn new(policy: Reconnect) -> Self {
         Self {
-            max_retry: 5,
+            policy,
             counter: 0,
-            time_before_next_retry: std::time::Duration::from_secs(5),
+            next_retry: None,
         }
     }
 
-    pub fn get_next_retry(&self) -> std::time::Duration {
-        self.time_before_next_retry
-            .mul_f32((self.counter + 1) as f32)
+    fn can_retry(&self) -> bool {
+        self.policy.max_retries == 0 || self.counter < self.policy.max_retries
+    }
+
+    /// Exponential backoff, jitter is between 0 and 1
+    pub fn get_next_retry(&self, jitter: f32) -> std::time::Duration {
+        let exponent = u32::try_from(self.counter.saturating_sub(1)).unwrap_or(u32::MAX);
+        let delay = self
+            .policy
+            .delay
+            .saturating_mul(2u64.saturating_pow(exponent))
+            .min(self.policy.max_delay);
+        std::time::Duration::from_secs(delay).mul_f32(1.0 - RETRY_JITTER * jitter.clamp(0.0, 1.0))
     }
 
     pub fn increment_retry(&mut self) -> bool {
-        if self.counter < self.max_retry {
-            self.counter += 1;
+        self.next_retry = None;
+        if self.can_retry() {
+            self.counter = self.counter.saturating_add(1);
             true
         } else {
             false
         }
     }
+
+    pub fn get_countdown(&self, now: std::time::Instant) -> Option<RetryCountdown> {
+        self.next_retry.map(|time| RetryCountdown {
+            remaining: time.saturating_duration_since(now),
+            attempt: self.counter.saturating_add(1),
+            max_retries: (self.policy.max_retries > 0).then_some(self.policy.max_retries),
+        })
+    }
+}
+
+/// Random value over the whole range of u64, from the random keys of the standard library
+fn random_value() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+/// Random value between 0 and 1
+fn random_jitter() -> f32 {
+    (random_value() % 1000) as f32 / 1000.0
+}
+
+/// Buffers opened when the connection was lost
+#[derive(Default)]
+struct OpenBuffers {
+    channels: Vec<String>,
+    queries: Vec<String>,
 }
 
 struct ServerSlot {
     retry: RetryState,
     connection: Option<IRCConnection>,
+    /// The server has asked to reconnect with TLS
+    sts_upgrade: bool,
+    buffers: OpenBuffers,
+    endpoints: EndpointRotation,
+    /// Keys of the joined channels, lowercase channel -> key
+    keys: ahash::AHashMap<String, String>,
+    /// Incremented at each connection, so that what was scheduled for a previous one is dropped
+    generation: usize,
 }
 
 impl ServerSlot {
     fn new() -> Self {
         Self {
-            retry: RetryState::new(),
+            retry: RetryState::new(Reconnect::default()),
             connection: None,
+            sts_upgrade: false,
+            buffers: OpenBuffers::default(),
+            endpoints: EndpointRotation::default(),
+            keys: ahash::AHashMap::new(),
+            generation: 0,
         }
     }
 }
@@ -75,6 +142,11 @@ impl ServerSlot {
 pub struct Session {
     pub model: IrcModel,
     servers: Vec<ServerSlot>,
+    sts: super::sts::StsStore,
+    /// The transfers do not depend on the connection to the server
+    pub dcc: super::dcc::DccManager,
+    /// Sent in the reply to CTCP VERSION
+    build_version: String,
 }
 
 impl Session {
@@ -84,12 +156,43 @@ impl Session {
             servers: std::iter::repeat_with(ServerSlot::new)
                 .take(in_length)
                 .collect(),
+            sts: super::sts::StsStore::default(),
+            dcc: super::dcc::DccManager::default(),
+            build_version: String::from("clown"),
         }
     }
 
-    pub fn reset_retry(&mut self, id: ServerID) {
+    pub fn set_build_version(&mut self, version: String) {
+        self.build_version = version;
+    }
+
+    /// A server has been added to the config (bouncer networks)
+    pub fn add_server(&mut self) {
+        self.servers.push(ServerSlot::new());
+        self.model.add_server();
+    }
+
+    pub fn load_sts_policies(&mut self, path: std::path::PathBuf) {
+        self.sts = super::sts::StsStore::load(path);
+    }
+
+    pub fn reset_retry(&mut self, id: ServerID, policy: Reconnect) {
+        if let Some(server) = self.servers.get_mut(id.as_usize()) {
+            server.retry = RetryState::new(policy);
+        }
+    }
+
+    /// The endpoint of the last attempt is tried first on the next connection
+    pub fn set_endpoint_working(&mut self, id: ServerID) {
+        if let Some(server) = self.servers.get_mut(id.as_usize()) {
+            server.endpoints.set_working();
+        }
+    }
+
+    /// The policy of the config is applied to the next attempts
+    pub fn set_reconnect_policy(&mut self, id: ServerID, policy: Reconnect) {
         if let Some(server) = self.servers.get_mut(id.as_usize()) {
-            server.retry = RetryState::new();
+            server.retry.policy = policy;
         }
     }
 
@@ -117,18 +220,277 @@ impl Session {
     }
 
     pub fn send_command_current_server(&mut self, in_command: Command) -> anyhow::Result<()> {
+        let current_channel = self
+            .get_current_irc_server_model()
+            .and_then(|v| v.get_current_channel())
+            .map(|v| v.to_string());
         if let Some(current_id) = self.model.current_id
             && let Some(connection) = self.get_mut_connection(current_id)
         {
+            //The replies will be displayed in the buffer which has sent the command
+            if let Some(channel) = current_channel
+                && connection.capabilities.is_enabled("labeled-response")
+            {
+                let label = connection.labels.create(&channel);
+                connection
+                    .command_sender
+                    .send_labeled(label, in_command)
+                    .map_err(Into::into)
+            } else {
+                connection
+                    .command_sender
+                    .send(in_command)
+                    .map_err(Into::into)
+            }
+        } else {
+            anyhow::bail!("Not connected")
+        }
+    }
+
+    /// The echo of the command is dropped, it holds a password
+    pub fn send_hidden_command(
+        &mut self,
+        in_id: ServerID,
+        in_command: Command,
+    ) -> anyhow::Result<()> {
+        let Some(connection) = self.get_mut_connection(in_id) else {
+            anyhow::bail!("connection {in_id} not found")
+        };
+        if connection.capabilities.is_enabled("echo-message")
+            && connection.capabilities.is_enabled("labeled-response")
+        {
+            let label = connection.labels.create_hidden();
             connection
                 .command_sender
-                .send(in_command)
+                .send_labeled(label, in_command)
                 .map_err(Into::into)
         } else {
-            anyhow::bail!("Not connected")
+            connection
+                .command_sender
+                .send(in_command)
+                .map_err(Into::into)
+        }
+    }
+
+    /// Echo of a command sent by send_hidden_command
+    pub fn is_hidden_echo(&mut self, in_id: ServerID, message: &ServerMessage) -> bool {
+        self.get_mut_connection(in_id)
+            .is_some_and(|v| v.labels.is_hidden(message.tag("label").as_deref()))
+    }
+
+    /// Buffer of the command which has triggered this reply
+    pub fn route_labeled_reply(
+        &mut self,
+        in_id: ServerID,
+        message: &ServerMessage,
+        reply: &clown_core::response::Response,
+    ) -> Option<String> {
+        let connection = self.get_mut_connection(in_id)?;
+        connection.labels.route(
+            message.tag("label").as_deref(),
+            message.tag("batch").as_deref(),
+            reply,
+        )
+    }
+
+    /// A CAP reply has been negotiated by the core client, its STS policy is applied
+    pub fn handle_capabilities(&mut self, in_id: ServerID) -> anyhow::Result<()> {
+        let Some(connection) = self.get_connection(in_id) else {
+            return Ok(());
+        };
+        let config = connection.connection_config.clone();
+        let Some(policy) = connection.capabilities.get_sts() else {
+            return Ok(());
+        };
+        //STS does not apply to WebSocket gateways
+        if config.websocket.is_some() {
+            return Ok(());
+        }
+        if config.use_tls {
+            if let Some(duration) = policy.duration {
+                self.sts.set_policy(
+                    &config.address,
+                    config.port,
+                    duration,
+                    std::time::SystemTime::now(),
+                )?;
+            }
+        } else if let Some(port) = policy.port {
+            //The core client closes the connection, it will be reopened with TLS
+            self.sts.upgrade(&config.address, port);
+            if let Some(server) = self.servers.get_mut(in_id.as_usize()) {
+                server.sts_upgrade = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// True once, when the connection has been closed to be upgraded to TLS
+    pub fn take_sts_upgrade(&mut self, in_id: ServerID) -> bool {
+        self.servers
+            .get_mut(in_id.as_usize())
+            .is_some_and(|v| std::mem::take(&mut v.sts_upgrade))
+    }
+
+    pub fn is_capability_enabled(&self, in_id: ServerID, capability: &str) -> bool {
+        self.get_connection(in_id)
+            .is_some_and(|v| v.capabilities.is_enabled(capability))
+    }
+
+    /// The registration is done, the notify list can be followed
+    pub fn start_notify(&mut self, in_id: ServerID) {
+        if let Some(connection) = self.get_mut_connection(in_id) {
+            connection.notify.start(std::time::Instant::now());
         }
     }
 
+    /// Send the MONITOR or ISON commands if needed
+    pub fn poll_notify(&mut self, in_id: ServerID, nicknames: &[String]) -> anyhow::Result<()> {
+        let monitor_limit = self
+            .model
+            .get_server(in_id)
+            .and_then(|v| v.get_isupport().get_monitor_limit());
+        let commands = match self.get_mut_connection(in_id) {
+            Some(connection) => {
+                connection
+                    .notify
+                    .poll(std::time::Instant::now(), nicknames, monitor_limit)
+            }
+            None => Vec::new(),
+        };
+        for command in commands {
+            self.send_command(in_id, command)?;
+        }
+        Ok(())
+    }
+
+    /// The notify list has been edited, MONITOR and the known presences follow it
+    pub fn update_notify(&mut self, in_id: ServerID, nicknames: &[String]) -> anyhow::Result<()> {
+        let monitor_limit = self
+            .model
+            .get_server(in_id)
+            .and_then(|v| v.get_isupport().get_monitor_limit());
+        let commands = match self.get_mut_connection(in_id) {
+            Some(connection) => connection.notify.update(nicknames, monitor_limit),
+            None => Vec::new(),
+        };
+        self.model.retain_notify(in_id, nicknames);
+        for command in commands {
+            self.send_command(in_id, command)?;
+        }
+        Ok(())
+    }
+
+    /// Nickname to try when ours is used during the registration
+    pub fn alternative_nick(&mut self, in_id: ServerID) -> Option<String> {
+        let nicklen = self
+            .model
+            .get_server(in_id)
+            .and_then(|v| v.get_isupport().get_nick_length());
+        self.get_mut_connection(in_id)
+            .and_then(|v| v.services.nick_in_use(nicklen))
+    }
+
+    /// Nickname accepted by the server at the registration
+    pub fn registered_nick(&mut self, in_id: ServerID) -> Option<String> {
+        self.get_mut_connection(in_id)
+            .map(|v| v.services.registered().to_string())
+    }
+
+    /// The channels refused until identified to NickServ are joined again afterwards
+    pub fn wait_identification(&mut self, in_id: ServerID) {
+        if let Some(connection) = self.get_mut_connection(in_id) {
+            connection
+                .services
+                .wait_identification(std::time::Instant::now());
+        }
+    }
+
+    /// The channel needs an account, false if it will not be joined again
+    pub fn delay_join(&mut self, in_id: ServerID, channel: &str) -> bool {
+        self.get_mut_connection(in_id)
+            .is_some_and(|v| v.services.delay_join(channel))
+    }
+
+    /// Channels to join, after the identification or when NickServ has not answered
+    pub fn poll_identification(&mut self, in_id: ServerID, is_done: bool) -> Vec<String> {
+        match self.get_mut_connection(in_id) {
+            Some(connection) if is_done => connection.services.identified(),
+            Some(connection) => connection.services.poll(std::time::Instant::now()),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn add_invite(&mut self, in_id: ServerID, source: String, channel: String) {
+        if let Some(connection) = self.get_mut_connection(in_id) {
+            connection.invites.push(source, channel);
+        }
+    }
+
+    /// The invitation to accept on the current server, the last one without a channel
+    pub fn take_invite(
+        &mut self,
+        channel: Option<&str>,
+    ) -> Option<(ServerID, super::invite::Invite)> {
+        let current_id = self.model.current_id?;
+        self.get_mut_connection(current_id)
+            .and_then(|v| v.invites.take(channel))
+            .map(|v| (current_id, v))
+    }
+
+    /// INVITE <nick> to the channel, the current one by default
+    pub fn send_command_invite(
+        &mut self,
+        nick: String,
+        channel: Option<String>,
+    ) -> anyhow::Result<()> {
+        let channel = match channel {
+            Some(channel) => channel,
+            None => self
+                .get_current_channel()
+                .map(|v| v.to_string())
+                .ok_or_else(|| anyhow!("No channel to invite {nick} to"))?,
+        };
+        self.send_command_current_server(Command::Invite(nick, channel))
+    }
+
+    /// The current buffer, if it is a channel (CHANTYPES)
+    fn get_current_channel(&self) -> Option<&str> {
+        let server = self.get_current_irc_server_model()?;
+        server
+            .get_current_channel()
+            .filter(|v| server.is_channel(v))
+    }
+
+    /// Tell the target we are typing, the notifications are throttled
+    pub fn send_typing(
+        &mut self,
+        in_id: ServerID,
+        target: &str,
+        state: super::typing::TypingState,
+    ) -> anyhow::Result<()> {
+        let state = self
+            .get_mut_connection(in_id)
+            .and_then(|v| v.typing.update(target, state, std::time::Instant::now()));
+        if let Some(state) = state {
+            self.send_command(
+                in_id,
+                Command::Tagged(
+                    vec![("+typing".to_string(), state.as_tag().to_string())],
+                    Box::new(Command::TagMsg(target.to_string())),
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn get_batches_mut(
+        &mut self,
+        in_id: ServerID,
+    ) -> Option<&mut super::history::BatchCollector> {
+        self.get_mut_connection(in_id).map(|v| &mut v.batches)
+    }
+
     pub fn is_connected(&self, in_id: ServerID) -> bool {
         self.get_connection(in_id).is_some()
     }
@@ -162,16 +524,53 @@ impl Session {
         }
     }
 
+    /// Identifies the current connection, None when not connected
+    pub fn connection_generation(&self, in_id: ServerID) -> Option<usize> {
+        self.servers
+            .get(in_id.as_usize())
+            .filter(|v| v.connection.is_some())
+            .map(|v| v.generation)
+    }
+
     pub fn clear_connection(&mut self, in_id: ServerID) {
         if let Some(server_slot) = self.servers.get_mut(in_id.as_usize()) {
             server_slot.connection = None;
         }
     }
 
-    pub fn get_duration_before_retry(&self, in_id: ServerID) -> Option<std::time::Duration> {
+    /// Delay before the next attempt, None if there are no retries left
+    pub fn schedule_retry(&mut self, in_id: ServerID) -> Option<std::time::Duration> {
+        let server = self.servers.get_mut(in_id.as_usize())?;
+        if !server.retry.can_retry() {
+            return None;
+        }
+        let delay = server.retry.get_next_retry(random_jitter());
+        server.retry.next_retry = Some(std::time::Instant::now() + delay);
+        Some(delay)
+    }
+
+    pub fn get_retry_countdown(&self, in_id: ServerID) -> Option<RetryCountdown> {
         self.servers
             .get(in_id.as_usize())
-            .map(|s| s.retry.get_next_retry())
+            .filter(|v| v.connection.is_none())
+            .and_then(|v| v.retry.get_countdown(std::time::Instant::now()))
+    }
+
+    pub fn has_retry_countdown(&self) -> bool {
+        self.servers
+            .iter()
+            .any(|v| v.connection.is_none() && v.retry.next_retry.is_some())
+    }
+
+    /// Channels to join again and queries to reopen after a reconnection
+    pub fn take_open_buffers(&mut self, in_id: ServerID) -> (Vec<String>, Vec<String>) {
+        self.servers
+            .get_mut(in_id.as_usize())
+            .map(|v| {
+                let buffers = std::mem::take(&mut v.buffers);
+                (buffers.channels, buffers.queries)
+            })
+            .unwrap_or_default()
     }
 
     pub fn pull_all_server_message(&mut self) -> impl Iterator<Item = (ServerID, ServerMessage)> {
@@ -211,8 +610,38 @@ impl Session {
         }
     }
 
-    pub fn send_command_join(&mut self, server: String) -> anyhow::Result<()> {
-        self.send_command_current_server(Command::Join(server))
+    /// `#a,#b key1`, the keys are kept to join the channels again after a reconnection
+    pub fn send_command_join(&mut self, args: String) -> anyhow::Result<()> {
+        let channels = super::join::parse(&args);
+        if channels.is_empty() {
+            anyhow::bail!("No channel to join");
+        }
+        if let Some(current_id) = self.model.current_id
+            && let Some(slot) = self.servers.get_mut(current_id.as_usize())
+        {
+            for (channel, key) in &channels {
+                if let Some(key) = key {
+                    slot.keys.insert(channel.to_lowercase(), key.clone());
+                }
+            }
+        }
+        for command in super::join::commands(channels) {
+            self.send_command_current_server(command)?;
+        }
+        Ok(())
+    }
+
+    /// Key read from the modes of the channel, or given to /join
+    pub fn get_channel_key(&self, in_id: ServerID, channel: &str) -> Option<&str> {
+        self.model
+            .get_server(in_id)
+            .and_then(|v| v.get_channel_key(channel))
+            .or_else(|| {
+                self.servers
+                    .get(in_id.as_usize())
+                    .and_then(|v| v.keys.get(&channel.to_lowercase()))
+                    .map(String::as_str)
+            })
     }
 
     pub fn send_command_part(
@@ -235,6 +664,263 @@ impl Session {
         self.send_command_current_server(Command::Part(channel, reason))
     }
 
+    /// The channel given or the current one
+    fn channel_or_current(&self, channel: Option<String>) -> anyhow::Result<String> {
+        match channel {
+            Some(channel) => Ok(channel),
+            None => self
+                .get_current_channel()
+                .map(|v| v.to_string())
+                .ok_or_else(|| anyhow!("Not in a channel")),
+        }
+    }
+
+    /// MODE #chan b, e and I for the lists the server supports, they are returned
+    pub fn send_command_mode_lists(
+        &mut self,
+        channel: Option<String>,
+    ) -> anyhow::Result<(ServerID, String, Vec<char>)> {
+        let channel = self.channel_or_current(channel)?;
+        let server_id = self
+            .model
+            .current_id
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        let modes = self
+            .get_current_irc_server_model()
+            .map(|v| {
+                super::mode_list::LIST_MODES
+                    .into_iter()
+                    .filter(|mode| v.get_isupport().is_list_mode(*mode))
+                    .collect::<Vec<char>>()
+            })
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        for mode in &modes {
+            self.send_command(server_id, Command::Mode(channel.clone(), mode.to_string()))?;
+        }
+        Ok((server_id, channel, modes))
+    }
+
+    pub fn push_mode_list_entry(
+        &mut self,
+        in_id: ServerID,
+        channel: &str,
+        mode: char,
+        entry: clown_core::response::ListEntry,
+    ) {
+        if let Some(connection) = self.get_mut_connection(in_id) {
+            connection.mode_lists.push(channel, mode, entry);
+        }
+    }
+
+    pub fn finish_mode_list(
+        &mut self,
+        in_id: ServerID,
+        channel: &str,
+        mode: char,
+    ) -> Vec<clown_core::response::ListEntry> {
+        self.get_mut_connection(in_id)
+            .map(|v| v.mode_lists.finish(channel, mode))
+            .unwrap_or_default()
+    }
+
+    /// WHOIS, or WHOWAS for a user who has left, the replies are gathered for the target
+    pub fn send_command_whois(
+        &mut self,
+        in_id: ServerID,
+        nick: String,
+        is_whowas: bool,
+        target: super::whois::WhoisTarget,
+    ) -> anyhow::Result<()> {
+        if let Some(connection) = self.get_mut_connection(in_id) {
+            connection.whois.request(&nick, target);
+        }
+        let command = if is_whowas {
+            Command::Whowas(nick)
+        } else {
+            Command::Whois(nick)
+        };
+        self.send_command(in_id, command)
+    }
+
+    pub fn get_whois_mut(&mut self, in_id: ServerID) -> Option<&mut super::whois::WhoisCollector> {
+        self.get_mut_connection(in_id).map(|v| &mut v.whois)
+    }
+
+    /// LIST on the current server, the replies of a previous one are dropped
+    pub fn send_command_list(&mut self) -> anyhow::Result<ServerID> {
+        let server_id = self
+            .model
+            .current_id
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        if let Some(connection) = self.get_mut_connection(server_id) {
+            connection.channel_list = super::channel_list::ChannelListCollector::default();
+        }
+        self.send_command(server_id, Command::List(None))?;
+        Ok(server_id)
+    }
+
+    pub fn get_channel_list_mut(
+        &mut self,
+        in_id: ServerID,
+    ) -> Option<&mut super::channel_list::ChannelListCollector> {
+        self.get_mut_connection(in_id).map(|v| &mut v.channel_list)
+    }
+
+    /// The changes are sent in several MODE commands, according to ISUPPORT MODES
+    pub fn send_command_modes(
+        &mut self,
+        channel: Option<String>,
+        changes: Vec<clown_core::mode::ModeChange>,
+    ) -> anyhow::Result<()> {
+        let channel = self.channel_or_current(channel)?;
+        let max_modes = self
+            .get_current_irc_server_model()
+            .map(|v| v.get_isupport().get_max_modes())
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        for modes in clown_core::mode::batch_modes(&changes, max_modes) {
+            self.send_command_current_server(Command::Mode(channel.clone(), modes))?;
+        }
+        Ok(())
+    }
+
+    /// Changes of a channel of the server, which may not be the current one
+    pub fn send_channel_modes(
+        &mut self,
+        in_id: ServerID,
+        channel: String,
+        changes: Vec<clown_core::mode::ModeChange>,
+    ) -> anyhow::Result<()> {
+        let max_modes = self
+            .model
+            .get_server(in_id)
+            .map(|v| v.get_isupport().get_max_modes())
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        for modes in clown_core::mode::batch_modes(&changes, max_modes) {
+            self.send_command(in_id, Command::Mode(channel.clone(), modes))?;
+        }
+        Ok(())
+    }
+
+    /// /mode, the modes of a channel are batched, the ones of a user are sent as they are
+    pub fn send_command_mode(
+        &mut self,
+        target: Option<String>,
+        modes: String,
+    ) -> anyhow::Result<()> {
+        let target = self.channel_or_current(target)?;
+        if modes.is_empty()
+            || !self
+                .get_current_irc_server_model()
+                .is_some_and(|v| v.is_channel(&target))
+        {
+            return self.send_command_current_server(Command::Mode(target, modes));
+        }
+        let changes = self
+            .get_current_irc_server_model()
+            .map(|v| clown_core::mode::parse_channel_modes(&modes, v.get_isupport()))
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        self.send_command_modes(Some(target), changes)
+    }
+
+    /// The nicknames are replaced by a mask of their user@host, the masks are kept
+    pub fn get_ban_masks(
+        &self,
+        style: super::config::BanMask,
+        targets: Vec<String>,
+    ) -> Vec<String> {
+        let irc_model = self.get_current_irc_server_model();
+        targets
+            .into_iter()
+            .map(|target| {
+                if super::moderation::is_mask(&target) {
+                    return target;
+                }
+                let user_host = irc_model
+                    .and_then(|v| v.get_user(&target))
+                    .and_then(|v| v.get_user_host());
+                super::moderation::ban_mask(style, &target, user_host)
+            })
+            .collect()
+    }
+
+    /// +b or -b, the quiets use +q or the extended ban of the server
+    pub fn send_command_bans(
+        &mut self,
+        channel: Option<String>,
+        is_set: bool,
+        is_quiet: bool,
+        masks: Vec<String>,
+    ) -> anyhow::Result<()> {
+        let (mode, prefix) = if is_quiet {
+            let isupport = self
+                .get_current_irc_server_model()
+                .map(|v| v.get_isupport())
+                .ok_or_else(|| anyhow!("Not connected"))?;
+            super::moderation::quiet_mode(isupport)?
+        } else {
+            ('b', String::new())
+        };
+        let masks = masks
+            .into_iter()
+            .map(|mask| format!("{prefix}{mask}"))
+            .collect();
+        self.send_command_modes(channel, super::moderation::changes(is_set, mode, masks))
+    }
+
+    pub fn send_command_kick(
+        &mut self,
+        channel: Option<String>,
+        nick: String,
+        reason: Option<String>,
+    ) -> anyhow::Result<()> {
+        let channel = self.channel_or_current(channel)?;
+        self.send_command_current_server(Command::Kick(channel, nick, reason))
+    }
+
+    /// Address announced in the DCC offers
+    pub fn get_dcc_address(
+        &self,
+        in_id: ServerID,
+        configured: Option<&str>,
+    ) -> anyhow::Result<std::net::IpAddr> {
+        if let Some(address) = configured {
+            return Ok(address.parse()?);
+        }
+        let connection = self
+            .get_connection(in_id)
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        connection
+            .dcc_address
+            .get()
+            .copied()
+            .ok_or_else(|| anyhow!("Cannot find the local address, set dcc.address"))
+    }
+
+    /// Answers a CTCP query by a NOTICE, returns false if it is not answered
+    pub fn reply_ctcp(
+        &mut self,
+        in_id: ServerID,
+        source: &str,
+        query: &Ctcp,
+        settings: &CtcpConfig,
+    ) -> anyhow::Result<bool> {
+        let build_version = self.build_version.clone();
+        let Some(connection) = self.get_mut_connection(in_id) else {
+            anyhow::bail!("connection {in_id} not found")
+        };
+        let Some(reply) =
+            connection
+                .ctcp
+                .reply(query, settings, &build_version, std::time::Instant::now())
+        else {
+            return Ok(false);
+        };
+        connection
+            .command_sender
+            .send(Command::Notice(source.to_string(), reply.to_string()))?;
+        Ok(true)
+    }
+
     pub fn send_command_action(&mut self, content: String) -> anyhow::Result<()> {
         if let Some(irc_model) = self.get_current_irc_server_model()
             && let Some(channel) = irc_model.get_current_channel()
@@ -242,7 +928,7 @@ impl Session {
             let channel = channel.to_string();
             self.send_command_current_server(clown_core::command::Command::PrivMsg(
                 channel,
-                format!("\x01ACTION {}\x01", content),
+                Ctcp::new("ACTION", Some(content)).to_string(),
             ))
         } else {
             anyhow::bail!("Not connected")
@@ -258,6 +944,24 @@ impl Session {
     }
 
     pub fn init_irc_model(&mut self, stored_nick: String, in_id: ServerID, server_name: String) {
+        //A failed attempt has nothing opened, the buffers of the lost connection are kept
+        if let Some(server) = self.model.get_server(in_id) {
+            let buffers = OpenBuffers {
+                channels: server.get_joined_channels(),
+                queries: server.get_queries(),
+            };
+            if let Some(slot) = self.servers.get_mut(in_id.as_usize()) {
+                //The key may have been changed by an operator since the join
+                for channel in &buffers.channels {
+                    if let Some(key) = server.get_channel_key(channel) {
+                        slot.keys.insert(channel.to_lowercase(), key.to_string());
+                    }
+                }
+                if !buffers.channels.is_empty() || !buffers.queries.is_empty() {
+                    slot.buffers = buffers;
+                }
+            }
+        }
         self.model.init_server(in_id, server_name, stored_nick);
     }
 
@@ -276,13 +980,31 @@ impl Session {
     pub fn init_connection(
         &mut self,
         in_id: ServerID,
-        connection_config: ConnectionConfig,
+        endpoints: Vec<ConnectionConfig>,
+        random_endpoints: bool,
         login_config: LoginConfig,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<ConnectionConfig> {
+        let server = self
+            .servers
+            .get_mut(in_id.as_usize())
+            .ok_or_else(|| anyhow!("Wrong ID {}", in_id))?;
+        let index = server.endpoints.select(
+            server.retry.counter,
+            endpoints.len(),
+            random_endpoints.then(random_value),
+        );
+        let connection_config = endpoints
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| anyhow!("No connection endpoint"))?;
         if connection_config.address.is_empty() {
             anyhow::bail!("Connection address is empty");
         }
 
+        let connection_config = self
+            .sts
+            .secure_config(connection_config, std::time::SystemTime::now());
+        let services = super::services::ServicesState::new(login_config.nickname.clone());
         let mut client = clown_core::client::Client::new(login_config);
 
         let receiver = client
@@ -290,6 +1012,7 @@ impl Session {
             .ok_or_else(|| anyhow!("Failed to get message receiver"))?;
 
         let command_sender = client.command_sender();
+        let capabilities = client.capabilities();
 
         let (error_sender, error_receiver) = mpsc::channel(10);
 
@@ -301,6 +1024,19 @@ impl Session {
             anyhow::bail!("No retries left");
         }
 
+        server.generation += 1;
+        let effective_config = connection_config.clone();
+        let dcc_address = std::sync::Arc::new(std::sync::OnceLock::new());
+        {
+            let dcc_address = dcc_address.clone();
+            let (address, port) = (effective_config.address.clone(), effective_config.port);
+            //The server name is resolved, out of the interface thread
+            tokio::task::spawn_blocking(move || {
+                if let Some(local) = super::dcc::local_address(&address, port) {
+                    let _ = dcc_address.set(local);
+                }
+            });
+        }
         server.connection = Some(IRCConnection {
             command_sender,
             error_receiver,
@@ -311,8 +1047,92 @@ impl Session {
                     let _ = error_sender.send(format!("Connection error: {err}")).await;
                 }
             }),
+            capabilities,
+            batches: super::history::BatchCollector::default(),
+            labels: super::label::LabelTracker::default(),
+            notify: super::notify::NotifyPoller::default(),
+            typing: super::typing::TypingThrottle::default(),
+            ctcp: super::ctcp::CtcpResponder::default(),
+            services,
+            invites: super::invite::PendingInvites::default(),
+            mode_lists: super::mode_list::ModeListCollector::default(),
+            channel_list: super::channel_list::ChannelListCollector::default(),
+            whois: super::whois::WhoisCollector::default(),
+            dcc_address,
+            connection_config: effective_config.clone(),
         });
 
-        Ok(())
+        Ok(effective_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_backoff() {
+        let mut retry = RetryState::new(Reconnect {
+            max_retries: 3,
+            delay: 5,
+            max_delay: 12,
+        });
+        assert!(retry.increment_retry());
+        assert_eq!(retry.get_next_retry(0.0), Duration::from_secs(5));
+        assert!(retry.increment_retry());
+        assert_eq!(retry.get_next_retry(0.0), Duration::from_secs(10));
+        assert_eq!(retry.get_next_retry(1.0), Duration::from_secs(8));
+        assert!(retry.increment_retry());
+        //Capped
+        assert_eq!(retry.get_next_retry(0.0), Duration::from_secs(12));
+        assert!(!retry.increment_retry());
+
+        let mut retry = RetryState::new(Reconnect {
+            max_retries: 0,
+            ..Reconnect::default()
+        });
+        for _ in 0..100 {
+            assert!(retry.increment_retry());
+        }
+        assert_eq!(retry.get_next_retry(0.0), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_countdown() {
+        let mut session = Session::new(1);
+        let id = ServerID::new(0);
+        assert!(session.get_retry_countdown(id).is_none());
+
+        session.set_reconnect_policy(id, Reconnect::default());
+        let delay = session.schedule_retry(id).unwrap();
+        assert!(delay <= Duration::from_secs(5));
+        let countdown = session.get_retry_countdown(id).unwrap();
+        assert_eq!(countdown.attempt, 1);
+        assert_eq!(countdown.max_retries, Some(5));
+        assert!(session.has_retry_countdown());
+    }
+
+    #[test]
+    fn test_open_buffers_kept_after_failed_attempt() {
+        let mut session = Session::new(1);
+        let id = ServerID::new(0);
+        session.init_irc_model("Me".into(), id, "TEST".into());
+        session
+            .model
+            .handle_action(&crate::message_event::MessageEvent::Join(
+                id,
+                "#rust".into(),
+                "Me".into(),
+            ));
+
+        session.init_irc_model("Me".into(), id, "TEST".into());
+        //The attempt has failed, nothing was joined
+        session.init_irc_model("Me".into(), id, "TEST".into());
+        assert_eq!(
+            session.take_open_buffers(id),
+            (vec!["#rust".to_string()], Vec::new())
+        );
+        assert_eq!(session.take_open_buffers(id), (Vec::new(), Vec::new()));
     }
 }
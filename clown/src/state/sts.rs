@@ -0,0 +1,214 @@
+use clown_core::conn::ConnectionConfig;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Policy remembered for a host, the connections are made with TLS on its port
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredPolicy {
+    pub host: String,
+    pub port: u16,
+    /// Seconds since UNIX_EPOCH
+    pub expiry: u64,
+}
+
+/// Strict Transport Security policies, stored next to the logs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StsStore {
+    #[serde(default)]
+    policies: Vec<StoredPolicy>,
+    /// Asked over plaintext, they are not persisted
+    #[serde(skip)]
+    upgrades: Vec<(String, u16)>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+fn to_seconds(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .map(|v| v.as_secs())
+        .unwrap_or_default()
+}
+
+impl StsStore {
+    /// A missing or invalid file gives an empty store
+    pub fn load(path: PathBuf) -> Self {
+        let mut store = std::fs::read(&path)
+            .ok()
+            .and_then(|content| toml::from_slice::<StsStore>(&content).ok())
+            .unwrap_or_default();
+        store.path = Some(path);
+        store
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, toml::to_string(self)?)?;
+        }
+        Ok(())
+    }
+
+    /// Port of the secure connection if the host has a valid policy
+    pub fn get_port(&self, host: &str, now: std::time::SystemTime) -> Option<u16> {
+        let now = to_seconds(now);
+        self.upgrades
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(host))
+            .map(|(_, port)| *port)
+            .or_else(|| {
+                self.policies
+                    .iter()
+                    .find(|v| v.host.eq_ignore_ascii_case(host) && v.expiry > now)
+                    .map(|v| v.port)
+            })
+    }
+
+    /// The server has asked over plaintext to reconnect with TLS
+    pub fn upgrade(&mut self, host: &str, port: u16) {
+        self.upgrades.retain(|(h, _)| !h.eq_ignore_ascii_case(host));
+        self.upgrades.push((host.to_string(), port));
+    }
+
+    /// Received over TLS, a duration of 0 removes the policy
+    ///
+    /// The file is written when the policy changes, a refreshed expiry is only written once it
+    /// has moved by more than half of the duration
+    pub fn set_policy(
+        &mut self,
+        host: &str,
+        port: u16,
+        duration: u64,
+        now: std::time::SystemTime,
+    ) -> anyhow::Result<()> {
+        self.upgrades.retain(|(h, _)| !h.eq_ignore_ascii_case(host));
+        let expiry = to_seconds(now).saturating_add(duration);
+        let position = self
+            .policies
+            .iter()
+            .position(|v| v.host.eq_ignore_ascii_case(host));
+        let is_changed = match position {
+            Some(position) if duration == 0 => {
+                self.policies.remove(position);
+                true
+            }
+            Some(position) => self.policies.get_mut(position).is_some_and(|policy| {
+                let is_changed =
+                    policy.port != port || policy.expiry.abs_diff(expiry) > duration / 2;
+                if is_changed {
+                    policy.port = port;
+                    policy.expiry = expiry;
+                }
+                is_changed
+            }),
+            None if duration > 0 => {
+                self.policies.push(StoredPolicy {
+                    host: host.to_string(),
+                    port,
+                    expiry,
+                });
+                true
+            }
+            None => false,
+        };
+        if is_changed { self.save() } else { Ok(()) }
+    }
+
+    /// Never connect in plaintext to a host with a policy
+    pub fn secure_config(
+        &self,
+        mut config: ConnectionConfig,
+        now: std::time::SystemTime,
+    ) -> ConnectionConfig {
+        if !config.use_tls
+            && let Some(port) = self.get_port(&config.address, now)
+        {
+            config.use_tls = true;
+            config.port = port;
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn config(use_tls: bool, port: u16) -> ConnectionConfig {
+        ConnectionConfig {
+            address: "irc.example.com".to_string(),
+            port,
+            use_tls,
+            websocket: None,
+        }
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let mut store = StsStore::default();
+        let now = SystemTime::now();
+        assert_eq!(store.secure_config(config(false, 6667), now).port, 6667);
+
+        store.upgrade("IRC.example.com", 6697);
+        let secured = store.secure_config(config(false, 6667), now);
+        assert!(secured.use_tls);
+        assert_eq!(secured.port, 6697);
+        //The port of a TLS connection is kept
+        assert_eq!(store.secure_config(config(true, 7000), now).port, 7000);
+    }
+
+    #[test]
+    fn test_policy_expiry() {
+        let mut store = StsStore::default();
+        let now = SystemTime::now();
+        store.set_policy("irc.example.com", 6697, 300, now).unwrap();
+        assert_eq!(store.get_port("irc.example.com", now), Some(6697));
+        assert_eq!(
+            store.get_port("irc.example.com", now + Duration::from_secs(300)),
+            None
+        );
+
+        store.set_policy("irc.example.com", 6697, 0, now).unwrap();
+        assert_eq!(store.get_port("irc.example.com", now), None);
+    }
+
+    #[test]
+    fn test_save_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sts.toml");
+        let now = SystemTime::now();
+
+        let mut store = StsStore::load(path.clone());
+        store.set_policy("irc.example.com", 6697, 300, now).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        //The same policy is not written again
+        store
+            .set_policy("irc.example.com", 6697, 300, now + Duration::from_secs(10))
+            .unwrap();
+        assert!(!path.exists());
+        store
+            .set_policy("irc.example.com", 6697, 300, now + Duration::from_secs(200))
+            .unwrap();
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+        store.set_policy("irc.example.com", 7000, 300, now).unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("sts.toml");
+        let now = SystemTime::now();
+
+        let mut store = StsStore::load(path.clone());
+        store.set_policy("irc.example.com", 6697, 300, now).unwrap();
+        store.upgrade("other.example.com", 6697);
+
+        let store = StsStore::load(path);
+        assert_eq!(store.get_port("irc.example.com", now), Some(6697));
+        assert_eq!(store.get_port("other.example.com", now), None);
+    }
+}
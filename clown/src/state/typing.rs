@@ -0,0 +1,190 @@
+/// An active notification expires if it is not refreshed
+const ACTIVE_EXPIRATION: u64 = 6;
+/// A paused notification is kept longer than an active one
+const PAUSED_EXPIRATION: u64 = 30;
+/// Do not send an active notification more than once every 3 seconds
+const ACTIVE_THROTTLE: u64 = 3;
+
+/// Value of the +typing client tag
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TypingState {
+    Active,
+    Paused,
+    Done,
+}
+
+impl TypingState {
+    pub fn from_tag(value: &str) -> Option<Self> {
+        match value {
+            "active" => Some(Self::Active),
+            "paused" => Some(Self::Paused),
+            "done" => Some(Self::Done),
+            _ => None,
+        }
+    }
+
+    pub fn as_tag(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Paused => "paused",
+            Self::Done => "done",
+        }
+    }
+
+    fn expiration(&self) -> std::time::Duration {
+        match self {
+            Self::Active => std::time::Duration::from_secs(ACTIVE_EXPIRATION),
+            Self::Paused => std::time::Duration::from_secs(PAUSED_EXPIRATION),
+            Self::Done => std::time::Duration::ZERO,
+        }
+    }
+}
+
+/// Users currently typing in the channels of a server
+#[derive(Debug, Default)]
+pub struct TypingTracker {
+    typing: Vec<(String, String, TypingState, std::time::Instant)>, /*channel, nickname */
+}
+
+impl TypingTracker {
+    pub fn set(
+        &mut self,
+        channel: &str,
+        nickname: &str,
+        state: TypingState,
+        now: std::time::Instant,
+    ) {
+        self.typing.retain(|(c, n, _, _)| {
+            !(c.eq_ignore_ascii_case(channel) && n.eq_ignore_ascii_case(nickname))
+        });
+        if state != TypingState::Done {
+            self.typing
+                .push((channel.to_string(), nickname.to_string(), state, now));
+        }
+    }
+
+    /// The user has sent its message or has left
+    pub fn remove(&mut self, nickname: &str) {
+        self.typing
+            .retain(|(_, n, _, _)| !n.eq_ignore_ascii_case(nickname));
+    }
+
+    /// Nicknames typing in the channel, paused users are ignored
+    pub fn get_typing(&self, channel: &str, now: std::time::Instant) -> Vec<&str> {
+        self.typing
+            .iter()
+            .filter(|(c, _, state, time)| {
+                c.eq_ignore_ascii_case(channel)
+                    && *state == TypingState::Active
+                    && now.duration_since(*time) < state.expiration()
+            })
+            .map(|(_, n, _, _)| n.as_str())
+            .collect()
+    }
+}
+
+/// Limits the notifications sent while typing
+#[derive(Debug, Default)]
+pub struct TypingThrottle {
+    last_active: Option<(String, std::time::Instant)>, /*target */
+}
+
+impl TypingThrottle {
+    /// Returns the state to send, if any
+    pub fn update(
+        &mut self,
+        target: &str,
+        state: TypingState,
+        now: std::time::Instant,
+    ) -> Option<TypingState> {
+        match state {
+            TypingState::Active => {
+                if let Some((last_target, time)) = &self.last_active
+                    && last_target.eq_ignore_ascii_case(target)
+                    && now.duration_since(*time) < std::time::Duration::from_secs(ACTIVE_THROTTLE)
+                {
+                    return None;
+                }
+                self.last_active = Some((target.to_string(), now));
+                Some(state)
+            }
+            TypingState::Paused | TypingState::Done => {
+                if self
+                    .last_active
+                    .as_ref()
+                    .is_some_and(|(last_target, _)| last_target.eq_ignore_ascii_case(target))
+                {
+                    self.last_active = None;
+                    Some(state)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_typing_state_tag() {
+        assert_eq!(TypingState::from_tag("active"), Some(TypingState::Active));
+        assert_eq!(TypingState::from_tag("done"), Some(TypingState::Done));
+        assert_eq!(TypingState::from_tag("other"), None);
+        assert_eq!(TypingState::Paused.as_tag(), "paused");
+    }
+
+    #[test]
+    fn test_tracker() {
+        let mut tracker = TypingTracker::default();
+        let start = Instant::now();
+        tracker.set("#rust", "alice", TypingState::Active, start);
+        tracker.set("#rust", "bob", TypingState::Paused, start);
+        tracker.set("#other", "carol", TypingState::Active, start);
+        assert_eq!(tracker.get_typing("#Rust", start), vec!["alice"]);
+
+        let later = start + Duration::from_secs(ACTIVE_EXPIRATION);
+        assert!(tracker.get_typing("#rust", later).is_empty());
+
+        tracker.set("#rust", "alice", TypingState::Active, later);
+        tracker.set("#rust", "alice", TypingState::Done, later);
+        assert!(tracker.get_typing("#rust", later).is_empty());
+
+        tracker.remove("carol");
+        assert!(tracker.get_typing("#other", start).is_empty());
+    }
+
+    #[test]
+    fn test_throttle() {
+        let mut throttle = TypingThrottle::default();
+        let start = Instant::now();
+        assert_eq!(throttle.update("#rust", TypingState::Done, start), None);
+        assert_eq!(
+            throttle.update("#rust", TypingState::Active, start),
+            Some(TypingState::Active)
+        );
+        assert_eq!(
+            throttle.update("#rust", TypingState::Active, start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            throttle.update(
+                "#other",
+                TypingState::Active,
+                start + Duration::from_secs(1)
+            ),
+            Some(TypingState::Active)
+        );
+        assert_eq!(
+            throttle.update("#other", TypingState::Done, start + Duration::from_secs(2)),
+            Some(TypingState::Done)
+        );
+        assert_eq!(
+            throttle.update("#other", TypingState::Done, start + Duration::from_secs(2)),
+            None
+        );
+    }
+}
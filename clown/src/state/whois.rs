@@ -0,0 +1,264 @@
+use std::time::{Duration, Instant};
+
+/// A reply is shown again without a new WHOIS for 1 minute
+const CACHE_DURATION: Duration = Duration::from_secs(60);
+/// A request without reply is not sent again for 10 seconds
+const REQUEST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Where the reply of a WHOIS is shown
+#[derive(Debug, Clone, PartialEq)]
+pub enum WhoisTarget {
+    /// The buffer of the command, the server buffer when None
+    Buffer(Option<String>),
+    ToolTip,
+}
+
+/// Replies of WHOIS or WHOWAS gathered until their end
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WhoisInfo {
+    pub nick: String,
+    pub user_host: Option<String>,
+    pub realname: Option<String>,
+    pub server: Option<String>,
+    pub is_operator: bool,
+    pub idle: Option<u64>,
+    pub signon: Option<u64>,
+    pub channels: Vec<String>,
+    pub account: Option<String>,
+    pub actual_host: Option<String>,
+    pub is_secure: bool,
+    pub is_whowas: bool,
+}
+
+/// 2 days 3 hours, only the two largest units are kept
+pub fn humanize_duration(seconds: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [
+        (86400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    let parts = UNITS
+        .iter()
+        .scan(seconds, |rest, (size, name)| {
+            let count = *rest / size;
+            *rest %= size;
+            Some((count, name))
+        })
+        .skip_while(|(count, _)| *count == 0)
+        .take(2)
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, name)| format!("{count} {name}{}", if count > 1 { "s" } else { "" }))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn format_date(timestamp: u64) -> Option<String> {
+    let timestamp = i64::try_from(timestamp).ok()?;
+    chrono::DateTime::from_timestamp(timestamp, 0).map(|v| {
+        v.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    })
+}
+
+impl WhoisInfo {
+    /// One information per line, the first one is about the nick
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![match (&self.user_host, self.is_whowas) {
+            (Some(user_host), false) => format!("{} ({user_host})", self.nick),
+            (Some(user_host), true) => format!("{} was {user_host}", self.nick),
+            (None, _) => self.nick.clone(),
+        }];
+        if let Some(realname) = &self.realname {
+            lines.push(format!("Name: {realname}"));
+        }
+        if let Some(account) = &self.account {
+            lines.push(format!("Account: {account}"));
+        }
+        if let Some(server) = &self.server {
+            lines.push(format!("Server: {server}"));
+        }
+        if let Some(actual_host) = &self.actual_host {
+            lines.push(format!("Actual host: {actual_host}"));
+        }
+        if self.is_secure {
+            lines.push("Secure connection".to_string());
+        }
+        if self.is_operator {
+            lines.push("IRC operator".to_string());
+        }
+        if !self.channels.is_empty() {
+            lines.push(format!("Channels: {}", self.channels.join(" ")));
+        }
+        if let Some(idle) = self.idle {
+            lines.push(format!("Idle: {}", humanize_duration(idle)));
+        }
+        if let Some(signon) = self.signon.and_then(format_date) {
+            lines.push(format!("Signed on: {signon}"));
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WhoisCollector {
+    requests: Vec<(String, WhoisTarget, Instant)>,
+    /// By lowercase nick
+    replies: ahash::AHashMap<String, WhoisInfo>,
+    /// The complete replies of WHOIS by lowercase nick, with their time
+    cache: ahash::AHashMap<String, (Instant, WhoisInfo)>,
+}
+
+impl WhoisCollector {
+    pub fn request(&mut self, nick: &str, target: WhoisTarget) {
+        self.requests
+            .retain(|(v, _, _)| !v.eq_ignore_ascii_case(nick));
+        self.requests
+            .push((nick.to_string(), target, Instant::now()));
+    }
+
+    /// A request for the nick is waiting for its reply
+    pub fn is_requested(&self, nick: &str, now: Instant) -> bool {
+        self.requests.iter().any(|(v, _, requested)| {
+            v.eq_ignore_ascii_case(nick) && now.duration_since(*requested) < REQUEST_INTERVAL
+        })
+    }
+
+    /// The last reply of WHOIS for the nick, if it is recent
+    pub fn cached(&self, nick: &str, now: Instant) -> Option<&WhoisInfo> {
+        self.cache
+            .get(&nick.to_lowercase())
+            .filter(|(received, _)| now.duration_since(*received) < CACHE_DURATION)
+            .map(|(_, info)| info)
+    }
+
+    /// The reply being received for the nick
+    pub fn reply(&mut self, nick: &str) -> &mut WhoisInfo {
+        self.replies
+            .entry(nick.to_lowercase())
+            .or_insert_with(|| WhoisInfo {
+                nick: nick.to_string(),
+                ..Default::default()
+            })
+    }
+
+    /// The complete reply and where to show it, None for a nick which does not exist
+    pub fn finish(&mut self, nick: &str) -> Option<(WhoisInfo, WhoisTarget)> {
+        let target = match self
+            .requests
+            .iter()
+            .position(|(v, _, _)| v.eq_ignore_ascii_case(nick))
+        {
+            Some(position) => self.requests.remove(position).1,
+            None => WhoisTarget::Buffer(None),
+        };
+        let info = self.replies.remove(&nick.to_lowercase())?;
+        if !info.is_whowas {
+            let now = Instant::now();
+            self.cache
+                .retain(|_, (received, _)| now.duration_since(*received) < CACHE_DURATION);
+            self.cache.insert(nick.to_lowercase(), (now, info.clone()));
+        }
+        Some((info, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize_duration() {
+        assert_eq!(humanize_duration(0), "0 seconds");
+        assert_eq!(humanize_duration(1), "1 second");
+        assert_eq!(humanize_duration(125), "2 minutes 5 seconds");
+        assert_eq!(humanize_duration(3600), "1 hour");
+        assert_eq!(
+            humanize_duration(2 * 86400 + 3 * 3600 + 59),
+            "2 days 3 hours"
+        );
+        assert_eq!(humanize_duration(86400 + 30), "1 day");
+    }
+
+    #[test]
+    fn test_lines() {
+        let info = WhoisInfo {
+            nick: "alice".to_string(),
+            user_host: Some("~al@host".to_string()),
+            realname: Some("Alice".to_string()),
+            account: Some("alice".to_string()),
+            is_secure: true,
+            channels: vec!["@#rust".to_string(), "#clown".to_string()],
+            idle: Some(65),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.lines(),
+            vec![
+                "alice (~al@host)",
+                "Name: Alice",
+                "Account: alice",
+                "Secure connection",
+                "Channels: @#rust #clown",
+                "Idle: 1 minute 5 seconds",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collector() {
+        let mut collector = WhoisCollector::default();
+        collector.request("alice", WhoisTarget::ToolTip);
+        collector.reply("alice").is_secure = true;
+        collector.reply("Alice").account = Some("alice".to_string());
+        let (info, target) = collector.finish("ALICE").unwrap();
+        assert!(info.is_secure);
+        assert_eq!(info.account.as_deref(), Some("alice"));
+        assert_eq!(target, WhoisTarget::ToolTip);
+
+        //No reply for an unknown nick, the request is dropped
+        collector.request("bob", WhoisTarget::Buffer(Some("#rust".to_string())));
+        assert!(collector.finish("bob").is_none());
+        collector.reply("bob");
+        assert_eq!(
+            collector.finish("bob").map(|v| v.1),
+            Some(WhoisTarget::Buffer(None))
+        );
+    }
+
+    #[test]
+    fn test_cache_and_request_interval() {
+        let mut collector = WhoisCollector::default();
+        let now = Instant::now();
+        assert!(!collector.is_requested("alice", now));
+        collector.request("alice", WhoisTarget::ToolTip);
+        assert!(collector.is_requested("Alice", Instant::now()));
+        assert!(!collector.is_requested("alice", Instant::now() + REQUEST_INTERVAL));
+        assert!(collector.cached("alice", Instant::now()).is_none());
+
+        collector.reply("alice").account = Some("alice".to_string());
+        collector.finish("alice");
+        assert!(!collector.is_requested("alice", Instant::now()));
+        assert_eq!(
+            collector
+                .cached("ALICE", Instant::now())
+                .and_then(|v| v.account.as_deref()),
+            Some("alice")
+        );
+        assert!(
+            collector
+                .cached("alice", Instant::now() + CACHE_DURATION)
+                .is_none()
+        );
+
+        //A WHOWAS does not describe a present user
+        collector.reply("bob").is_whowas = true;
+        collector.finish("bob");
+        assert!(collector.cached("bob", Instant::now()).is_none());
+    }
+}